@@ -59,6 +59,7 @@ class ProofResponse(BaseModel):
     confidence: float
     timestamp: int
     model_id: str
+    model_hash: str
     is_valid: bool
     
 class PredictionRequest(BaseModel):
@@ -179,6 +180,10 @@ async def generate_proof(request: ProofRequest):
         # Verify proof
         is_valid = proof_of_ai.verify_proof(request.block_data.dict(), proof)
         
+        # Identify the model the way the chain does
+        proof["model_id"] = model_id
+        proof["model_hash"] = model["hash"]
+        
         # Add validation result
         proof["is_valid"] = is_valid
         
@@ -0,0 +1,183 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::discovery::NodeRecord;
+use crate::utils::{now, JsonFile, Snapshot};
+
+/// Time after which a peer that has not been seen is forgotten
+pub const MAX_PEER_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Failed dials in a row after which a peer is forgotten
+pub const MAX_RECENT_FAILURES: u32 = 3;
+
+/// Most peers remembered
+pub const MAX_ENTRIES: usize = 1000;
+
+/// Peer this node has connected to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerEntry {
+    /// Hex-encoded node public key
+    pub public_key: String,
+    /// Address the peer can be dialed at, as host:port
+    pub address: String,
+    /// Services the peer announced
+    pub services: u64,
+    /// Unix time in seconds of the last connection to the peer
+    pub last_seen: u64,
+    /// Number of connections made to the peer
+    pub successes: u32,
+    /// Number of failed dials of the peer
+    pub failures: u32,
+    /// Number of failed dials since the peer was last seen
+    pub recent_failures: u32,
+}
+
+impl PeerEntry {
+    /// Check whether the peer looks gone for good
+    fn is_dead(&self, now: u64) -> bool {
+        self.recent_failures >= MAX_RECENT_FAILURES || now.saturating_sub(self.last_seen) > MAX_PEER_AGE.as_secs()
+    }
+}
+
+/// Peers this node has connected to, so it can rejoin the network through them
+///
+/// Entries are saved to a file, when given one, so they outlive restarts. Peers
+/// that keep failing or have not been seen for a long time are forgotten.
+pub struct AddressBook {
+    /// Entries by node public key
+    entries: HashMap<String, PeerEntry>,
+    /// File the entries are saved to
+    file: Option<JsonFile>,
+    /// Whether the entries changed since they were last saved
+    unsaved: bool,
+}
+
+impl AddressBook {
+    /// Create an address book kept in memory only
+    pub fn new() -> Self {
+        AddressBook {
+            entries: HashMap::new(),
+            file: None,
+            unsaved: false,
+        }
+    }
+    
+    /// Open an address book saving to a file, loading the peers still worth dialing
+    ///
+    /// A file that cannot be read is logged and replaced, as the node can rejoin
+    /// the network through its bootnodes.
+    pub fn open(path: &Path) -> Self {
+        let file = JsonFile::new(path, "peer addresses");
+        let now = now();
+        let entries = file
+            .load::<PeerEntry>()
+            .into_iter()
+            .filter(|entry| !entry.is_dead(now))
+            .map(|entry| (entry.public_key.clone(), entry))
+            .collect();
+        
+        AddressBook {
+            entries,
+            file: Some(file),
+            unsaved: false,
+        }
+    }
+    
+    /// Remember a successful connection to a peer
+    pub fn record_success(&mut self, record: &NodeRecord, services: u64) {
+        let entry = self.entries.entry(record.public_key.clone()).or_insert_with(|| PeerEntry {
+            public_key: record.public_key.clone(),
+            address: record.address.clone(),
+            services,
+            last_seen: 0,
+            successes: 0,
+            failures: 0,
+            recent_failures: 0,
+        });
+        entry.address = record.address.clone();
+        entry.services = services;
+        entry.last_seen = now();
+        entry.successes = entry.successes.saturating_add(1);
+        entry.recent_failures = 0;
+        
+        // Make room by forgetting the peers least worth dialing
+        if self.entries.len() > MAX_ENTRIES {
+            for entry in self.best().into_iter().skip(MAX_ENTRIES) {
+                self.entries.remove(&entry.public_key);
+            }
+        }
+        
+        self.unsaved = true;
+    }
+    
+    /// Remember a failed dial of an address, forgetting the peers there if they look gone
+    ///
+    /// Returns whether a known peer was at the address.
+    pub fn record_failure(&mut self, address: &str) -> bool {
+        let mut known = false;
+        for entry in self.entries.values_mut().filter(|entry| entry.address == address) {
+            entry.failures = entry.failures.saturating_add(1);
+            entry.recent_failures = entry.recent_failures.saturating_add(1);
+            known = true;
+        }
+        
+        if known {
+            let now = now();
+            self.entries.retain(|_, entry| !entry.is_dead(now));
+            self.unsaved = true;
+        }
+        
+        known
+    }
+    
+    /// Get the entry of a peer
+    pub fn get(&self, public_key: &str) -> Option<&PeerEntry> {
+        self.entries.get(public_key)
+    }
+    
+    /// Get the peers worth dialing, best first
+    ///
+    /// Peers that have not failed since they were last seen come first, then those
+    /// connected to most often, then those seen most recently.
+    pub fn best(&self) -> Vec<PeerEntry> {
+        let now = now();
+        let mut entries: Vec<PeerEntry> = self.entries.values().filter(|entry| !entry.is_dead(now)).cloned().collect();
+        entries.sort_by_key(|entry| (entry.recent_failures, Reverse(entry.successes), Reverse(entry.last_seen)));
+        entries
+    }
+    
+    /// Get the number of remembered peers
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Check whether no peer is remembered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    
+    /// Take the peers worth dialing to save if they changed, so the file is written outside any lock
+    pub fn take_unsaved(&mut self) -> Option<Snapshot<PeerEntry>> {
+        if !std::mem::take(&mut self.unsaved) {
+            return None;
+        }
+        
+        let best = self.best();
+        Some(self.file.as_mut()?.snapshot(best))
+    }
+    
+    /// Save the peers worth dialing if they changed, keeping the node running if the file cannot be written
+    pub fn save(&mut self) {
+        if let Some(snapshot) = self.take_unsaved() {
+            snapshot.save();
+        }
+    }
+}
+
+impl Default for AddressBook {
+    fn default() -> Self {
+        AddressBook::new()
+    }
+}
@@ -0,0 +1,227 @@
+use std::time::Duration;
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, StatusCode};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use log::warn;
+
+use crate::block::Block;
+
+/// Error returned by the AI service client
+pub type AiClientError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Block fields the AI service computes proofs over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockData {
+    /// Block height
+    pub height: u64,
+    /// Hash of the previous block
+    pub prev_hash: String,
+    /// Block timestamp
+    pub timestamp: u64,
+    /// Transactions included in the block
+    pub transactions: Vec<Value>,
+}
+
+impl BlockData {
+    /// Extract the proof input of a block
+    pub fn from_block(block: &Block) -> Self {
+        BlockData {
+            height: block.header.height,
+            prev_hash: block.header.prev_hash.clone(),
+            timestamp: block.header.timestamp,
+            transactions: block
+                .transactions
+                .iter()
+                .map(|tx| serde_json::to_value(tx).unwrap())
+                .collect(),
+        }
+    }
+}
+
+/// Proof returned by the AI service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofResponse {
+    /// Nonce satisfying the model's difficulty
+    pub nonce: u64,
+    /// Hash of the block data with the nonce
+    pub hash: String,
+    /// Model confidence for the nonce
+    pub confidence: f64,
+    /// Time the proof was generated
+    pub timestamp: u64,
+    /// Model used to generate the proof
+    pub model_id: String,
+    /// SHA-256 of the model file (hex)
+    pub model_hash: String,
+    /// Whether the service could verify its own proof
+    #[serde(default)]
+    pub is_valid: bool,
+}
+
+/// Predictions returned by the AI service
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PredictionResponse {
+    /// Model that made the predictions
+    pub model_id: String,
+    /// One prediction per input
+    pub predictions: Vec<Value>,
+    /// Time the model took, in seconds
+    pub inference_time: f64,
+    /// Time the predictions were made
+    pub timestamp: u64,
+}
+
+/// Timeout and retry settings of the AI service client
+#[derive(Debug, Clone)]
+pub struct AiClientConfig {
+    /// Time allowed for a single request, including reading the response
+    pub timeout: Duration,
+    /// Number of times a failed request is retried
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub retry_backoff: Duration,
+}
+
+impl Default for AiClientConfig {
+    fn default() -> Self {
+        AiClientConfig {
+            timeout: Duration::from_secs(10),
+            max_retries: 3,
+            retry_backoff: Duration::from_millis(200),
+        }
+    }
+}
+
+/// HTTP client for the consensus API of the AI service
+#[derive(Debug, Clone)]
+pub struct AiClient {
+    /// Base URL of the AI service
+    endpoint: String,
+    /// Timeout and retry settings
+    config: AiClientConfig,
+    /// Underlying HTTP client
+    client: Client<HttpConnector>,
+}
+
+impl AiClient {
+    /// Create a client for the AI service at a base URL
+    pub fn new(endpoint: &str) -> Self {
+        Self::with_config(endpoint, AiClientConfig::default())
+    }
+    
+    /// Create a client with custom timeout and retry settings
+    pub fn with_config(endpoint: &str, config: AiClientConfig) -> Self {
+        AiClient {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            config,
+            client: Client::new(),
+        }
+    }
+    
+    /// Get the base URL of the AI service
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+    
+    /// Ask the AI service to generate a proof for a block
+    pub async fn generate_proof(&self, block_data: &BlockData, model_id: Option<&str>) -> Result<ProofResponse, AiClientError> {
+        let body = json!({
+            "block_data": block_data,
+            "model_id": model_id,
+        });
+        
+        let response = self.post("/consensus/generate_proof", &body).await?;
+        
+        Ok(serde_json::from_value(response)?)
+    }
+    
+    /// Ask the AI service whether a proof is valid for a block
+    pub async fn verify_proof(&self, block_data: &BlockData, proof: &ProofResponse) -> Result<bool, AiClientError> {
+        let body = json!({
+            "block_data": block_data,
+            "proof": proof,
+        });
+        
+        let response = self.post("/consensus/verify_proof", &body).await?;
+        
+        response
+            .get("is_valid")
+            .and_then(Value::as_bool)
+            .ok_or_else(|| "AI service response is missing is_valid".into())
+    }
+    
+    /// Run a model of the AI service on a batch of inputs
+    pub async fn predict(&self, model_id: &str, input_data: &Value) -> Result<PredictionResponse, AiClientError> {
+        let body = json!({
+            "model_id": model_id,
+            "input_data": input_data,
+        });
+        
+        let response = self.post("/models/predict", &body).await?;
+        
+        Ok(serde_json::from_value(response)?)
+    }
+    
+    /// Post a JSON body, retrying timeouts, connection failures and server errors
+    async fn post(&self, path: &str, body: &Value) -> Result<Value, AiClientError> {
+        let url = format!("{}{}", self.endpoint, path);
+        let body = serde_json::to_vec(body)?;
+        let mut backoff = self.config.retry_backoff;
+        let mut attempt = 0;
+        
+        loop {
+            let error = match tokio::time::timeout(self.config.timeout, self.send(&url, body.clone())).await {
+                Ok(Ok(response)) => return Ok(response),
+                Ok(Err(Failure::Permanent(e))) => return Err(e),
+                Ok(Err(Failure::Transient(e))) => e,
+                Err(_) => format!("Request to {} timed out after {:?}", url, self.config.timeout).into(),
+            };
+            
+            if attempt >= self.config.max_retries {
+                return Err(format!("AI service request failed after {} attempts: {}", attempt + 1, error).into());
+            }
+            
+            warn!("AI service request to {} failed, retrying: {}", url, error);
+            
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+    
+    /// Send a single request and parse the JSON response
+    async fn send(&self, url: &str, body: Vec<u8>) -> Result<Value, Failure> {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(url)
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .map_err(|e| Failure::Permanent(e.into()))?;
+        
+        let response = self.client.request(request).await.map_err(|e| Failure::Transient(e.into()))?;
+        let status = response.status();
+        let bytes = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|e| Failure::Transient(e.into()))?;
+        
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(Failure::Transient(format!("AI service returned {}", status).into()));
+        }
+        
+        if !status.is_success() {
+            let detail = String::from_utf8_lossy(&bytes);
+            return Err(Failure::Permanent(format!("AI service returned {}: {}", status, detail).into()));
+        }
+        
+        serde_json::from_slice(&bytes).map_err(|e| Failure::Permanent(e.into()))
+    }
+}
+
+/// Failed request, split by whether retrying can help
+enum Failure {
+    /// Network or server failure that may succeed on retry
+    Transient(AiClientError),
+    /// Rejected request that will fail again
+    Permanent(AiClientError),
+}
@@ -0,0 +1,195 @@
+use std::path::Path;
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+use serde_json::Value;
+use sha2::{Sha256, Digest};
+
+use crate::ai_client::BlockData;
+use crate::ai_proof::MAX_CONFIDENCE_BPS;
+
+/// Fractional bits of the fixed-point numbers models compute with
+pub const FRAC_BITS: u32 = 32;
+
+/// Fixed-point representation of 1.0
+pub const ONE: i64 = 1 << FRAC_BITS;
+
+/// Length of the feature vector extracted from a block
+pub const FEATURE_COUNT: usize = 10;
+
+/// Relative difference allowed between a feature and the service's `float32` value, on top of 2^-32
+pub const FEATURE_TOLERANCE: f64 = f32::EPSILON as f64;
+
+/// Activation applied to the outputs of a layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum Activation {
+    /// Outputs are passed through unchanged
+    Linear,
+    /// Negative outputs become zero
+    Relu,
+    /// Piecewise linear sigmoid, clamp(x / 5 + 1/2, 0, 1)
+    HardSigmoid,
+}
+
+/// Fully connected layer with fixed-point weights
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct DenseLayer {
+    /// Weights by output, then input
+    pub weights: Vec<Vec<i64>>,
+    /// Bias of each output
+    pub biases: Vec<i64>,
+    /// Activation applied to the outputs
+    pub activation: Activation,
+}
+
+/// Quantized PoAI model evaluated identically by every validator
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct QuantizedModel {
+    /// Layers applied in order to the block features
+    pub layers: Vec<DenseLayer>,
+}
+
+impl QuantizedModel {
+    /// Create a model, checking the layer shapes chain together
+    pub fn new(layers: Vec<DenseLayer>) -> Result<Self, Box<dyn std::error::Error>> {
+        let model = QuantizedModel { layers };
+        model.check_shape()?;
+        Ok(model)
+    }
+    
+    /// Load a model from a file holding its SCALE encoding
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+    
+    /// Decode a model, rejecting trailing or non-canonical bytes
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let model = Self::decode_all(&mut &data[..]).map_err(|e| format!("Invalid model encoding: {}", e))?;
+        
+        if model.encode() != data {
+            return Err("Model is not canonically encoded".into());
+        }
+        
+        model.check_shape()?;
+        
+        Ok(model)
+    }
+    
+    /// Encode the model canonically, as stored in model files
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+    
+    /// SHA-256 of the encoded model, matching the hash of its model file
+    pub fn hash(&self) -> [u8; 32] {
+        Sha256::digest(self.to_bytes()).into()
+    }
+    
+    /// Check each layer consumes the outputs of the previous one
+    fn check_shape(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.layers.is_empty() {
+            return Err("Model has no layers".into());
+        }
+        
+        let mut inputs = FEATURE_COUNT;
+        
+        for (index, layer) in self.layers.iter().enumerate() {
+            if layer.weights.is_empty() || layer.weights.len() != layer.biases.len() {
+                return Err(format!("Layer {} has mismatched weights and biases", index).into());
+            }
+            
+            if layer.weights.iter().any(|row| row.len() != inputs) {
+                return Err(format!("Layer {} expects {} inputs", index, inputs).into());
+            }
+            
+            inputs = layer.weights.len();
+        }
+        
+        Ok(())
+    }
+    
+    /// Run the layers on an input vector
+    pub fn forward(&self, input: &[i64]) -> Vec<i64> {
+        let mut values = input.to_vec();
+        
+        for layer in &self.layers {
+            values = layer
+                .weights
+                .iter()
+                .zip(&layer.biases)
+                .map(|(row, bias)| {
+                    let sum = row
+                        .iter()
+                        .zip(&values)
+                        .fold(0i128, |sum, (w, x)| sum.saturating_add(i128::from(*w) * i128::from(*x)));
+                    let output = saturate(sum >> FRAC_BITS).saturating_add(*bias);
+                    activate(layer.activation, output)
+                })
+                .collect();
+        }
+        
+        values
+    }
+    
+    /// Evaluate the model's confidence in a block for a nonce, in basis points
+    pub fn evaluate(&self, block_data: &BlockData, nonce: u64) -> Result<u16, Box<dyn std::error::Error>> {
+        let features = extract_features(block_data)?;
+        let confidence = self.forward(&features)[0].clamp(0, ONE);
+        
+        // Nonces scale the confidence by (1 + (nonce % 100) / 100), as the AI service does in floats
+        let adjusted = (i128::from(confidence) * i128::from(100 + nonce % 100) / 100).min(i128::from(ONE));
+        let bps = (adjusted * i128::from(MAX_CONFIDENCE_BPS)) >> FRAC_BITS;
+        
+        Ok(bps as u16)
+    }
+}
+
+/// Extract the model input from block data, in the layout of `_prepare_input` of the AI service
+///
+/// Features are fixed-point and rounded down, so every validator computes the same bits.
+/// The service rounds the same ratios to `float32`, so each feature is within
+/// `FEATURE_TOLERANCE` of the service's value: one `f32::EPSILON` relative plus 2^-32.
+/// Transactions without an amount, or with a null one, count as zero; those with any
+/// other amount than an unsigned integer are rejected.
+pub fn extract_features(block_data: &BlockData) -> Result<[i64; FEATURE_COUNT], Box<dyn std::error::Error>> {
+    let mut features = [0; FEATURE_COUNT];
+    
+    let mut volume: u128 = 0;
+    for (index, tx) in block_data.transactions.iter().enumerate() {
+        let amount = match tx.get("amount") {
+            None | Some(Value::Null) => 0,
+            Some(amount) => amount.as_u64().ok_or_else(|| format!("Transaction {} has an invalid amount: {}", index, amount))?,
+        };
+        volume += u128::from(amount);
+    }
+    
+    // The first 8 bytes of the previous hash as a fraction of 2^64
+    let prefix = block_data.prev_hash.get(..16).ok_or("Previous hash is too short")?;
+    let prev_hash_num = u64::from_str_radix(prefix, 16).map_err(|e| format!("Invalid previous hash: {}", e))?;
+    
+    features[0] = ratio(u128::from(block_data.height), 1_000_000);
+    features[1] = ratio(u128::from(block_data.timestamp), 1_000_000_000);
+    features[2] = ratio(block_data.transactions.len() as u128, 1000);
+    features[3] = ratio(volume, 1_000_000);
+    features[4] = (prev_hash_num >> (64 - FRAC_BITS)) as i64;
+    
+    Ok(features)
+}
+
+/// Fixed-point value of num / den, rounded down
+fn ratio(num: u128, den: u128) -> i64 {
+    let value = num.saturating_mul(1 << FRAC_BITS) / den;
+    value.min(i64::MAX as u128) as i64
+}
+
+/// Clamp a wide intermediate into the fixed-point range
+fn saturate(value: i128) -> i64 {
+    value.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+}
+
+/// Apply an activation to a fixed-point value
+fn activate(activation: Activation, value: i64) -> i64 {
+    match activation {
+        Activation::Linear => value,
+        Activation::Relu => value.max(0),
+        Activation::HardSigmoid => (value.div_euclid(5).saturating_add(ONE / 2)).clamp(0, ONE),
+    }
+}
@@ -0,0 +1,104 @@
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+use sha2::{Sha256, Digest};
+
+use crate::block::BlockHeader;
+use crate::crypto::{KeyPair, Signature, SignatureType};
+
+/// Confidence of a certain model output, in basis points
+pub const MAX_CONFIDENCE_BPS: u16 = 10000;
+
+/// Proof of AI carried by a block, SCALE-encoded in `Block::ai_proof`
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct AIProof {
+    /// Model that evaluated the block
+    pub model_id: String,
+    /// SHA-256 of the model file
+    pub model_hash: [u8; 32],
+    /// Nonce found by the model
+    pub nonce: u64,
+    /// Model confidence for the nonce, in basis points
+    pub confidence_bps: u16,
+    /// Hash committing the proof to the block header
+    pub proof_hash: [u8; 32],
+    /// Producer's Ed25519 signature over the proof hash
+    pub validator_signature: Vec<u8>,
+}
+
+impl AIProof {
+    /// Create an unsigned proof for a block header
+    pub fn new(header: &BlockHeader, model_id: String, model_hash: [u8; 32], nonce: u64, confidence_bps: u16) -> Self {
+        let proof_hash = Self::commitment(header, &model_id, &model_hash, nonce, confidence_bps);
+        
+        AIProof {
+            model_id,
+            model_hash,
+            nonce,
+            confidence_bps,
+            proof_hash,
+            validator_signature: Vec::new(),
+        }
+    }
+    
+    /// Calculate the hash binding a model's result to a block header
+    pub fn commitment(header: &BlockHeader, model_id: &str, model_hash: &[u8; 32], nonce: u64, confidence_bps: u16) -> [u8; 32] {
+        let preimage = (header.hash(), model_id, model_hash, nonce, confidence_bps).encode();
+        Sha256::digest(&preimage).into()
+    }
+    
+    /// Sign the proof hash with the producer's key
+    pub fn sign(&mut self, keypair: &KeyPair) {
+        self.validator_signature = keypair.sign(&self.proof_hash).bytes;
+    }
+    
+    /// Encode the proof canonically
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+    
+    /// Decode a proof, rejecting trailing or non-canonical bytes
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let proof = Self::decode_all(&mut &data[..]).map_err(|e| format!("Invalid AI proof encoding: {}", e))?;
+        
+        if proof.encode() != data {
+            return Err("AI proof is not canonically encoded".into());
+        }
+        
+        if proof.confidence_bps > MAX_CONFIDENCE_BPS {
+            return Err(format!("AI proof confidence {} exceeds {} bps", proof.confidence_bps, MAX_CONFIDENCE_BPS).into());
+        }
+        
+        Ok(proof)
+    }
+    
+    /// Model confidence as a fraction
+    pub fn confidence(&self) -> f64 {
+        f64::from(self.confidence_bps) / f64::from(MAX_CONFIDENCE_BPS)
+    }
+    
+    /// Verify the proof commits to a header, meets the confidence threshold and is signed by the producer
+    pub fn verify(&self, header: &BlockHeader, public_key: &str, min_confidence_bps: u16) -> Result<(), Box<dyn std::error::Error>> {
+        let expected = Self::commitment(header, &self.model_id, &self.model_hash, self.nonce, self.confidence_bps);
+        
+        if expected != self.proof_hash {
+            return Err("AI proof hash does not commit to the block header".into());
+        }
+        
+        if self.confidence_bps < min_confidence_bps {
+            return Err(format!(
+                "AI proof confidence {} bps is below the threshold of {} bps",
+                self.confidence_bps, min_confidence_bps
+            ).into());
+        }
+        
+        let signature = Signature {
+            bytes: self.validator_signature.clone(),
+            sig_type: SignatureType::Ed25519,
+        };
+        
+        if !signature.verify(public_key, &self.proof_hash)? {
+            return Err("Invalid validator signature on AI proof".into());
+        }
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use crate::crypto::KeyPair;
+use crate::epoch::ValidatorSetEntry;
+
+/// Domain separating RANDAO secrets from other signatures made with a validator key
+const SECRET_DOMAIN: &str = "hypernova-randao";
+
+/// Hash byte strings together into a hex digest
+fn hash_hex(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Decode a 32-byte hex value
+fn decode_32(value: &str, what: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    match hex::decode(value) {
+        Ok(bytes) if bytes.len() == 32 => Ok(bytes),
+        _ => Err(format!("{} must be a 32-byte hex string", what).into()),
+    }
+}
+
+/// Get the beacon output before any block, derived from the chain id
+pub fn genesis_randomness(chain_id: &str) -> String {
+    hash_hex(&[b"hypernova-beacon:", chain_id.as_bytes()])
+}
+
+/// Derive the secret a validator commits to at a height
+///
+/// Ed25519 signatures are deterministic, so the validator can recompute the
+/// secret from its key when it has to reveal it.
+pub fn derive_secret(keypair: &KeyPair, height: u64) -> String {
+    let signature = keypair.sign(format!("{}:{}", SECRET_DOMAIN, height).as_bytes());
+    hash_hex(&[&signature.bytes])
+}
+
+/// Get the commitment to a secret
+pub fn commitment(secret: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(hash_hex(&[&decode_32(secret, "RANDAO secret")?]))
+}
+
+/// Mix a revealed secret into the beacon output
+pub fn mix(randomness: &str, reveal: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(hash_hex(&[&decode_32(randomness, "Randomness")?, &decode_32(reveal, "RANDAO reveal")?]))
+}
+
+/// Shuffle items deterministically from a seed (Fisher-Yates)
+pub fn shuffle<T>(items: &mut [T], seed: &str) {
+    for i in (1..items.len()).rev() {
+        let digest = Sha256::new()
+            .chain_update(seed.as_bytes())
+            .chain_update((i as u64).to_be_bytes())
+            .finalize();
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&digest[..8]);
+        
+        let j = (u64::from_be_bytes(word) % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Select a committee of validators from a seed
+pub fn select_committee(validators: &[ValidatorSetEntry], seed: &str, size: usize) -> Vec<ValidatorSetEntry> {
+    let mut committee = validators.to_vec();
+    shuffle(&mut committee, seed);
+    committee.truncate(size);
+    committee
+}
+
+/// Commitment a validator must reveal the next time it produces a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RandaoCommitment {
+    /// Hash of the secret (hex)
+    pub hash: String,
+    /// Height of the block the commitment was made in
+    pub height: u64,
+}
+
+/// RANDAO commit-reveal beacon mixed by block producers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RandomBeacon {
+    /// Output after the latest block (hex)
+    randomness: String,
+    /// Outstanding commitments by validator address
+    commitments: BTreeMap<String, RandaoCommitment>,
+}
+
+impl RandomBeacon {
+    /// Create the beacon of a new chain
+    pub fn genesis(chain_id: &str) -> Self {
+        RandomBeacon {
+            randomness: genesis_randomness(chain_id),
+            commitments: BTreeMap::new(),
+        }
+    }
+    
+    /// Get the output after the latest block
+    pub fn randomness(&self) -> &str {
+        &self.randomness
+    }
+    
+    /// Get a validator's outstanding commitment
+    pub fn commitment(&self, validator: &str) -> Option<&RandaoCommitment> {
+        self.commitments.get(validator)
+    }
+    
+    /// Get the output after revealing a secret, without changing the beacon
+    pub fn preview(&self, reveal: Option<&str>) -> Result<String, Box<dyn std::error::Error>> {
+        match reveal {
+            Some(reveal) => mix(&self.randomness, reveal),
+            None => Ok(self.randomness.clone()),
+        }
+    }
+    
+    /// Apply a block producer's reveal and new commitment
+    ///
+    /// A producer with an outstanding commitment must reveal its secret, which is
+    /// mixed into the output. Producers without one may only commit.
+    pub fn apply(
+        &mut self,
+        producer: &str,
+        reveal: Option<&str>,
+        new_commitment: Option<&str>,
+        height: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match (self.commitments.get(producer), reveal) {
+            (Some(outstanding), Some(reveal)) => {
+                if commitment(reveal)? != outstanding.hash {
+                    return Err(format!("RANDAO reveal of {} does not match its commitment", producer).into());
+                }
+                
+                self.randomness = mix(&self.randomness, reveal)?;
+                self.commitments.remove(producer);
+            }
+            (Some(outstanding), None) => {
+                return Err(format!(
+                    "{} must reveal the secret it committed to at height {}",
+                    producer, outstanding.height
+                ).into());
+            }
+            (None, Some(_)) => return Err(format!("{} has no RANDAO commitment to reveal", producer).into()),
+            (None, None) => {}
+        }
+        
+        if let Some(hash) = new_commitment {
+            decode_32(hash, "RANDAO commitment")?;
+            self.commitments.insert(producer.to_string(), RandaoCommitment {
+                hash: hash.to_string(),
+                height,
+            });
+        }
+        
+        Ok(())
+    }
+}
@@ -3,7 +3,8 @@ use sha2::{Sha256, Digest};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::transaction::Transaction;
-use crate::crypto::Signature;
+use crate::crypto::{KeyPair, Signature};
+use crate::evidence::DoubleSignEvidence;
 
 /// Block structure for the HyperNova blockchain
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,8 +15,14 @@ pub struct Block {
     pub transactions: Vec<Transaction>,
     /// Validator signature
     pub validator_signature: Option<Signature>,
-    /// AI proof data (for PoAI consensus)
+    /// Quantum-resistant signature of the producer
+    #[serde(default)]
+    pub quantum_signature: Option<Signature>,
+    /// SCALE-encoded `AIProof` (for PoAI consensus)
     pub ai_proof: Option<Vec<u8>>,
+    /// Evidence of validator misbehaviour
+    #[serde(default)]
+    pub evidence: Vec<DoubleSignEvidence>,
 }
 
 /// Block header containing metadata
@@ -37,6 +44,34 @@ pub struct BlockHeader {
     pub nonce: u64,
     /// Shard ID (for sharding)
     pub shard_id: u32,
+    /// Address of the validator that produced the block
+    #[serde(default)]
+    pub producer: Option<String>,
+    /// Hash of the evidence included in the block
+    #[serde(default)]
+    pub evidence_root: Option<String>,
+    /// Hash of the epoch's validator set, set on the first block of each epoch
+    #[serde(default)]
+    pub validator_set_hash: Option<String>,
+    /// Secret the producer committed to in its previous block (hex)
+    #[serde(default)]
+    pub randao_reveal: Option<String>,
+    /// Commitment to the secret the producer will reveal in its next block (hex)
+    #[serde(default)]
+    pub randao_commitment: Option<String>,
+    /// Randomness beacon output after this block (hex)
+    #[serde(default)]
+    pub randomness: Option<String>,
+}
+
+impl BlockHeader {
+    /// Calculate the hash of this header
+    pub fn hash(&self) -> String {
+        let header_json = serde_json::to_string(self).unwrap();
+        let mut hasher = Sha256::new();
+        hasher.update(header_json.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
 }
 
 impl Block {
@@ -64,19 +99,24 @@ impl Block {
                 difficulty: 0, // Will be set by consensus
                 nonce: 0,      // Will be set during mining
                 shard_id,
+                producer: None,
+                evidence_root: None,
+                validator_set_hash: None,
+                randao_reveal: None,
+                randao_commitment: None,
+                randomness: None,
             },
             transactions,
             validator_signature: None,
+            quantum_signature: None,
             ai_proof: None,
+            evidence: Vec::new(),
         }
     }
     
     /// Calculate the hash of this block
     pub fn hash(&self) -> String {
-        let header_json = serde_json::to_string(&self.header).unwrap();
-        let mut hasher = Sha256::new();
-        hasher.update(header_json.as_bytes());
-        format!("{:x}", hasher.finalize())
+        self.header.hash()
     }
     
     /// Calculate the Merkle root of transactions
@@ -97,11 +137,64 @@ impl Block {
         format!("{:x}", hasher.finalize())
     }
     
+    /// Calculate the hash committing to the block's evidence
+    pub fn calculate_evidence_root(evidence: &[DoubleSignEvidence]) -> Option<String> {
+        if evidence.is_empty() {
+            return None;
+        }
+        
+        let evidence_json = serde_json::to_string(evidence).unwrap();
+        let mut hasher = Sha256::new();
+        hasher.update(evidence_json.as_bytes());
+        Some(format!("{:x}", hasher.finalize()))
+    }
+    
+    /// Check the header commits to the block's transactions and evidence
+    pub fn has_valid_roots(&self) -> bool {
+        self.header.merkle_root == Self::calculate_merkle_root(&self.transactions)
+            && self.header.evidence_root == Self::calculate_evidence_root(&self.evidence)
+    }
+    
+    /// Include evidence in the block
+    pub fn set_evidence(&mut self, evidence: Vec<DoubleSignEvidence>) {
+        self.header.evidence_root = Self::calculate_evidence_root(&evidence);
+        self.evidence = evidence;
+    }
+    
     /// Sign the block with validator's signature
     pub fn sign(&mut self, signature: Signature) {
         self.validator_signature = Some(signature);
     }
     
+    /// Set the producer and sign the block header with the producer's key
+    pub fn sign_as(&mut self, producer: &str, keypair: &KeyPair) {
+        self.header.producer = Some(producer.to_string());
+        let signature = keypair.sign(self.hash().as_bytes());
+        self.sign(signature);
+    }
+    
+    /// Verify the validator signature against the producer's public key
+    pub fn verify_signature(&self, public_key: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        match &self.validator_signature {
+            Some(signature) => signature.verify(public_key, self.hash().as_bytes()),
+            None => Ok(false),
+        }
+    }
+    
+    /// Sign the block header with the producer's quantum-resistant key
+    pub fn sign_quantum(&mut self, keypair: &KeyPair) -> Result<(), Box<dyn std::error::Error>> {
+        self.quantum_signature = Some(keypair.sign_quantum(self.hash().as_bytes())?);
+        Ok(())
+    }
+    
+    /// Verify the quantum-resistant signature against the producer's quantum public key
+    pub fn verify_quantum_signature(&self, public_key: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        match &self.quantum_signature {
+            Some(signature) => signature.verify(public_key, self.hash().as_bytes()),
+            None => Ok(false),
+        }
+    }
+    
     /// Add AI proof to the block
     pub fn add_ai_proof(&mut self, proof: Vec<u8>) {
         self.ai_proof = Some(proof);
@@ -1,22 +1,83 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
-use log::{info, error};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, Notify};
+use log::{info, warn, error};
 
-use crate::block::Block;
+use crate::beacon;
+use crate::block::{Block, BlockHeader};
+use crate::consensus::Consensus;
+use crate::crypto::KeyPair;
+use crate::epoch::ValidatorSetEntry;
+use crate::evidence::{DoubleSignEvidence, EquivocationDetector};
+use crate::governance::Proposal;
+use crate::model_registry::ModelRecord;
+use crate::rewards::RewardTotals;
+use crate::scoring::{ScoreLog, ScoringContext, ScreeningAction, ScreeningResult, TransactionScreener};
 use crate::transaction::Transaction;
+use crate::spec::ChainSpec;
+use crate::staking::{DelegatorPositions, ValidatorRecord};
+use crate::state::{Account, ChainState};
+use crate::uptime::ValidatorUptime;
 use crate::storage::Storage;
 
+/// Storage key of the chain state snapshot
+const STATE_KEY: &str = "state.json";
+
+/// Number of transaction screening results kept for queries
+const SCORE_LOG_CAPACITY: usize = 10000;
+
+/// Chain events kept for subscribers that fall behind
+const EVENT_BUFFER_SIZE: usize = 1024;
+
+/// Seconds a block timestamp may be ahead of the local clock
+const MAX_CLOCK_DRIFT: u64 = 1;
+
+/// Transaction or block newly added to the chain
+#[derive(Debug, Clone)]
+pub enum ChainEvent {
+    /// Transaction entered the pending pool
+    Transaction(Transaction),
+    /// Block was added on top of the chain
+    Block(Block),
+}
+
+/// Source of block timestamps, in Unix seconds
+pub type Clock = Arc<dyn Fn() -> u64 + Send + Sync>;
+
 /// Blockchain implementation for HyperNova Chain
 pub struct Blockchain {
     /// Chain of blocks
     blocks: Arc<Mutex<Vec<Block>>>,
     /// Map of block hashes to block indices
     block_index: Arc<Mutex<HashMap<String, usize>>>,
+    /// Held while a block is added, so blocks from gossip, sync and local sealing go in one at a time
+    import_lock: Arc<Mutex<()>>,
     /// Pending transactions
     pending_transactions: Arc<Mutex<Vec<Transaction>>>,
+    /// Signalled whenever a transaction enters the pending pool
+    transaction_notifier: Arc<Notify>,
+    /// Sender of the transactions and blocks added to the chain
+    events: broadcast::Sender<ChainEvent>,
+    /// Anomaly screening applied to incoming and block-bound transactions
+    screener: TransactionScreener,
+    /// Latest screening results by transaction hash
+    scores: Arc<Mutex<ScoreLog>>,
+    /// Evidence waiting to be included in a block
+    pending_evidence: Arc<Mutex<Vec<DoubleSignEvidence>>>,
+    /// Detector for validators signing conflicting blocks
+    detector: EquivocationDetector,
+    /// State after applying the latest block
+    state: Arc<Mutex<ChainState>>,
+    /// Chain specification
+    spec: ChainSpec,
     /// Storage backend
     storage: Box<dyn Storage>,
+    /// Clock timestamping built blocks, the system clock if unset
+    clock: Option<Clock>,
+    /// Consensus engine whose rules every added block must pass, if any
+    consensus: Option<Arc<dyn Consensus>>,
     /// Data directory
     data_dir: String,
 }
@@ -24,6 +85,13 @@ pub struct Blockchain {
 impl Blockchain {
     /// Create a new blockchain instance
     pub fn new(data_dir: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_spec(data_dir, ChainSpec::default())
+    }
+    
+    /// Create a new blockchain instance for a chain specification
+    pub fn with_spec(data_dir: &str, spec: ChainSpec) -> Result<Self, Box<dyn std::error::Error>> {
+        spec.validate()?;
+        
         let data_path = Path::new(data_dir);
         
         // Create data directory if it doesn't exist
@@ -38,8 +106,19 @@ impl Blockchain {
         let mut blockchain = Blockchain {
             blocks: Arc::new(Mutex::new(Vec::new())),
             block_index: Arc::new(Mutex::new(HashMap::new())),
+            import_lock: Arc::new(Mutex::new(())),
             pending_transactions: Arc::new(Mutex::new(Vec::new())),
+            transaction_notifier: Arc::new(Notify::new()),
+            events: broadcast::channel(EVENT_BUFFER_SIZE).0,
+            screener: TransactionScreener::default(),
+            scores: Arc::new(Mutex::new(ScoreLog::new(SCORE_LOG_CAPACITY))),
+            pending_evidence: Arc::new(Mutex::new(Vec::new())),
+            detector: EquivocationDetector::new(),
+            state: Arc::new(Mutex::new(ChainState::genesis(&spec)?)),
+            spec,
             storage,
+            clock: None,
+            consensus: None,
             data_dir: data_dir.to_string(),
         };
         
@@ -52,19 +131,42 @@ impl Blockchain {
         Ok(blockchain)
     }
     
+    /// Screen transactions with a different scorer or policy
+    pub fn with_transaction_screener(mut self, screener: TransactionScreener) -> Self {
+        self.screener = screener;
+        self
+    }
+    
+    /// Timestamp built blocks with a clock other than the system clock, such as a simulation's
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+    
+    /// Check every added block against the rules of a consensus engine, wherever it came from
+    pub fn with_consensus(mut self, consensus: Arc<dyn Consensus>) -> Self {
+        self.consensus = Some(consensus);
+        self
+    }
+    
     /// Create the genesis block
     fn create_genesis_block(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let genesis = Block::new(
+        let mut genesis = Block::new(
             "0".repeat(64),
             0,
             Vec::new(),
             0, // Main shard
         );
+        genesis.header.timestamp = self.spec.genesis_timestamp;
+        genesis.header.randomness = Some(beacon::genesis_randomness(&self.spec.chain_id));
         
         let hash = genesis.hash();
         
-        // Store the genesis block
+        // Store the genesis block and state
+        let state = ChainState::genesis(&self.spec)?;
         self.storage.store_block(&genesis)?;
+        self.storage.store_data(STATE_KEY, &serde_json::to_vec(&state)?)?;
+        *self.state.lock().unwrap() = state;
         
         // Add to in-memory structures
         let mut blocks = self.blocks.lock().unwrap();
@@ -96,6 +198,28 @@ impl Blockchain {
             block_index_lock.insert(block.hash(), i);
         }
         
+        // Use the state snapshot if it matches the tip, otherwise replay the chain
+        let tip_height = blocks_lock.last().unwrap().header.height;
+        let snapshot = match self.storage.load_data(STATE_KEY)? {
+            Some(data) => serde_json::from_slice::<ChainState>(&data).ok(),
+            None => None,
+        };
+        
+        let state = match snapshot {
+            Some(state) if state.height == tip_height => state,
+            _ => {
+                info!("Rebuilding chain state from {} blocks", blocks_lock.len());
+                let mut state = ChainState::genesis(&self.spec)?;
+                for block in blocks_lock.iter().skip(1) {
+                    state.apply_block(block, &self.spec)?;
+                }
+                self.storage.store_data(STATE_KEY, &serde_json::to_vec(&state)?)?;
+                state
+            }
+        };
+        
+        *self.state.lock().unwrap() = state;
+        
         info!("Loaded {} blocks from storage", blocks_lock.len());
         
         Ok(())
@@ -105,25 +229,100 @@ impl Blockchain {
     pub fn add_block(&self, block: Block) -> Result<(), Box<dyn std::error::Error>> {
         let hash = block.hash();
         
+        // Another block validated against the same tip must not go in at the same time
+        let _import = self.import_lock.lock().unwrap();
+        
         // Validate the block
         self.validate_block(&block)?;
+        if let Some(consensus) = &self.consensus {
+            consensus.validate_block(&block, self)?;
+        }
         
-        // Store the block
+        // Apply the block to a copy of the state so a failing transaction leaves it untouched
+        let mut state = self.state.lock().unwrap().clone();
+        state.apply_block(&block, &self.spec)?;
+        
+        // Store the block and the resulting state
         self.storage.store_block(&block)?;
+        self.storage.store_data(STATE_KEY, &serde_json::to_vec(&state)?)?;
+        *self.state.lock().unwrap() = state;
+        
+        // Drop included transactions from the pending pool
+        let included: HashSet<String> = block.transactions.iter().map(|tx| tx.hash()).collect();
+        self.pending_transactions.lock().unwrap().retain(|tx| !included.contains(&tx.hash()));
+        
+        // Drop evidence that was included or can no longer be applied
+        self.prune_evidence();
+        
+        // Remember the producer's signature in case it signs a conflicting block
+        self.observe_block(&block);
         
         // Add to in-memory structures
         let mut blocks = self.blocks.lock().unwrap();
         let mut block_index = self.block_index.lock().unwrap();
         
         let index = blocks.len();
-        blocks.push(block);
-        block_index.insert(hash, index);
+        blocks.push(block.clone());
+        block_index.insert(hash.clone(), index);
+        let _ = self.events.send(ChainEvent::Block(block));
         
         info!("Added block {} with hash: {}", index, hash);
         
         Ok(())
     }
     
+    /// Build the next block on top of the tip, committing to the validator set at epoch boundaries
+    pub fn build_block(&self, transactions: Vec<Transaction>) -> Result<Block, Box<dyn std::error::Error>> {
+        let latest_block = self.get_latest_block().ok_or("No blocks in the chain")?;
+        let height = latest_block.header.height + 1;
+        
+        let state = self.state.lock().unwrap();
+        let mut block = Block::new(latest_block.hash(), height, transactions, 0);
+        block.header.timestamp = self.now();
+        block.header.validator_set_hash = state.expected_validator_set_hash(height, &self.spec);
+        block.header.randomness = Some(state.beacon().randomness().to_string());
+        
+        Ok(block)
+    }
+    
+    /// Reveal the producer's previous RANDAO secret in a block and commit to a new one
+    ///
+    /// The block must then be signed by the producer for the commitment to be accepted.
+    /// Producers that aren't registered validators leave the beacon unchanged.
+    pub fn contribute_randomness(
+        &self,
+        block: &mut Block,
+        producer: &str,
+        keypair: &KeyPair,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let state = self.state.lock().unwrap();
+        let registered = state
+            .staking()
+            .validator(producer)
+            .is_some_and(|record| record.public_key == keypair.public_key_hex());
+        if !registered {
+            return Ok(());
+        }
+        
+        let reveal = match state.beacon().commitment(producer) {
+            Some(outstanding) => {
+                let secret = beacon::derive_secret(keypair, outstanding.height);
+                if beacon::commitment(&secret)? != outstanding.hash {
+                    return Err(format!("Key of {} does not match its RANDAO commitment", producer).into());
+                }
+                Some(secret)
+            }
+            None => None,
+        };
+        
+        let height = block.header.height;
+        block.header.randomness = Some(state.beacon().preview(reveal.as_deref())?);
+        block.header.randao_commitment = Some(beacon::commitment(&beacon::derive_secret(keypair, height))?);
+        block.header.randao_reveal = reveal;
+        
+        Ok(())
+    }
+    
     /// Validate a block
     fn validate_block(&self, block: &Block) -> Result<(), Box<dyn std::error::Error>> {
         let blocks = self.blocks.lock().unwrap();
@@ -153,16 +352,18 @@ impl Blockchain {
             return Err("Invalid previous hash".into());
         }
         
-        // Validate transactions
-        for tx in &block.transactions {
-            if !tx.verify_signature() {
-                return Err("Invalid transaction signature".into());
-            }
+        // Check the header commits to the included evidence
+        if block.header.evidence_root != Block::calculate_evidence_root(&block.evidence) {
+            return Err("Evidence root does not match block evidence".into());
+        }
+        
+        // A timestamp from the future would claim a later producer's turn
+        if block.header.timestamp > self.now().saturating_add(MAX_CLOCK_DRIFT) {
+            return Err(format!("Block {} timestamp {} is in the future", block.header.height, block.header.timestamp).into());
         }
         
+        // Consensus rules are checked by the engine the chain was given, if any.
         // In a real implementation, this would also validate:
-        // - Consensus rules (PoAI, DPoS)
-        // - Quantum-resistant signatures
         // - Sharding rules
         
         Ok(())
@@ -170,24 +371,217 @@ impl Blockchain {
     
     /// Add a transaction to the pending pool
     pub fn add_transaction(&self, transaction: Transaction) -> Result<(), Box<dyn std::error::Error>> {
-        // Validate the transaction
-        if !transaction.verify_signature() {
-            return Err("Invalid transaction signature".into());
+        // Scorers may wait on the AI service, so they get a copy of what they need instead of the locks
+        let (state, pending) = {
+            let state = self.state.lock().unwrap();
+            state.check_signature(&transaction)?;
+            Self::check_nonce(&state, &transaction)?;
+            
+            let accounts = std::iter::once(&transaction.from).chain(&transaction.to);
+            (state.accounts_snapshot(accounts), self.pending_transactions.lock().unwrap().clone())
+        };
+        
+        // Screen the transaction for anomalies before admitting it
+        let result = self.screener.screen(&transaction, &ScoringContext {
+            state: &state,
+            pending: &pending,
+        });
+        let action = result.action;
+        self.record_score(result);
+        
+        if action == ScreeningAction::Reject {
+            return Err(format!("Transaction {} rejected as anomalous", transaction.hash()).into());
         }
         
+        // A block may have used the nonce while the transaction was scored
+        let state = self.state.lock().unwrap();
+        Self::check_nonce(&state, &transaction)?;
+        
         // Add to pending transactions
-        let mut pending = self.pending_transactions.lock().unwrap();
-        pending.push(transaction);
+        self.pending_transactions.lock().unwrap().push(transaction.clone());
+        self.transaction_notifier.notify_one();
+        // Nobody may be subscribed
+        let _ = self.events.send(ChainEvent::Transaction(transaction));
         
         Ok(())
     }
     
+    /// Check a transaction does not reuse a nonce its sender has already used, as it could never be applied
+    fn check_nonce(state: &ChainState, transaction: &Transaction) -> Result<(), Box<dyn std::error::Error>> {
+        let nonce = state.account(&transaction.from).nonce;
+        if transaction.nonce < nonce {
+            return Err(format!(
+                "Transaction {} reuses nonce {} of {}, next is {}",
+                transaction.hash(), transaction.nonce, transaction.from, nonce
+            ).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Check a transaction is signed with its sender's key
+    pub fn check_transaction_signature(&self, transaction: &Transaction) -> Result<(), Box<dyn std::error::Error>> {
+        self.state.lock().unwrap().check_signature(transaction)
+    }
+    
+    /// Check an observed block for equivocation by its producer, queueing any evidence found
+    pub fn observe_block(&self, block: &Block) -> Option<DoubleSignEvidence> {
+        let producer = block.header.producer.as_ref()?;
+        let validator = self.get_validator(producer)?;
+        
+        // Only blocks really signed by the producer can incriminate it
+        if !block.verify_signature(&validator.public_key).unwrap_or(false) {
+            return None;
+        }
+        
+        let evidence = self.detector.observe(block, self.get_height())?;
+        
+        if let Err(e) = self.add_evidence(evidence.clone()) {
+            warn!("Discarding double-sign evidence {}: {}", evidence.id(), e);
+            return None;
+        }
+        
+        Some(evidence)
+    }
+    
+    /// Add double-sign evidence to the pending pool
+    pub fn add_evidence(&self, evidence: DoubleSignEvidence) -> Result<(), Box<dyn std::error::Error>> {
+        {
+            let state = self.state.lock().unwrap();
+            state.check_evidence(&evidence, state.height + 1, &self.spec)?;
+        }
+        
+        let mut pending = self.pending_evidence.lock().unwrap();
+        
+        if !pending.iter().any(|existing| existing.id() == evidence.id()) {
+            info!("Queued double-sign evidence {}", evidence.id());
+            pending.push(evidence);
+        }
+        
+        Ok(())
+    }
+    
+    /// Get evidence waiting to be included in a block
+    pub fn get_pending_evidence(&self) -> Vec<DoubleSignEvidence> {
+        let pending = self.pending_evidence.lock().unwrap();
+        pending.clone()
+    }
+    
+    /// Remove pending evidence that no longer applies on top of the current state
+    fn prune_evidence(&self) {
+        let state = self.state.lock().unwrap();
+        let mut pending = self.pending_evidence.lock().unwrap();
+        
+        pending.retain(|evidence| state.check_evidence(evidence, state.height + 1, &self.spec).is_ok());
+    }
+    
     /// Get pending transactions
     pub fn get_pending_transactions(&self) -> Vec<Transaction> {
         let pending = self.pending_transactions.lock().unwrap();
         pending.clone()
     }
     
+    /// Get the pending transactions to include in the next block, flagged ones last and stale ones dropped
+    pub fn get_block_candidates(&self) -> Vec<Transaction> {
+        let (state, pending) = {
+            let state = self.state.lock().unwrap();
+            let pending = self.pending_transactions.lock().unwrap().clone();
+            let accounts = pending.iter().flat_map(|tx| std::iter::once(&tx.from).chain(&tx.to));
+            
+            (state.accounts_snapshot(accounts), pending)
+        };
+        
+        // Transactions keep the score they were admitted with, only those without one are scored
+        let results: Vec<ScreeningResult> = pending
+            .iter()
+            .map(|tx| {
+                let cached = self.scores.lock().unwrap().get(&tx.hash()).cloned();
+                cached.unwrap_or_else(|| {
+                    let result = self.screener.screen(tx, &ScoringContext {
+                        state: &state,
+                        pending: &pending,
+                    });
+                    self.record_score(result.clone());
+                    result
+                })
+            })
+            .collect();
+        
+        let mut candidates = Vec::new();
+        let mut deprioritized = Vec::new();
+        let mut rejected = HashSet::new();
+        
+        for (tx, result) in pending.iter().zip(results) {
+            // Transactions whose nonce was used by another one can never be applied
+            if tx.nonce < state.account(&tx.from).nonce {
+                rejected.insert(tx.hash());
+                continue;
+            }
+            
+            match result.action {
+                ScreeningAction::Reject => {
+                    rejected.insert(result.transaction_hash.clone());
+                }
+                ScreeningAction::Deprioritize => deprioritized.push(tx.clone()),
+                ScreeningAction::Admit | ScreeningAction::Log => candidates.push(tx.clone()),
+            }
+        }
+        
+        // Transactions that turned out anomalous or stale while waiting leave the pool
+        self.pending_transactions.lock().unwrap().retain(|tx| !rejected.contains(&tx.hash()));
+        
+        candidates.extend(deprioritized);
+        candidates
+    }
+    
+    /// Keep a screening result, logging flagged transactions
+    fn record_score(&self, result: ScreeningResult) {
+        if result.action != ScreeningAction::Admit {
+            warn!(
+                "Transaction {} scored {} bps by the {} scorer ({:?}): {}",
+                result.transaction_hash,
+                result.score_bps,
+                result.scorer,
+                result.action,
+                result.reasons.join("; ")
+            );
+        }
+        
+        self.scores.lock().unwrap().record(result);
+    }
+    
+    /// Get the latest screening result of a transaction
+    pub fn get_transaction_score(&self, hash: &str) -> Option<ScreeningResult> {
+        let scores = self.scores.lock().unwrap();
+        scores.get(hash).cloned()
+    }
+    
+    /// Get the latest screening results of the pending transactions
+    pub fn get_pending_scores(&self) -> Vec<ScreeningResult> {
+        let pending = self.pending_transactions.lock().unwrap();
+        let scores = self.scores.lock().unwrap();
+        
+        pending.iter().filter_map(|tx| scores.get(&tx.hash()).cloned()).collect()
+    }
+    
+    /// Drop a transaction from the pending pool
+    pub fn remove_pending_transaction(&self, hash: &str) {
+        let mut pending = self.pending_transactions.lock().unwrap();
+        pending.retain(|tx| tx.hash() != hash);
+    }
+    
+    /// Get a handle that is notified whenever a transaction enters the pending pool
+    pub fn transaction_notifier(&self) -> Arc<Notify> {
+        self.transaction_notifier.clone()
+    }
+    
+    /// Subscribe to the transactions and blocks added from now on
+    ///
+    /// Subscribers more than 1024 events behind miss the oldest ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<ChainEvent> {
+        self.events.subscribe()
+    }
+    
     /// Get the latest block
     pub fn get_latest_block(&self) -> Option<Block> {
         let blocks = self.blocks.lock().unwrap();
@@ -196,8 +590,8 @@ impl Blockchain {
     
     /// Get a block by hash
     pub fn get_block_by_hash(&self, hash: &str) -> Option<Block> {
-        let block_index = self.block_index.lock().unwrap();
         let blocks = self.blocks.lock().unwrap();
+        let block_index = self.block_index.lock().unwrap();
         
         block_index.get(hash).map(|&index| blocks[index].clone())
     }
@@ -220,4 +614,137 @@ impl Blockchain {
             None => 0,
         }
     }
+    
+    /// Get the chain specification
+    pub fn get_spec(&self) -> &ChainSpec {
+        &self.spec
+    }
+    
+    /// Get a copy of the current chain state
+    pub fn get_state(&self) -> ChainState {
+        let state = self.state.lock().unwrap();
+        state.clone()
+    }
+    
+    /// Get the balance of an account
+    pub fn get_balance(&self, address: &str) -> u64 {
+        let state = self.state.lock().unwrap();
+        state.balance(address)
+    }
+    
+    /// Get the balance and nonce of an account
+    pub fn get_account(&self, address: &str) -> Account {
+        let state = self.state.lock().unwrap();
+        state.account(address)
+    }
+    
+    /// Get the delegations and unbonding entries of a delegator
+    pub fn get_delegations(&self, delegator: &str) -> DelegatorPositions {
+        let state = self.state.lock().unwrap();
+        state.staking().positions(delegator)
+    }
+    
+    /// Get a registered validator
+    pub fn get_validator(&self, address: &str) -> Option<ValidatorRecord> {
+        let state = self.state.lock().unwrap();
+        state.staking().validator(address).cloned()
+    }
+    
+    /// Get all registered validators
+    pub fn get_validators(&self) -> Vec<ValidatorRecord> {
+        let state = self.state.lock().unwrap();
+        state.staking().validators().cloned().collect()
+    }
+    
+    /// Get the uptime report of a validator
+    pub fn get_uptime(&self, address: &str) -> ValidatorUptime {
+        let state = self.state.lock().unwrap();
+        state.uptime().report(address)
+    }
+    
+    /// Get the uptime reports of all validators scheduled in the current epoch
+    pub fn get_uptime_reports(&self) -> Vec<ValidatorUptime> {
+        let state = self.state.lock().unwrap();
+        state
+            .epochs()
+            .current()
+            .iter()
+            .map(|validator| state.uptime().report(&validator.address))
+            .collect()
+    }
+    
+    /// Get the validator set that signs the block at a height, if it is known yet
+    pub fn get_validator_set_at(&self, height: u64) -> Option<Vec<ValidatorSetEntry>> {
+        let state = self.state.lock().unwrap();
+        state.validator_set_at(height, &self.spec)
+    }
+    
+    /// Get the validator scheduled to produce the block at a height in a round, if its set is known yet
+    pub fn get_scheduled_producer(&self, height: u64, round: u64) -> Option<ValidatorSetEntry> {
+        let state = self.state.lock().unwrap();
+        state.scheduled_producer_at(height, round, &self.spec)
+    }
+    
+    /// Get the validator scheduled to produce a block, from how long after its parent it was made
+    ///
+    /// Returns `None` if the parent or the validator set is not known yet.
+    pub fn get_scheduled_producer_of(&self, header: &BlockHeader) -> Option<ValidatorSetEntry> {
+        let parent = self.get_block_by_hash(&header.prev_hash)?;
+        let round = self.spec.slot_round(parent.header.timestamp, header.timestamp);
+        self.get_scheduled_producer(header.height, round)
+    }
+    
+    /// Get the current time of the chain's clock, in Unix seconds
+    pub fn now(&self) -> u64 {
+        match &self.clock {
+            Some(clock) => clock(),
+            None => SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        }
+    }
+    
+    /// Get the issuance and fee distribution totals
+    pub fn get_reward_totals(&self) -> RewardTotals {
+        let state = self.state.lock().unwrap();
+        state.rewards().clone()
+    }
+    
+    /// Get the block reward the next block's producer will earn
+    pub fn get_next_block_reward(&self) -> u64 {
+        let state = self.state.lock().unwrap();
+        self.spec.monetary_policy.block_reward(state.height + 1, state.rewards())
+    }
+    
+    /// Get the randomness beacon output after the block at a height
+    pub fn get_randomness_at(&self, height: u64) -> Option<String> {
+        self.get_block_by_height(height)?.header.randomness
+    }
+    
+    /// Get the validators selected to verify the AI proof of the block at a height
+    ///
+    /// The committee is drawn from the block's validator set using the beacon
+    /// output of its parent, so it is known before the block is produced.
+    pub fn get_verification_committee(&self, height: u64) -> Option<Vec<ValidatorSetEntry>> {
+        let seed = self.get_randomness_at(height.checked_sub(1)?)?;
+        let validators = self.get_validator_set_at(height)?;
+        
+        Some(beacon::select_committee(&validators, &seed, self.spec.poai_committee_size))
+    }
+    
+    /// Get a model submitted for Proof of AI
+    pub fn get_model(&self, model_id: &str) -> Option<ModelRecord> {
+        let state = self.state.lock().unwrap();
+        state.models().model(model_id).cloned()
+    }
+    
+    /// Get all models submitted for Proof of AI
+    pub fn get_models(&self) -> Vec<ModelRecord> {
+        let state = self.state.lock().unwrap();
+        state.models().models().cloned().collect()
+    }
+    
+    /// Get a governance proposal
+    pub fn get_proposal(&self, id: u64) -> Option<Proposal> {
+        let state = self.state.lock().unwrap();
+        state.governance().proposal(id).cloned()
+    }
 }
\ No newline at end of file
@@ -1,8 +1,19 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::{Arc, Mutex};
-use log::{info, error};
+use std::time::Duration;
+use log::{info, warn, error};
 
+use crate::ai_client::{AiClient, BlockData};
+use crate::ai_model::QuantizedModel;
+use crate::ai_proof::{AIProof, MAX_CONFIDENCE_BPS};
 use crate::block::Block;
 use crate::chain::Blockchain;
+use crate::crypto::{KeyPair, SignatureType};
+use crate::epoch::ValidatorSetEntry;
+use crate::model_registry::ModelStatus;
+use crate::staking::ValidatorStatus;
+use crate::transaction::Transaction;
+use crate::utils;
 
 /// Consensus trait for HyperNova Chain
 pub trait Consensus: Send + Sync {
@@ -21,98 +32,180 @@ pub trait Consensus: Send + Sync {
 
 /// Proof of AI (PoAI) consensus implementation
 pub struct ProofOfAI {
-    /// AI model endpoint
-    ai_endpoint: String,
+    /// Client for the AI service generating and verifying proofs
+    ai_client: AiClient,
     /// Minimum AI confidence threshold
     confidence_threshold: f64,
-    /// Current validators
-    validators: Arc<Mutex<Vec<String>>>,
+    /// Address and key of the local validator producing blocks
+    validator: Option<(String, KeyPair)>,
+    /// Models evaluated locally to check proofs, by hash
+    models: BTreeMap<[u8; 32], QuantizedModel>,
 }
 
 impl ProofOfAI {
     /// Create a new PoAI consensus instance
     pub fn new(ai_endpoint: &str, confidence_threshold: f64) -> Self {
+        Self::with_client(AiClient::new(ai_endpoint), confidence_threshold)
+    }
+    
+    /// Create a new PoAI consensus instance using a configured AI service client
+    pub fn with_client(ai_client: AiClient, confidence_threshold: f64) -> Self {
         ProofOfAI {
-            ai_endpoint: ai_endpoint.to_string(),
+            ai_client,
             confidence_threshold,
-            validators: Arc::new(Mutex::new(Vec::new())),
+            validator: None,
+            models: BTreeMap::new(),
         }
     }
     
+    /// Load a model so proofs made with it can be re-evaluated locally
+    pub fn with_model(mut self, model: QuantizedModel) -> Self {
+        self.models.insert(model.hash(), model);
+        self
+    }
+    
+    /// Get a locally loaded model by hash
+    fn model(&self, model_hash: &[u8; 32]) -> Result<&QuantizedModel, Box<dyn std::error::Error>> {
+        self.models
+            .get(model_hash)
+            .ok_or_else(|| format!("Model {} is not loaded locally", hex::encode(model_hash)).into())
+    }
+    
+    /// Check a model is approved on chain for consensus use with this hash
+    fn check_model(&self, model_id: &str, model_hash: &[u8; 32], blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        let record = blockchain
+            .get_model(model_id)
+            .ok_or_else(|| format!("Model {} is not registered", model_id))?;
+        
+        if record.status != ModelStatus::Approved {
+            return Err(format!("Model {} is {:?}, not approved for consensus", model_id, record.status).into());
+        }
+        
+        if record.model_hash != hex::encode(model_hash) {
+            return Err(format!("Model {} is registered with hash {}", model_id, record.model_hash).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Produce blocks as a registered validator, signing their AI proofs with its key
+    pub fn with_validator(mut self, address: &str, keypair: KeyPair) -> Self {
+        self.validator = Some((address.to_string(), keypair));
+        self
+    }
+    
+    /// Get the validators allowed to produce PoAI blocks
+    pub fn get_validators(&self, blockchain: &Blockchain) -> Vec<String> {
+        blockchain
+            .get_validators()
+            .into_iter()
+            .filter(|record| record.status == ValidatorStatus::Active)
+            .map(|record| record.address)
+            .collect()
+    }
+    
+    /// Minimum confidence an AI proof must carry, in basis points
+    fn min_confidence_bps(&self) -> u16 {
+        (self.confidence_threshold * f64::from(MAX_CONFIDENCE_BPS)).ceil() as u16
+    }
+    
     /// Generate AI proof for a block
-    fn generate_ai_proof(&self, block: &Block) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        // In a real implementation, this would:
-        // 1. Send block data to the AI model
-        // 2. Get the AI model's prediction/validation
-        // 3. Return the proof data
+    pub(crate) fn generate_ai_proof(&self, block: &Block, keypair: &KeyPair, blockchain: &Blockchain) -> Result<AIProof, Box<dyn std::error::Error>> {
+        let block_data = BlockData::from_block(block);
+        let response = utils::block_on(None, self.ai_client.generate_proof(&block_data, None))
+            .and_then(|response| response)
+            .map_err(|e| e as Box<dyn std::error::Error>)?;
+        
+        if !response.is_valid {
+            return Err(format!("AI service could not verify its proof for block {}", block.header.height).into());
+        }
+        
+        let model_hash: [u8; 32] = hex::decode(&response.model_hash)?
+            .try_into()
+            .map_err(|_| "AI service returned a malformed model hash")?;
+        
+        self.check_model(&response.model_id, &model_hash, blockchain)?;
+        
+        // Prove the confidence every validator will reproduce, not the one the service reports
+        let confidence_bps = self.model(&model_hash)?.evaluate(&block_data, response.nonce)?;
+        
+        if confidence_bps < self.min_confidence_bps() {
+            return Err(format!(
+                "AI proof confidence {} bps is below the threshold of {}",
+                confidence_bps, self.confidence_threshold
+            ).into());
+        }
+        
+        let mut proof = AIProof::new(&block.header, response.model_id, model_hash, response.nonce, confidence_bps);
+        proof.sign(keypair);
         
-        // Placeholder implementation
-        Ok(vec![1, 2, 3, 4])
+        Ok(proof)
     }
     
     /// Verify AI proof for a block
-    fn verify_ai_proof(&self, block: &Block) -> Result<bool, Box<dyn std::error::Error>> {
-        // In a real implementation, this would verify the AI proof
+    pub(crate) fn verify_ai_proof(&self, block: &Block, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        let proof_data = match &block.ai_proof {
+            Some(proof_data) => proof_data,
+            None => return Err("No AI proof found in block".into()),
+        };
+        
+        let proof = AIProof::from_bytes(proof_data)?;
+        
+        // Only models approved by governance may prove blocks
+        self.check_model(&proof.model_id, &proof.model_hash, blockchain)?;
         
-        // Placeholder implementation
-        match &block.ai_proof {
-            Some(_) => Ok(true),
-            None => Err("No AI proof found in block".into()),
+        // Re-run the model instead of trusting the producer's confidence
+        let confidence_bps = self.model(&proof.model_hash)?.evaluate(&BlockData::from_block(block), proof.nonce)?;
+        if confidence_bps != proof.confidence_bps {
+            return Err(format!(
+                "AI proof claims {} bps but the model evaluates to {} bps",
+                proof.confidence_bps, confidence_bps
+            ).into());
         }
+        
+        // The proof must be signed by the active validator that produced the block, whichever
+        // validators the beacon drew to re-verify it
+        let producer = block.header.producer.as_ref().ok_or("Block has no producer")?;
+        let validator = blockchain
+            .get_validator(producer)
+            .filter(|record| record.status == ValidatorStatus::Active)
+            .ok_or_else(|| format!("Producer {} is not an active validator", producer))?;
+        
+        proof.verify(&block.header, &validator.public_key, self.min_confidence_bps())
     }
 }
 
 impl Consensus for ProofOfAI {
     fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Initializing Proof of AI consensus");
-        // Initialize validators
-        let mut validators = self.validators.lock().unwrap();
-        
-        // In a real implementation, this would load validators from storage
-        *validators = vec![
-            "validator1".to_string(),
-            "validator2".to_string(),
-            "validator3".to_string(),
-        ];
+        // Validators are read from chain state
         
         Ok(())
     }
     
     fn create_block(&self, blockchain: &Blockchain) -> Result<Block, Box<dyn std::error::Error>> {
-        // Get the latest block
-        let latest_block = match blockchain.get_latest_block() {
-            Some(block) => block,
-            None => return Err("No blocks in the chain".into()),
-        };
-        
         // Get pending transactions
-        let transactions = blockchain.get_pending_transactions();
+        let transactions = blockchain.get_block_candidates();
+        
+        let (address, keypair) = self.validator.as_ref().ok_or("No validator key configured for PoAI block production")?;
         
-        // Create a new block
-        let mut block = Block::new(
-            latest_block.hash(),
-            latest_block.header.height + 1,
-            transactions,
-            0, // Main shard
-        );
+        // Create a new block on top of the tip
+        let mut block = blockchain.build_block(transactions)?;
+        block.set_evidence(blockchain.get_pending_evidence());
+        block.header.producer = Some(address.clone());
+        blockchain.contribute_randomness(&mut block, address, keypair)?;
         
-        // Generate AI proof
-        let ai_proof = self.generate_ai_proof(&block)?;
-        block.add_ai_proof(ai_proof);
+        // Generate an AI proof committing to the finished header
+        let ai_proof = self.generate_ai_proof(&block, keypair, blockchain)?;
+        block.add_ai_proof(ai_proof.to_bytes());
+        block.sign_as(address, keypair);
         
         Ok(block)
     }
     
     fn validate_block(&self, block: &Block, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
         // Verify AI proof
-        if !self.verify_ai_proof(block)? {
-            return Err("Invalid AI proof".into());
-        }
-        
-        // In a real implementation, this would also:
-        // - Check if the block creator is a valid validator
-        // - Verify the validator's signature
-        // - Apply additional PoAI-specific rules
+        self.verify_ai_proof(block, blockchain)?;
         
         Ok(())
     }
@@ -130,8 +223,8 @@ pub struct DelegatedProofOfStake {
     min_stake: u64,
     /// Current validators
     validators: Arc<Mutex<Vec<String>>>,
-    /// Validator stakes
-    stakes: Arc<Mutex<std::collections::HashMap<String, u64>>>,
+    /// Epoch the current validators were elected for
+    elected_epoch: Arc<Mutex<Option<u64>>>,
 }
 
 impl DelegatedProofOfStake {
@@ -141,41 +234,64 @@ impl DelegatedProofOfStake {
             validator_count,
             min_stake,
             validators: Arc::new(Mutex::new(Vec::new())),
-            stakes: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            elected_epoch: Arc::new(Mutex::new(None)),
         }
     }
     
+    /// Get the current validators
+    pub fn get_validators(&self) -> Vec<String> {
+        let validators = self.validators.lock().unwrap();
+        validators.clone()
+    }
+    
     /// Check if an address is a validator
     fn is_validator(&self, address: &str) -> bool {
         let validators = self.validators.lock().unwrap();
         validators.contains(&address.to_string())
     }
     
-    /// Get the stake of a validator
-    fn get_stake(&self, address: &str) -> u64 {
-        let stakes = self.stakes.lock().unwrap();
-        *stakes.get(address).unwrap_or(&0)
+    /// Reload the validators if the next block belongs to a new epoch
+    pub fn refresh_validators(&self, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        let epoch = blockchain.get_spec().epoch_of(blockchain.get_height() + 1);
+        
+        if *self.elected_epoch.lock().unwrap() != Some(epoch) {
+            self.update_validators(blockchain)?;
+            *self.elected_epoch.lock().unwrap() = Some(epoch);
+        }
+        
+        // Validators jailed or tombstoned during the epoch stop producing immediately
+        let mut validators = self.validators.lock().unwrap();
+        validators.retain(|address| {
+            blockchain
+                .get_validator(address)
+                .is_some_and(|record| record.status == ValidatorStatus::Active)
+        });
+        
+        Ok(())
     }
     
-    /// Update validators based on stakes
-    fn update_validators(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let stakes = self.stakes.lock().unwrap();
+    /// Update validators from the set elected on chain for the epoch of the next block
+    pub fn update_validators(&self, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        let next_height = blockchain.get_height() + 1;
+        let elected = blockchain.get_validator_set_at(next_height).unwrap_or_default();
         let mut validators = self.validators.lock().unwrap();
         
-        // Sort addresses by stake
-        let mut addresses: Vec<(String, u64)> = stakes
+        // Keep the largest stakes, but in the beacon-shuffled order of the elected set
+        let mut by_stake: Vec<&ValidatorSetEntry> = elected
             .iter()
-            .filter(|(_, &stake)| stake >= self.min_stake)
-            .map(|(addr, &stake)| (addr.clone(), stake))
+            .filter(|validator| validator.stake >= self.min_stake)
             .collect();
-            
-        addresses.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        // Select top validators
-        *validators = addresses
+        by_stake.sort_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.address.cmp(&b.address)));
+        let chosen: BTreeSet<&str> = by_stake
             .into_iter()
             .take(self.validator_count)
-            .map(|(addr, _)| addr)
+            .map(|validator| validator.address.as_str())
+            .collect();
+        
+        *validators = elected
+            .iter()
+            .filter(|validator| chosen.contains(validator.address.as_str()))
+            .map(|validator| validator.address.clone())
             .collect();
             
         info!("Updated validators: {:?}", validators);
@@ -188,51 +304,45 @@ impl Consensus for DelegatedProofOfStake {
     fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Initializing Delegated Proof of Stake consensus");
         
-        // In a real implementation, this would load stakes from storage
-        let mut stakes = self.stakes.lock().unwrap();
-        
-        stakes.insert("validator1".to_string(), 1000000);
-        stakes.insert("validator2".to_string(), 800000);
-        stakes.insert("validator3".to_string(), 600000);
-        
-        // Update validators based on stakes
-        drop(stakes);
-        self.update_validators()?;
+        // Validators are elected from on-chain delegations on first use
+        *self.elected_epoch.lock().unwrap() = None;
         
         Ok(())
     }
     
     fn create_block(&self, blockchain: &Blockchain) -> Result<Block, Box<dyn std::error::Error>> {
-        // Get the latest block
-        let latest_block = match blockchain.get_latest_block() {
-            Some(block) => block,
-            None => return Err("No blocks in the chain".into()),
-        };
+        self.refresh_validators(blockchain)?;
         
         // Get pending transactions
-        let transactions = blockchain.get_pending_transactions();
+        let transactions = blockchain.get_block_candidates();
         
-        // Create a new block
-        let block = Block::new(
-            latest_block.hash(),
-            latest_block.header.height + 1,
-            transactions,
-            0, // Main shard
-        );
+        // Create a new block on top of the tip
+        let mut block = blockchain.build_block(transactions)?;
+        block.set_evidence(blockchain.get_pending_evidence());
         
         Ok(block)
     }
     
-    fn validate_block(&self, block: &Block, _blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_block(&self, block: &Block, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        self.refresh_validators(blockchain)?;
+        
         // Check if the block has a validator signature
         if block.validator_signature.is_none() {
             return Err("No validator signature found in block".into());
         }
         
-        // In a real implementation, this would:
-        // - Verify that the block creator is a current validator
-        // - Check the validator's signature
-        // - Ensure the validator is following the correct schedule
+        // The block must be signed by one of the current validators
+        let producer = block.header.producer.as_ref().ok_or("Block has no producer")?;
+        if !self.is_validator(producer) {
+            return Err(format!("Producer {} is not a current validator", producer).into());
+        }
+        
+        let record = blockchain
+            .get_validator(producer)
+            .ok_or_else(|| format!("Producer {} is not registered", producer))?;
+        if !block.verify_signature(&record.public_key)? {
+            return Err(format!("Invalid validator signature on block {}", block.header.height).into());
+        }
         
         Ok(())
     }
@@ -240,4 +350,254 @@ impl Consensus for DelegatedProofOfStake {
     fn name(&self) -> &'static str {
         "Delegated Proof of Stake (DPoS)"
     }
+}
+
+/// Quantum Proofed Hybrid Proof consensus, combining DPoS scheduling, PoAI and quantum-resistant signatures
+pub struct HybridConsensus {
+    /// Engine generating and verifying AI proofs
+    proof_of_ai: ProofOfAI,
+    /// Address and key of the local validator producing blocks
+    validator: Option<(String, KeyPair)>,
+}
+
+impl HybridConsensus {
+    /// Create a hybrid consensus instance checking AI proofs with a PoAI engine
+    pub fn new(proof_of_ai: ProofOfAI) -> Self {
+        HybridConsensus {
+            proof_of_ai,
+            validator: None,
+        }
+    }
+    
+    /// Produce blocks as a registered validator, signing them with its keys
+    pub fn with_validator(mut self, address: &str, keypair: KeyPair) -> Self {
+        self.validator = Some((address.to_string(), keypair));
+        self
+    }
+    
+    /// Check the block was produced and signed by the validator scheduled for its slot
+    fn verify_producer(&self, block: &Block, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        let height = block.header.height;
+        let producer = block.header.producer.as_ref().ok_or("Block has no producer")?;
+        let scheduled = blockchain
+            .get_scheduled_producer_of(&block.header)
+            .ok_or_else(|| format!("No validator is scheduled at height {}", height))?;
+        
+        if *producer != scheduled.address {
+            return Err(format!(
+                "Block {} was produced by {} but {} is scheduled",
+                height, producer, scheduled.address
+            ).into());
+        }
+        
+        if !block.verify_signature(&scheduled.public_key)? {
+            return Err(format!("Invalid validator signature on block {}", height).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Check the block carries the producer's quantum-resistant signature
+    fn verify_quantum_signature(&self, block: &Block, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        let producer = block.header.producer.as_ref().ok_or("Block has no producer")?;
+        let public_key = blockchain
+            .get_validator(producer)
+            .and_then(|record| record.quantum_public_key)
+            .ok_or_else(|| format!("Producer {} has no quantum public key", producer))?;
+        
+        // A classical signature checked against a classical key would otherwise pass
+        let quantum_resistant = block
+            .quantum_signature
+            .as_ref()
+            .is_some_and(|signature| matches!(signature.sig_type, SignatureType::QuantumResistant));
+        if !quantum_resistant {
+            return Err(format!("Block {} has no quantum-resistant signature", block.header.height).into());
+        }
+        
+        if !block.verify_quantum_signature(&public_key)? {
+            return Err(format!("Invalid quantum signature on block {}", block.header.height).into());
+        }
+        
+        Ok(())
+    }
+}
+
+impl Consensus for HybridConsensus {
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("Initializing Quantum Proofed Hybrid Proof consensus");
+        self.proof_of_ai.init()
+    }
+    
+    fn create_block(&self, blockchain: &Blockchain) -> Result<Block, Box<dyn std::error::Error>> {
+        let components = &blockchain.get_spec().consensus;
+        let (address, keypair) = self.validator.as_ref().ok_or("No validator key configured for block production")?;
+        
+        // Create a new block on top of the tip
+        let mut block = blockchain.build_block(blockchain.get_block_candidates())?;
+        block.set_evidence(blockchain.get_pending_evidence());
+        block.header.producer = Some(address.clone());
+        
+        if components.scheduled_producer {
+            let scheduled = blockchain.get_scheduled_producer_of(&block.header);
+            if scheduled.as_ref().map(|validator| &validator.address) != Some(address) {
+                return Err(format!("{} is not scheduled to produce block {}", address, block.header.height).into());
+            }
+        }
+        
+        blockchain.contribute_randomness(&mut block, address, keypair)?;
+        
+        // The AI proof and signatures all commit to the finished header
+        if components.ai_proof {
+            let ai_proof = self.proof_of_ai.generate_ai_proof(&block, keypair, blockchain)?;
+            block.add_ai_proof(ai_proof.to_bytes());
+        }
+        
+        block.sign_as(address, keypair);
+        
+        if components.quantum_signature {
+            block.sign_quantum(keypair)?;
+        }
+        
+        Ok(block)
+    }
+    
+    fn validate_block(&self, block: &Block, blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        let components = &blockchain.get_spec().consensus;
+        
+        // Until the first validators are elected, the chain grows by blocks nobody produced
+        let bootstrapping = blockchain
+            .get_validator_set_at(block.header.height)
+            .is_some_and(|validators| validators.is_empty());
+        if bootstrapping && block.header.producer.is_none() {
+            return Ok(());
+        }
+        
+        if components.scheduled_producer {
+            self.verify_producer(block, blockchain)?;
+        }
+        
+        if components.ai_proof {
+            self.proof_of_ai.verify_ai_proof(block, blockchain)?;
+        }
+        
+        if components.quantum_signature {
+            self.verify_quantum_signature(block, blockchain)?;
+        }
+        
+        Ok(())
+    }
+    
+    fn name(&self) -> &'static str {
+        "Quantum Proofed Hybrid Proof (QPHP)"
+    }
+}
+
+/// Instant-seal consensus for single-node development chains
+pub struct DevConsensus {
+    /// Seal on a fixed interval instead of whenever a transaction arrives
+    block_interval: Option<Duration>,
+}
+
+impl DevConsensus {
+    /// Seal a block as soon as transactions are pending
+    pub fn instant() -> Self {
+        DevConsensus { block_interval: None }
+    }
+    
+    /// Seal a block on a fixed interval, even if it is empty
+    pub fn interval(block_interval: Duration) -> Self {
+        DevConsensus {
+            block_interval: Some(block_interval),
+        }
+    }
+    
+    /// Seal and import the next block, returning it if one was sealed
+    pub fn seal(&self, blockchain: &Blockchain) -> Result<Option<Block>, Box<dyn std::error::Error>> {
+        let block = self.create_block(blockchain)?;
+        
+        if block.transactions.is_empty() && self.block_interval.is_none() {
+            return Ok(None);
+        }
+        
+        self.validate_block(&block, blockchain)?;
+        
+        match blockchain.add_block(block.clone()) {
+            Ok(()) => Ok(Some(block)),
+            Err(e) if !block.transactions.is_empty() => {
+                warn!("Failed to seal {} pending transactions, sealing them one by one: {}", block.transactions.len(), e);
+                self.seal_individually(blockchain, block.transactions)
+            }
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Seal each transaction in its own block, dropping the ones that fail to apply
+    fn seal_individually(
+        &self,
+        blockchain: &Blockchain,
+        transactions: Vec<Transaction>,
+    ) -> Result<Option<Block>, Box<dyn std::error::Error>> {
+        let mut sealed = None;
+        
+        for tx in transactions {
+            let mut block = blockchain.build_block(vec![tx.clone()])?;
+            block.set_evidence(blockchain.get_pending_evidence());
+            
+            match blockchain.add_block(block.clone()) {
+                Ok(()) => sealed = Some(block),
+                Err(e) => {
+                    warn!("Dropping transaction {}: {}", tx.hash(), e);
+                    blockchain.remove_pending_transaction(&tx.hash());
+                }
+            }
+        }
+        
+        Ok(sealed)
+    }
+    
+    /// Seal blocks until the task is cancelled
+    pub async fn run(&self, blockchain: Arc<Blockchain>) {
+        let notifier = blockchain.transaction_notifier();
+        
+        loop {
+            match self.block_interval {
+                Some(interval) => tokio::time::sleep(interval).await,
+                None => notifier.notified().await,
+            }
+            
+            match self.seal(&blockchain) {
+                Ok(Some(block)) => info!(
+                    "Sealed block {} with {} transactions",
+                    block.header.height,
+                    block.transactions.len()
+                ),
+                Ok(None) => {}
+                Err(e) => error!("Failed to seal block: {}", e),
+            }
+        }
+    }
+}
+
+impl Consensus for DevConsensus {
+    fn init(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        info!("Initializing instant-seal development consensus");
+        
+        Ok(())
+    }
+    
+    fn create_block(&self, blockchain: &Blockchain) -> Result<Block, Box<dyn std::error::Error>> {
+        let mut block = blockchain.build_block(blockchain.get_block_candidates())?;
+        block.set_evidence(blockchain.get_pending_evidence());
+        
+        Ok(block)
+    }
+    
+    fn validate_block(&self, _block: &Block, _blockchain: &Blockchain) -> Result<(), Box<dyn std::error::Error>> {
+        // Development chains trust their only node
+        Ok(())
+    }
+    
+    fn name(&self) -> &'static str {
+        "Instant Seal (Dev)"
+    }
 }
\ No newline at end of file
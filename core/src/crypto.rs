@@ -3,7 +3,7 @@ use ed25519_dalek::{Keypair as Ed25519Keypair, PublicKey, SecretKey, Signature a
 use rand::rngs::OsRng;
 
 /// Signature wrapper for HyperNova Chain
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Signature {
     /// Signature bytes
     pub bytes: Vec<u8>,
@@ -11,8 +11,33 @@ pub struct Signature {
     pub sig_type: SignatureType,
 }
 
+impl Signature {
+    /// Verify this signature over a message with a hex-encoded public key
+    pub fn verify(&self, public_key: &str, message: &[u8]) -> Result<bool, Box<dyn std::error::Error>> {
+        match self.sig_type {
+            SignatureType::Ed25519 => {
+                let public_key = PublicKey::from_bytes(&hex::decode(public_key)?)?;
+                let signature = match Ed25519Signature::from_bytes(&self.bytes) {
+                    Ok(signature) => signature,
+                    Err(_) => return Ok(false),
+                };
+                
+                Ok(public_key.verify_strict(message, &signature).is_ok())
+            }
+            SignatureType::QuantumResistant => {
+                QuantumResistantCrypto::verify(&hex::decode(public_key)?, message, &self.bytes)
+            }
+        }
+    }
+}
+
+/// Check a hex string encodes an Ed25519 public key
+pub fn is_public_key(public_key: &str) -> bool {
+    hex::decode(public_key).is_ok_and(|bytes| PublicKey::from_bytes(&bytes).is_ok())
+}
+
 /// Supported signature types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SignatureType {
     /// Ed25519 signature
     Ed25519,
@@ -40,6 +65,17 @@ impl KeyPair {
         })
     }
     
+    /// Derive a key pair from a 32-byte seed, for reproducible keys in tests and simulations
+    pub fn from_seed(seed: &[u8; 32]) -> Result<Self, Box<dyn std::error::Error>> {
+        let secret = SecretKey::from_bytes(seed)?;
+        let public = PublicKey::from(&secret);
+        
+        Ok(KeyPair {
+            ed25519_keypair: Ed25519Keypair { secret, public },
+            quantum_keypair: None,
+        })
+    }
+    
     /// Get the public key as a hex string
     pub fn public_key_hex(&self) -> String {
         hex::encode(self.ed25519_keypair.public.as_bytes())
@@ -0,0 +1,190 @@
+use std::time::Duration;
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use tokio::time::Instant;
+
+/// Most nodes kept per bucket
+pub const BUCKET_SIZE: usize = 16;
+
+/// Number of peers queried in parallel by a lookup
+pub const LOOKUP_PARALLELISM: usize = 3;
+
+/// Number of bits in a node id
+const ID_BITS: usize = 256;
+
+/// Node that can be dialed, identified by its node public key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeRecord {
+    /// Hex-encoded node public key
+    pub public_key: String,
+    /// Address the node listens on, as host:port
+    pub address: String,
+}
+
+/// Message of the discovery protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DiscoveryMessage {
+    /// Ask for the nodes closest to a target id
+    FindNode {
+        /// Hex-encoded target id
+        target: String,
+    },
+    /// Nodes closest to a requested target
+    Nodes {
+        /// Known nodes, closest first
+        nodes: Vec<NodeRecord>,
+    },
+}
+
+impl DiscoveryMessage {
+    /// Encode the message for the wire
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+    
+    /// Decode a message from the wire
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(data)?)
+    }
+}
+
+/// Decode a hex node public key into a node id
+pub fn node_id(public_key: &str) -> Option<[u8; 32]> {
+    hex::decode(public_key).ok()?.try_into().ok()
+}
+
+/// Get the XOR distance between two node ids
+pub fn distance(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut distance = [0u8; 32];
+    for (i, byte) in distance.iter_mut().enumerate() {
+        *byte = a[i] ^ b[i];
+    }
+    distance
+}
+
+/// Kademlia routing table of known nodes, bucketed by distance from the local node
+pub struct RoutingTable {
+    /// Id of the local node
+    local: [u8; 32],
+    /// Buckets by the length of the prefix shared with the local id, oldest first
+    buckets: Vec<Vec<NodeRecord>>,
+    /// Last time a lookup targeted each bucket
+    refreshed: Vec<Option<Instant>>,
+}
+
+impl RoutingTable {
+    /// Create an empty table for a node
+    pub fn new(local: [u8; 32]) -> Self {
+        RoutingTable {
+            local,
+            buckets: vec![Vec::new(); ID_BITS],
+            refreshed: vec![None; ID_BITS],
+        }
+    }
+    
+    /// Get the bucket of a node id, or `None` for the local id
+    fn bucket_index(&self, id: &[u8; 32]) -> Option<usize> {
+        let distance = distance(&self.local, id);
+        let leading_zeros = distance
+            .iter()
+            .position(|byte| *byte != 0)
+            .map(|i| i * 8 + distance[i].leading_zeros() as usize)?;
+        
+        Some(leading_zeros)
+    }
+    
+    /// Add or refresh a node, returning whether it is in the table
+    ///
+    /// Known nodes move to the back of their bucket. New nodes are dropped when
+    /// their bucket is full, favouring nodes that have stayed reachable.
+    pub fn insert(&mut self, record: NodeRecord) -> bool {
+        let index = match node_id(&record.public_key).and_then(|id| self.bucket_index(&id)) {
+            Some(index) => index,
+            None => return false,
+        };
+        
+        let bucket = &mut self.buckets[index];
+        if let Some(position) = bucket.iter().position(|known| known.public_key == record.public_key) {
+            bucket.remove(position);
+        } else if bucket.len() >= BUCKET_SIZE {
+            return false;
+        }
+        
+        bucket.push(record);
+        true
+    }
+    
+    /// Remove a node
+    pub fn remove(&mut self, public_key: &str) {
+        if let Some(index) = node_id(public_key).and_then(|id| self.bucket_index(&id)) {
+            self.buckets[index].retain(|record| record.public_key != public_key);
+        }
+    }
+    
+    /// Get a known node
+    pub fn get(&self, public_key: &str) -> Option<&NodeRecord> {
+        let index = node_id(public_key).and_then(|id| self.bucket_index(&id))?;
+        self.buckets[index].iter().find(|record| record.public_key == public_key)
+    }
+    
+    /// Get the known nodes closest to a target id
+    pub fn closest(&self, target: &[u8; 32], count: usize) -> Vec<NodeRecord> {
+        let mut records: Vec<(_, &NodeRecord)> = self
+            .records()
+            .filter_map(|record| Some((distance(&node_id(&record.public_key)?, target), record)))
+            .collect();
+        records.sort_by_key(|(distance, _)| *distance);
+        
+        records.into_iter().take(count).map(|(_, record)| record.clone()).collect()
+    }
+    
+    /// Iterate over all known nodes
+    pub fn records(&self) -> impl Iterator<Item = &NodeRecord> {
+        self.buckets.iter().flatten()
+    }
+    
+    /// Get the number of known nodes
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(Vec::len).sum()
+    }
+    
+    /// Check whether no node is known
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Get lookup targets for the non-empty buckets not refreshed within an interval
+    ///
+    /// Each target is a random id falling in its bucket, and the bucket is marked
+    /// as refreshed.
+    pub fn refresh_targets<R: Rng + ?Sized>(&mut self, interval: Duration, rng: &mut R) -> Vec<[u8; 32]> {
+        let now = Instant::now();
+        let mut targets = Vec::new();
+        
+        for index in 0..ID_BITS {
+            let stale = self.refreshed[index].is_none_or(|at| now.duration_since(at) >= interval);
+            if self.buckets[index].is_empty() || !stale {
+                continue;
+            }
+            
+            targets.push(self.random_id_in_bucket(index, rng));
+            self.refreshed[index] = Some(now);
+        }
+        
+        targets
+    }
+    
+    /// Get a random id sharing exactly `index` leading bits with the local id
+    fn random_id_in_bucket<R: Rng + ?Sized>(&self, index: usize, rng: &mut R) -> [u8; 32] {
+        let mut id: [u8; 32] = rng.gen();
+        let (byte, bit) = (index / 8, index % 8);
+        
+        // Copy the shared prefix, then flip the first differing bit
+        id[..byte].copy_from_slice(&self.local[..byte]);
+        let prefix_mask = !(0xffu8 >> bit);
+        let flip = 0x80u8 >> bit;
+        id[byte] = (self.local[byte] & prefix_mask) | ((!self.local[byte]) & flip) | (id[byte] & !(prefix_mask | flip));
+        
+        id
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
+
+/// Validator allowed to sign blocks during an epoch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSetEntry {
+    /// Validator's address
+    pub address: String,
+    /// Ed25519 public key used to sign blocks (hex)
+    pub public_key: String,
+    /// Stake the validator was elected with
+    pub stake: u64,
+}
+
+/// Calculate the hash a block header commits to for a validator set
+pub fn validator_set_hash(validators: &[ValidatorSetEntry]) -> String {
+    let set_json = serde_json::to_string(validators).unwrap();
+    let mut hasher = Sha256::new();
+    hasher.update(set_json.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Get the validator of a set whose turn it is to produce the block at a height
+///
+/// Each round passed over hands the slot to the next validator in the set.
+pub fn slot_leader(validators: &[ValidatorSetEntry], height: u64, round: u64) -> Option<&ValidatorSetEntry> {
+    if validators.is_empty() {
+        return None;
+    }
+    
+    let count = validators.len() as u64;
+    let slot = ((height % count + round % count) % count) as usize;
+    Some(&validators[slot])
+}
+
+/// Validator sets of the current, next and past epochs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EpochState {
+    /// Epoch in progress
+    current_epoch: u64,
+    /// Validators of the epoch in progress, in production order
+    current: Vec<ValidatorSetEntry>,
+    /// Validators elected for the next epoch
+    queued: Option<Vec<ValidatorSetEntry>>,
+    /// Validator sets by the first epoch they applied to, recorded whenever the set changed
+    history: BTreeMap<u64, Vec<ValidatorSetEntry>>,
+}
+
+impl EpochState {
+    /// Create the epoch state of genesis with its initial validator set
+    pub fn genesis(validators: Vec<ValidatorSetEntry>) -> Self {
+        let mut history = BTreeMap::new();
+        history.insert(0, validators.clone());
+        
+        EpochState {
+            current_epoch: 0,
+            current: validators,
+            queued: None,
+            history,
+        }
+    }
+    
+    /// Get the epoch in progress
+    pub fn current_epoch(&self) -> u64 {
+        self.current_epoch
+    }
+    
+    /// Get the validators of the epoch in progress
+    pub fn current(&self) -> &[ValidatorSetEntry] {
+        &self.current
+    }
+    
+    /// Get the validators queued for the next epoch
+    pub fn queued(&self) -> Option<&[ValidatorSetEntry]> {
+        self.queued.as_deref()
+    }
+    
+    /// Queue the validators elected for the next epoch
+    pub fn queue(&mut self, validators: Vec<ValidatorSetEntry>) {
+        self.queued = Some(validators);
+    }
+    
+    /// Hash the first block of the next epoch must commit to
+    pub fn next_set_hash(&self) -> Option<String> {
+        self.queued.as_deref().map(validator_set_hash)
+    }
+    
+    /// Start a new epoch with the queued validators
+    pub fn rotate(&mut self, epoch: u64) {
+        if let Some(validators) = self.queued.take() {
+            if validators != self.current {
+                self.history.insert(epoch, validators.clone());
+            }
+            self.current = validators;
+        }
+        
+        self.current_epoch = epoch;
+    }
+    
+    /// Get the validator scheduled to produce the block at a height in a round
+    pub fn scheduled_producer(&self, height: u64, round: u64) -> Option<&str> {
+        slot_leader(&self.current, height, round).map(|validator| validator.address.as_str())
+    }
+
+    
+    /// Get the validator set of an epoch, including the next epoch once it has been elected
+    pub fn set_for_epoch(&self, epoch: u64) -> Option<Vec<ValidatorSetEntry>> {
+        if epoch == self.current_epoch + 1 {
+            return self.queued.clone();
+        }
+        
+        if epoch > self.current_epoch {
+            return None;
+        }
+        
+        self.history
+            .range(..=epoch)
+            .next_back()
+            .map(|(_, validators)| validators.clone())
+    }
+}
@@ -0,0 +1,133 @@
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use log::warn;
+
+use crate::block::{Block, BlockHeader};
+use crate::crypto::Signature;
+use crate::staking::StakingState;
+
+/// Number of heights around the chain tip the detector remembers
+const DETECTION_WINDOW: u64 = 1000;
+
+/// Signed headers seen at one height, by producer
+type SignedHeaders = HashMap<String, (BlockHeader, Signature)>;
+
+/// Proof that a validator signed two different blocks at the same height
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoubleSignEvidence {
+    /// Address of the equivocating validator
+    pub validator: String,
+    /// First signed header
+    pub header_a: BlockHeader,
+    /// Signature over the first header
+    pub signature_a: Signature,
+    /// Second signed header
+    pub header_b: BlockHeader,
+    /// Signature over the second header
+    pub signature_b: Signature,
+}
+
+impl DoubleSignEvidence {
+    /// Height at which the validator equivocated
+    pub fn height(&self) -> u64 {
+        self.header_a.height
+    }
+    
+    /// Identifier of the infraction, so the same equivocation is only punished once
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.validator, self.height())
+    }
+    
+    /// Verify the evidence against the registered validator keys
+    pub fn verify(&self, staking: &StakingState) -> Result<(), Box<dyn std::error::Error>> {
+        if self.header_a.height != self.header_b.height {
+            return Err("Evidence headers are at different heights".into());
+        }
+        
+        if self.header_a.hash() == self.header_b.hash() {
+            return Err("Evidence headers are identical".into());
+        }
+        
+        for header in [&self.header_a, &self.header_b] {
+            if header.producer.as_deref() != Some(self.validator.as_str()) {
+                return Err("Evidence header was not produced by the accused validator".into());
+            }
+        }
+        
+        let record = staking
+            .validator(&self.validator)
+            .ok_or_else(|| format!("Validator not found: {}", self.validator))?;
+        
+        for (header, signature) in [(&self.header_a, &self.signature_a), (&self.header_b, &self.signature_b)] {
+            if !signature.verify(&record.public_key, header.hash().as_bytes())? {
+                return Err("Invalid signature in evidence".into());
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// Detects validators signing conflicting blocks from the blocks a node observes
+pub struct EquivocationDetector {
+    /// First signed header seen for each height and producer
+    seen: Arc<Mutex<BTreeMap<u64, SignedHeaders>>>,
+}
+
+impl EquivocationDetector {
+    /// Create a new detector
+    pub fn new() -> Self {
+        EquivocationDetector {
+            seen: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+    
+    /// Record an observed block, returning evidence if its producer already signed another block at this height
+    ///
+    /// The window follows the chain tip, so blocks claiming far-future heights cannot evict real ones.
+    pub fn observe(&self, block: &Block, tip: u64) -> Option<DoubleSignEvidence> {
+        let producer = block.header.producer.as_ref()?;
+        let signature = block.validator_signature.as_ref()?;
+        let height = block.header.height;
+        
+        let mut seen = self.seen.lock().unwrap();
+        
+        // Forget heights that fell out of the detection window
+        let retain_from = tip.saturating_sub(DETECTION_WINDOW);
+        let retain_to = tip.saturating_add(DETECTION_WINDOW);
+        *seen = seen.split_off(&retain_from);
+        seen.split_off(&retain_to.saturating_add(1));
+        
+        if height < retain_from || height > retain_to {
+            return None;
+        }
+        
+        let at_height = seen.entry(height).or_default();
+        
+        match at_height.get(producer) {
+            Some((header, first_signature)) if header.hash() != block.hash() => {
+                warn!("Validator {} signed two blocks at height {}", producer, height);
+                
+                Some(DoubleSignEvidence {
+                    validator: producer.clone(),
+                    header_a: header.clone(),
+                    signature_a: first_signature.clone(),
+                    header_b: block.header.clone(),
+                    signature_b: signature.clone(),
+                })
+            }
+            Some(_) => None,
+            None => {
+                at_height.insert(producer.clone(), (block.header.clone(), signature.clone()));
+                None
+            }
+        }
+    }
+}
+
+impl Default for EquivocationDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
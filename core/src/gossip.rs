@@ -0,0 +1,287 @@
+use std::collections::{HashSet, VecDeque};
+use serde::{Deserialize, Serialize};
+use crate::block::Block;
+use crate::chain::Blockchain;
+use crate::crypto::{KeyPair, Signature};
+use crate::staking::ValidatorStatus;
+use crate::transaction::Transaction;
+use crate::utils;
+
+/// Kind of content spread through gossip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Topic {
+    /// Pending transactions
+    Transactions,
+    /// Newly produced blocks
+    Blocks,
+    /// Consensus votes
+    Votes,
+}
+
+impl Topic {
+    /// Get the byte identifying the topic on the wire
+    pub fn code(&self) -> u8 {
+        match self {
+            Topic::Transactions => 0,
+            Topic::Blocks => 1,
+            Topic::Votes => 2,
+        }
+    }
+    
+    /// Get the topic identified by a byte
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Topic::Transactions),
+            1 => Some(Topic::Blocks),
+            2 => Some(Topic::Votes),
+            _ => None,
+        }
+    }
+}
+
+/// Message spread to the whole network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GossipMessage {
+    /// Topic of the message
+    pub topic: Topic,
+    /// Encoded transaction, block or vote
+    pub data: Vec<u8>,
+}
+
+impl GossipMessage {
+    /// Create a message on a topic
+    pub fn new(topic: Topic, data: Vec<u8>) -> Self {
+        GossipMessage { topic, data }
+    }
+    
+    /// Get the hash identifying the message, the same at every node
+    pub fn id(&self) -> String {
+        utils::sha256(&self.encode())
+    }
+    
+    /// Encode the message for the wire as its topic byte followed by its data
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(self.data.len() + 1);
+        encoded.push(self.topic.code());
+        encoded.extend_from_slice(&self.data);
+        encoded
+    }
+    
+    /// Decode a message from the wire
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let (code, data) = data.split_first().ok_or("Empty gossip message")?;
+        let topic = Topic::from_code(*code).ok_or_else(|| format!("Unknown gossip topic {}", code))?;
+        
+        Ok(GossipMessage::new(topic, data.to_vec()))
+    }
+}
+
+/// Attestation by a verification committee member that it re-verified a block's AI proof
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    /// Address of the voting validator
+    pub validator: String,
+    /// Height of the block voted for
+    pub height: u64,
+    /// Hash of the block voted for
+    pub block_hash: String,
+    /// Signature of the validator over the height and block hash
+    pub signature: Signature,
+}
+
+impl Vote {
+    /// Create a vote for a block signed by a validator
+    pub fn new(validator: &str, block: &Block, keypair: &KeyPair) -> Self {
+        let (height, block_hash) = (block.header.height, block.hash());
+        let signature = keypair.sign(&Self::message(height, &block_hash));
+        
+        Vote {
+            validator: validator.to_string(),
+            height,
+            block_hash,
+            signature,
+        }
+    }
+    
+    /// Get the bytes a vote for a block signs
+    fn message(height: u64, block_hash: &str) -> Vec<u8> {
+        format!("vote:{}:{}", height, block_hash).into_bytes()
+    }
+    
+    /// Verify the vote was signed with a validator's public key
+    pub fn verify(&self, public_key: &str) -> bool {
+        self.signature
+            .verify(public_key, &Self::message(self.height, &self.block_hash))
+            .unwrap_or(false)
+    }
+}
+
+/// Bounded set of the ids of recently seen messages, forgetting the oldest first
+pub struct SeenCache {
+    /// Most ids remembered
+    capacity: usize,
+    /// Remembered ids
+    ids: HashSet<String>,
+    /// Remembered ids, oldest first
+    order: VecDeque<String>,
+}
+
+impl SeenCache {
+    /// Create an empty cache
+    pub fn new(capacity: usize) -> Self {
+        SeenCache {
+            capacity,
+            ids: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+    
+    /// Remember an id, returning whether it was not seen before
+    pub fn insert(&mut self, id: &str) -> bool {
+        if self.capacity == 0 || self.ids.contains(id) {
+            return false;
+        }
+        
+        while self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        
+        self.ids.insert(id.to_string());
+        self.order.push_back(id.to_string());
+        true
+    }
+    
+    /// Check whether an id was seen
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+    
+    /// Get the number of remembered ids
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+    
+    /// Check whether no id is remembered
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+}
+
+/// Outcome of validating a gossip message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Message is valid, deliver and forward it
+    Accept,
+    /// Message is not useful, such as a known block, drop it without blaming the sender
+    Ignore,
+    /// Message is malformed or forged, drop it and the peer that sent it
+    Reject,
+}
+
+/// Check gossip messages before they are delivered and forwarded
+pub trait GossipValidator: Send + Sync {
+    /// Validate a message, importing it if it is valid
+    fn validate(&self, message: &GossipMessage) -> Verdict;
+}
+
+impl GossipValidator for Blockchain {
+    /// Add gossiped transactions to the pending pool and gossiped blocks to the chain
+    ///
+    /// Blocks this node cannot place yet, with an unknown parent, and blocks already
+    /// built upon are ignored. Blocks extending the tip must be signed by the validator
+    /// scheduled for their slot, or by an active validator on chains without a schedule,
+    /// and must pass the chain's consensus engine and apply. No block may have roots
+    /// that do not match its contents.
+    ///
+    /// Votes are only checked for blocks on this node's chain, and must be signed by a
+    /// member of the block's verification committee.
+    fn validate(&self, message: &GossipMessage) -> Verdict {
+        match message.topic {
+            Topic::Transactions => {
+                let transaction: Transaction = match serde_json::from_slice(&message.data) {
+                    Ok(transaction) => transaction,
+                    Err(_) => return Verdict::Reject,
+                };
+                
+                if self.check_transaction_signature(&transaction).is_err() {
+                    return Verdict::Reject;
+                }
+                
+                match self.add_transaction(transaction) {
+                    Ok(_) => Verdict::Accept,
+                    Err(_) => Verdict::Ignore,
+                }
+            }
+            Topic::Blocks => {
+                let block: Block = match serde_json::from_slice(&message.data) {
+                    Ok(block) => block,
+                    Err(_) => return Verdict::Reject,
+                };
+                
+                if !block.has_valid_roots() {
+                    return Verdict::Reject;
+                }
+                
+                // Blocks that do not extend the tip may be forks or from peers ahead of this node
+                let extends_tip = |tip: Option<Block>| tip.is_some_and(|tip| tip.hash() == block.header.prev_hash);
+                if self.get_block_by_hash(&block.hash()).is_some() || !extends_tip(self.get_latest_block()) {
+                    return Verdict::Ignore;
+                }
+                
+                // Only the scheduled producer may extend the chain, so others cannot take its slots
+                let scheduled = self.get_spec().consensus.scheduled_producer;
+                let signed = match &block.header.producer {
+                    Some(producer) => {
+                        let public_key = if scheduled {
+                            self.get_scheduled_producer_of(&block.header)
+                                .filter(|validator| validator.address == *producer)
+                                .map(|validator| validator.public_key)
+                        } else {
+                            self.get_validator(producer)
+                                .filter(|record| record.status == ValidatorStatus::Active)
+                                .map(|record| record.public_key)
+                        };
+                        public_key.is_some_and(|public_key| block.verify_signature(&public_key).unwrap_or(false))
+                    }
+                    // Nobody is scheduled before the first validators are elected
+                    None => !scheduled || self
+                        .get_validator_set_at(block.header.height)
+                        .is_some_and(|validators| validators.is_empty()),
+                };
+                if !signed {
+                    return Verdict::Reject;
+                }
+                
+                match self.add_block(block.clone()) {
+                    Ok(_) => Verdict::Accept,
+                    // Another block may have been added first
+                    Err(_) if !extends_tip(self.get_latest_block()) => Verdict::Ignore,
+                    Err(_) => Verdict::Reject,
+                }
+            }
+            Topic::Votes => {
+                let vote: Vote = match serde_json::from_slice(&message.data) {
+                    Ok(vote) => vote,
+                    Err(_) => return Verdict::Reject,
+                };
+                
+                // Votes may be for blocks this node has not received yet, or for forks
+                if self.get_block_by_height(vote.height).is_none_or(|block| block.hash() != vote.block_hash) {
+                    return Verdict::Ignore;
+                }
+                
+                let member = self
+                    .get_verification_committee(vote.height)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .find(|member| member.address == vote.validator);
+                match member {
+                    Some(member) if vote.verify(&member.public_key) => Verdict::Accept,
+                    _ => Verdict::Reject,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::epoch::ValidatorSetEntry;
+
+/// Change a governance proposal makes once passed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProposalAction {
+    /// Allow a pending model to be used for Proof of AI
+    ApproveModel {
+        /// Model to approve
+        model_id: String,
+    },
+    /// Stop accepting proofs from an approved model
+    RetireModel {
+        /// Model to retire
+        model_id: String,
+    },
+}
+
+impl ProposalAction {
+    /// Encode the action as governance proposal data
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+    
+    /// Decode an action from governance proposal data
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(data)?)
+    }
+}
+
+/// Outcome of a governance proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProposalStatus {
+    /// Validators can still vote
+    Voting,
+    /// Enough stake voted yes and the action was applied
+    Passed,
+    /// Voting ended without enough support, or the action no longer applied
+    Rejected,
+}
+
+/// Validator vote on a proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vote {
+    /// Proposal voted on
+    pub proposal_id: u64,
+    /// Whether the validator supports the proposal
+    pub approve: bool,
+}
+
+impl Vote {
+    /// Encode the vote as governance vote data
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+    
+    /// Decode a vote from governance vote data
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(data)?)
+    }
+}
+
+/// Governance proposal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    /// Proposal identifier
+    pub id: u64,
+    /// Account that submitted the proposal
+    pub proposer: String,
+    /// Change made if the proposal passes
+    pub action: ProposalAction,
+    /// Height the proposal was submitted at
+    pub submitted_height: u64,
+    /// Last height votes are accepted at
+    pub voting_ends: u64,
+    /// Votes by validator address
+    pub votes: BTreeMap<String, bool>,
+    /// Current status
+    pub status: ProposalStatus,
+}
+
+impl Proposal {
+    /// Check whether validators holding enough of the stake voted yes
+    pub fn has_passed(&self, validators: &[ValidatorSetEntry], threshold_bps: u64) -> bool {
+        let total: u128 = validators.iter().map(|validator| u128::from(validator.stake)).sum();
+        let yes: u128 = validators
+            .iter()
+            .filter(|validator| self.votes.get(&validator.address) == Some(&true))
+            .map(|validator| u128::from(validator.stake))
+            .sum();
+        
+        total > 0 && yes * 10000 >= total * u128::from(threshold_bps)
+    }
+}
+
+/// Governance proposals by id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GovernanceState {
+    /// Identifier of the next proposal
+    next_id: u64,
+    /// All proposals
+    proposals: BTreeMap<u64, Proposal>,
+    /// Proposals still open for voting, by last voting height and id
+    open: BTreeSet<(u64, u64)>,
+}
+
+impl GovernanceState {
+    /// Open a proposal for voting, returning its id
+    pub fn submit(&mut self, proposer: &str, action: ProposalAction, height: u64, voting_period: u64) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        
+        let voting_ends = height + voting_period;
+        self.proposals.insert(id, Proposal {
+            id,
+            proposer: proposer.to_string(),
+            action,
+            submitted_height: height,
+            voting_ends,
+            votes: BTreeMap::new(),
+            status: ProposalStatus::Voting,
+        });
+        self.open.insert((voting_ends, id));
+        
+        id
+    }
+    
+    /// Record a validator's vote, replacing any earlier vote
+    pub fn vote(&mut self, voter: &str, vote: &Vote, height: u64) -> Result<&Proposal, Box<dyn std::error::Error>> {
+        let proposal = self
+            .proposals
+            .get_mut(&vote.proposal_id)
+            .ok_or_else(|| format!("Proposal not found: {}", vote.proposal_id))?;
+        
+        if proposal.status != ProposalStatus::Voting || height > proposal.voting_ends {
+            return Err(format!("Proposal {} is closed for voting", vote.proposal_id).into());
+        }
+        
+        proposal.votes.insert(voter.to_string(), vote.approve);
+        
+        Ok(proposal)
+    }
+    
+    /// Set the outcome of a proposal
+    pub fn close(&mut self, id: u64, status: ProposalStatus) {
+        if let Some(proposal) = self.proposals.get_mut(&id) {
+            proposal.status = status;
+            self.open.remove(&(proposal.voting_ends, id));
+        }
+    }
+    
+    /// Reject proposals whose voting period ended before a height
+    ///
+    /// Only open proposals are visited, so finished ones add no cost to later blocks.
+    pub fn expire(&mut self, height: u64) {
+        while let Some(&(voting_ends, id)) = self.open.first() {
+            if voting_ends >= height {
+                break;
+            }
+            
+            self.close(id, ProposalStatus::Rejected);
+        }
+    }
+    
+    /// Get a proposal
+    pub fn proposal(&self, id: u64) -> Option<&Proposal> {
+        self.proposals.get(&id)
+    }
+    
+    /// Iterate over all proposals
+    pub fn proposals(&self) -> impl Iterator<Item = &Proposal> {
+        self.proposals.values()
+    }
+}
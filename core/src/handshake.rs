@@ -0,0 +1,220 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::chain::Blockchain;
+use crate::crypto::KeyPair;
+
+/// Version of the peer protocol spoken by this node
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// Oldest protocol version this node can talk to
+pub const MIN_PROTOCOL_VERSION: u32 = 3;
+
+/// Service flag of nodes keeping the full chain and serving blocks
+pub const SERVICE_FULL_NODE: u64 = 1;
+
+/// Service flag of nodes producing blocks
+pub const SERVICE_VALIDATOR: u64 = 1 << 1;
+
+/// Service flag of nodes serving the JSON-RPC API
+pub const SERVICE_RPC: u64 = 1 << 2;
+
+/// Chain a node follows and its best block
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainStatus {
+    /// Chain identifier from the chain specification
+    pub chain_id: String,
+    /// Hash of the genesis block
+    pub genesis_hash: String,
+    /// Height of the best block
+    pub best_height: u64,
+    /// Hash of the best block
+    pub best_hash: String,
+}
+
+impl ChainStatus {
+    /// Get the status of a blockchain
+    pub fn from_blockchain(blockchain: &Blockchain) -> Self {
+        let genesis_hash = blockchain.get_block_by_height(0).map(|block| block.hash()).unwrap_or_default();
+        let (best_height, best_hash) = blockchain
+            .get_latest_block()
+            .map(|block| (block.header.height, block.hash()))
+            .unwrap_or_default();
+        
+        ChainStatus {
+            chain_id: blockchain.get_spec().chain_id.clone(),
+            genesis_hash,
+            best_height,
+            best_hash,
+        }
+    }
+}
+
+/// First message sent by each side once the connection is encrypted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    /// Protocol version of the sender
+    pub protocol_version: u32,
+    /// Chain followed by the sender
+    pub status: ChainStatus,
+    /// Hex-encoded Ed25519 node public key of the sender
+    pub public_key: String,
+    /// Services offered by the sender
+    pub services: u64,
+    /// Port the sender accepts connections on, 0 if it does not listen
+    #[serde(default)]
+    pub listen_port: u16,
+}
+
+impl Hello {
+    /// Create a hello for a node
+    pub fn new(keypair: &KeyPair, status: ChainStatus, services: u64) -> Self {
+        Hello {
+            protocol_version: PROTOCOL_VERSION,
+            status,
+            public_key: keypair.public_key_hex(),
+            services,
+            listen_port: 0,
+        }
+    }
+    
+    /// Check a peer's hello is compatible with this one
+    pub fn check_compatible(&self, remote: &Hello) -> Result<(), DisconnectReason> {
+        if remote.protocol_version < MIN_PROTOCOL_VERSION {
+            return Err(DisconnectReason::IncompatibleVersion);
+        }
+        
+        if remote.status.chain_id != self.status.chain_id {
+            return Err(DisconnectReason::WrongChain);
+        }
+        
+        if remote.status.genesis_hash != self.status.genesis_hash {
+            return Err(DisconnectReason::WrongGenesis);
+        }
+        
+        if hex::decode(&remote.public_key).map_or(true, |key| key.len() != 32) {
+            return Err(DisconnectReason::ProtocolViolation);
+        }
+        
+        if remote.public_key == self.public_key {
+            return Err(DisconnectReason::SelfConnection);
+        }
+        
+        Ok(())
+    }
+}
+
+/// Message exchanged while peers are connecting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HandshakeMessage {
+    /// Identity and chain of the sender
+    Hello(Hello),
+    /// Sender accepted the peer's hello and is ready for messages
+    Ready,
+    /// Sender is closing the connection
+    Disconnect(DisconnectReason),
+}
+
+impl HandshakeMessage {
+    /// Encode the message for the wire
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+    
+    /// Decode a message from the wire
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(data)?)
+    }
+}
+
+/// Reason a peer was disconnected during the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisconnectReason {
+    /// Peer sent a malformed or unexpected message
+    ProtocolViolation,
+    /// Peer speaks an unsupported protocol version
+    IncompatibleVersion,
+    /// Peer follows another chain
+    WrongChain,
+    /// Peer has another genesis block
+    WrongGenesis,
+    /// Peer announced another node key than the one it authenticated with
+    AuthenticationFailed,
+    /// Peer is this node
+    SelfConnection,
+    /// Node has no room for more peers
+    TooManyPeers,
+    /// Node is already connected to the peer
+    AlreadyConnected,
+    /// Peer did not complete the handshake in time
+    Timeout,
+    /// Peer is banned for misbehaving
+    Banned,
+}
+
+impl DisconnectReason {
+    /// Get the numeric code of the reason
+    pub fn code(&self) -> u8 {
+        match self {
+            DisconnectReason::ProtocolViolation => 1,
+            DisconnectReason::IncompatibleVersion => 2,
+            DisconnectReason::WrongChain => 3,
+            DisconnectReason::WrongGenesis => 4,
+            DisconnectReason::AuthenticationFailed => 5,
+            DisconnectReason::SelfConnection => 6,
+            DisconnectReason::TooManyPeers => 7,
+            DisconnectReason::AlreadyConnected => 8,
+            DisconnectReason::Timeout => 9,
+            DisconnectReason::Banned => 10,
+        }
+    }
+}
+
+impl fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            DisconnectReason::ProtocolViolation => "protocol violation",
+            DisconnectReason::IncompatibleVersion => "incompatible protocol version",
+            DisconnectReason::WrongChain => "wrong chain",
+            DisconnectReason::WrongGenesis => "wrong genesis block",
+            DisconnectReason::AuthenticationFailed => "authentication failed",
+            DisconnectReason::SelfConnection => "connected to self",
+            DisconnectReason::TooManyPeers => "too many peers",
+            DisconnectReason::AlreadyConnected => "already connected",
+            DisconnectReason::Timeout => "handshake timed out",
+            DisconnectReason::Banned => "banned",
+        };
+        
+        write!(f, "{} (code {})", description, self.code())
+    }
+}
+
+/// Identity and chain of a connected peer, as announced in its handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// Hex-encoded node public key of the peer
+    pub public_key: String,
+    /// Socket address the connection was made with
+    pub address: String,
+    /// Whether this node dialed the peer
+    pub outbound: bool,
+    /// Protocol version of the peer
+    pub protocol_version: u32,
+    /// Services offered by the peer
+    pub services: u64,
+    /// Chain status of the peer when it connected
+    pub status: ChainStatus,
+}
+
+impl PeerInfo {
+    /// Get the information announced in a peer's hello
+    pub fn from_hello(hello: Hello, address: String, outbound: bool) -> Self {
+        PeerInfo {
+            public_key: hello.public_key,
+            address,
+            outbound,
+            protocol_version: hello.protocol_version,
+            services: hello.services,
+            status: hello.status,
+        }
+    }
+}
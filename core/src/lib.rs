@@ -1,17 +1,45 @@
+pub mod addressbook;
+pub mod ai_client;
+pub mod ai_model;
+pub mod ai_proof;
+pub mod beacon;
 pub mod block;
 pub mod chain;
 pub mod consensus;
 pub mod crypto;
+pub mod discovery;
+pub mod epoch;
+pub mod evidence;
+pub mod gossip;
+pub mod governance;
+pub mod handshake;
+pub mod model_registry;
 pub mod network;
+pub mod noise;
+pub mod ratelimit;
+pub mod reputation;
+pub mod rewards;
+pub mod rpc;
+pub mod scoring;
+pub mod simulation;
+pub mod spec;
+pub mod staking;
+pub mod state;
 pub mod storage;
+pub mod sync;
+pub mod transport;
 pub mod transaction;
+pub mod uptime;
 pub mod utils;
 
 pub use block::Block;
 pub use chain::Blockchain;
-pub use consensus::{Consensus, ProofOfAI, DelegatedProofOfStake};
+pub use consensus::{Consensus, ProofOfAI, DelegatedProofOfStake, DevConsensus, HybridConsensus};
 pub use crypto::{KeyPair, Signature, QuantumResistantCrypto};
 pub use network::P2PNetwork;
+pub use rpc::RpcServer;
+pub use spec::ChainSpec;
+pub use state::ChainState;
 pub use storage::{Storage, DistributedQuantumStorage};
 pub use transaction::Transaction;
 
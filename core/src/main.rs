@@ -1,6 +1,23 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 use clap::Parser;
-use hypernova_core::{Blockchain, P2PNetwork, init};
-use log::{info, error};
+use hypernova_core::{Blockchain, ChainSpec, Consensus, DevConsensus, HybridConsensus, KeyPair, P2PNetwork, ProofOfAI, RpcServer, init};
+use hypernova_core::addressbook::AddressBook;
+use hypernova_core::ai_model::QuantizedModel;
+use hypernova_core::chain::ChainEvent;
+use hypernova_core::gossip::Topic;
+use hypernova_core::handshake::{ChainStatus, SERVICE_FULL_NODE, SERVICE_RPC, SERVICE_VALIDATOR};
+use hypernova_core::network::NetworkConfig;
+use hypernova_core::ratelimit::Rate;
+use hypernova_core::reputation::{Reputation, DEFAULT_BAN_DURATION};
+use hypernova_core::spec::{DEV_ACCOUNTS, DEV_ACCOUNT_BALANCE};
+use hypernova_core::sync::SyncManager;
+use log::{debug, info, warn, error};
+use tokio::sync::broadcast::error::RecvError;
 
 #[derive(Parser)]
 #[clap(name = "HyperNova Node")]
@@ -14,11 +31,43 @@ struct Cli {
     #[clap(long, default_value = "30303")]
     p2p_port: u16,
     
+    /// Peer to connect to on startup, as host:port (repeatable)
+    #[clap(long = "peer")]
+    peers: Vec<String>,
+    
     #[clap(long, default_value = "data")]
     data_dir: String,
     
     #[clap(long)]
     validator: bool,
+    
+    /// Base URL of the AI service proving blocks
+    #[clap(long, default_value = "http://localhost:8000")]
+    ai_endpoint: String,
+    
+    /// Quantized model file to re-evaluate AI proofs with (repeatable)
+    #[clap(long = "model")]
+    models: Vec<String>,
+    
+    /// Lowest AI proof confidence accepted, between 0 and 1
+    #[clap(long, default_value = "0.75")]
+    min_confidence: f64,
+    
+    /// Run a fresh single-node development chain with pre-funded accounts
+    #[clap(long)]
+    dev: bool,
+    
+    /// Seal development blocks every this many milliseconds instead of on each transaction
+    #[clap(long, requires = "dev")]
+    dev_block_time: Option<u64>,
+    
+    /// Most bytes per second read from all peers together
+    #[clap(long)]
+    max_inbound_bandwidth: Option<u64>,
+    
+    /// Most bytes per second written to all peers together
+    #[clap(long)]
+    max_outbound_bandwidth: Option<u64>,
 }
 
 #[tokio::main]
@@ -32,17 +81,78 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting HyperNova Chain node v{}", hypernova_core::VERSION);
     info!("Data directory: {}", args.data_dir);
     
+    if args.dev {
+        return run_dev(&args).await;
+    }
+    
+    // Blocks from peers must pass the same consensus rules as blocks produced here
+    let mut proof_of_ai = ProofOfAI::new(&args.ai_endpoint, args.min_confidence);
+    for path in &args.models {
+        proof_of_ai = proof_of_ai.with_model(QuantizedModel::from_file(path)?);
+    }
+    let mut consensus = HybridConsensus::new(proof_of_ai);
+    consensus.init()?;
+    
     // Initialize the blockchain
     let blockchain = match Blockchain::new(&args.data_dir) {
-        Ok(chain) => chain,
+        Ok(chain) => Arc::new(chain.with_consensus(Arc::new(consensus))),
         Err(e) => {
             error!("Failed to initialize blockchain: {}", e);
             return Err(e.into());
         }
     };
     
+    // Subscribe before peers can add anything, so all of it is gossiped
+    let mut events = blockchain.subscribe();
+    
     // Initialize the P2P network
-    let network = P2PNetwork::new(args.p2p_port)?;
+    let mut services = SERVICE_FULL_NODE | SERVICE_RPC;
+    if args.validator {
+        services |= SERVICE_VALIDATOR;
+    }
+    let network = P2PNetwork::new(args.p2p_port)?
+        .with_node_key(load_node_key(Path::new(&args.data_dir))?)
+        .with_services(services)
+        .with_config(NetworkConfig {
+            bootnodes: blockchain.get_spec().bootnodes.clone(),
+            max_inbound_bandwidth: args.max_inbound_bandwidth.map(|rate| Rate::new(rate, rate)),
+            max_outbound_bandwidth: args.max_outbound_bandwidth.map(|rate| Rate::new(rate, rate)),
+            ..NetworkConfig::default()
+        })
+        .with_gossip_validator(blockchain.clone())
+        .with_block_source(blockchain.clone())
+        .with_reputation(Reputation::open(&Path::new(&args.data_dir).join("banned_peers.json"), DEFAULT_BAN_DURATION))
+        .with_address_book(AddressBook::open(&Path::new(&args.data_dir).join("peers.json")));
+    let network = Arc::new(network);
+    network.set_chain_status(ChainStatus::from_blockchain(&blockchain));
+    network.start()?;
+    info!("Node public key: {}", network.public_key());
+    
+    for peer in &args.peers {
+        if let Err(e) = network.connect(peer) {
+            error!("Failed to connect to peer {}: {}", peer, e);
+        }
+    }
+    
+    if let Some(mut inbound) = network.take_inbound() {
+        tokio::spawn(async move {
+            while let Some(message) = inbound.recv().await {
+                debug!("Received {} bytes from peer {}", message.data.len(), message.peer);
+            }
+        });
+    }
+    
+    // Gossiped transactions and blocks are imported, and votes checked, by the validator before they are delivered
+    if let Some(mut gossip) = network.take_gossip() {
+        tokio::spawn(async move {
+            while let Some(message) = gossip.recv().await {
+                debug!("Imported gossip {} on {:?}", message.id(), message.topic);
+            }
+        });
+    }
+    
+    // Catch up with peers ahead of this node
+    tokio::spawn(SyncManager::new(blockchain.clone(), network.clone()).run());
     
     // Start the node
     if args.validator {
@@ -54,10 +164,93 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     // Start RPC server
-    info!("RPC server listening on port {}", args.rpc_port);
+    let rpc_server = Arc::new(RpcServer::new(blockchain.clone(), args.rpc_port).with_network(network.clone()));
+    tokio::spawn(async move {
+        if let Err(e) = rpc_server.start().await {
+            error!("RPC server failed: {}", e);
+        }
+    });
     
-    // Keep the main thread running
+    // Keep the main thread running, gossiping transactions and blocks as they are added
+    // and announcing the best block to new peers. Gossip that arrived from peers was
+    // already forwarded, so publishing it again sends nothing.
     loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+        match events.recv().await {
+            Ok(ChainEvent::Transaction(transaction)) => {
+                if let Err(e) = network.publish(Topic::Transactions, &serde_json::to_vec(&transaction)?) {
+                    error!("Failed to gossip transaction {}: {}", transaction.hash(), e);
+                }
+            }
+            Ok(ChainEvent::Block(block)) => {
+                network.set_chain_status(ChainStatus::from_blockchain(&blockchain));
+                
+                // Blocks already built upon, such as those fetched while catching up, are old news
+                if block.header.height < blockchain.get_height() {
+                    continue;
+                }
+                if let Err(e) = network.publish(Topic::Blocks, &serde_json::to_vec(&block)?) {
+                    error!("Failed to gossip block {}: {}", block.hash(), e);
+                }
+            }
+            Err(RecvError::Lagged(missed)) => warn!("Missed gossiping {} transactions and blocks", missed),
+            Err(RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+/// Load the node key from the data directory, creating it on first start
+fn load_node_key(data_dir: &Path) -> Result<KeyPair, Box<dyn std::error::Error>> {
+    let path = data_dir.join("node_key");
+    
+    let seed = if path.exists() {
+        // Anyone who can read the seed can impersonate the node
+        if std::fs::metadata(&path)?.permissions().mode() & 0o077 != 0 {
+            return Err(format!("Node key {} is readable by other users, restrict it with chmod 600", path.display()).into());
+        }
+        
+        let seed: [u8; 32] = hex::decode(std::fs::read_to_string(&path)?.trim())?
+            .try_into()
+            .map_err(|_| "Node key must be 32 bytes")?;
+        seed
+    } else {
+        let seed = rand::random::<[u8; 32]>();
+        std::fs::create_dir_all(data_dir)?;
+        let mut file = OpenOptions::new().write(true).create_new(true).mode(0o600).open(&path)?;
+        file.write_all(hex::encode(seed).as_bytes())?;
+        seed
+    };
+    
+    KeyPair::from_seed(&seed)
+}
+
+/// Run a development chain that seals blocks locally without validators or the AI service
+async fn run_dev(args: &Cli) -> Result<(), Box<dyn std::error::Error>> {
+    // Development chains start from genesis on every run
+    let data_dir = Path::new(&args.data_dir).join("dev");
+    if data_dir.exists() {
+        std::fs::remove_dir_all(&data_dir)?;
+    }
+    
+    let blockchain = Arc::new(Blockchain::with_spec(data_dir.to_str().ok_or("Invalid data directory")?, ChainSpec::dev())?);
+    
+    info!("Running development chain in {}", data_dir.display());
+    for account in DEV_ACCOUNTS {
+        info!("Dev account {} funded with {}", account, DEV_ACCOUNT_BALANCE);
+    }
+    
+    let consensus = match args.dev_block_time {
+        Some(millis) => DevConsensus::interval(Duration::from_millis(millis)),
+        None => DevConsensus::instant(),
+    };
+    
+    let sealing_chain = blockchain.clone();
+    tokio::spawn(async move { consensus.run(sealing_chain).await });
+    
+    let rpc_server = Arc::new(RpcServer::new(blockchain, args.rpc_port));
+    if let Err(e) = rpc_server.start().await {
+        error!("RPC server failed: {}", e);
+        return Err(e.to_string().into());
     }
-}
\ No newline at end of file
+    
+    Ok(())
+}
@@ -0,0 +1,236 @@
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Owner recorded for models approved in the chain specification
+pub const GENESIS_OWNER: &str = "genesis";
+
+/// Lifecycle of a model submitted for Proof of AI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ModelStatus {
+    /// Submitted and waiting for governance approval
+    Pending,
+    /// Approved by governance for consensus use
+    Approved,
+    /// Retired by governance, proofs from it are rejected
+    Retired,
+}
+
+/// Data of an AI model submission transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelSubmission {
+    /// Identifier proofs refer to the model by
+    pub model_id: String,
+    /// SHA-256 of the model file (hex)
+    pub model_hash: String,
+    /// Description of the model's layers
+    pub architecture: String,
+}
+
+impl ModelSubmission {
+    /// Encode the submission as transaction data
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+    
+    /// Decode a submission from transaction data
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let submission: ModelSubmission = serde_json::from_slice(data)?;
+        
+        if submission.model_id.is_empty() || submission.model_id.len() > 64 {
+            return Err("Model id must be between 1 and 64 characters".into());
+        }
+        
+        if hex::decode(&submission.model_hash).map_or(true, |hash| hash.len() != 32) {
+            return Err("Model hash must be a 32-byte hex string".into());
+        }
+        
+        if submission.architecture.is_empty() {
+            return Err("Model architecture must be described".into());
+        }
+        
+        Ok(submission)
+    }
+}
+
+/// Model registered on chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRecord {
+    /// Identifier proofs refer to the model by
+    pub model_id: String,
+    /// SHA-256 of the model file (hex)
+    pub model_hash: String,
+    /// Description of the model's layers
+    pub architecture: String,
+    /// Account that submitted the model
+    pub owner: String,
+    /// Tokens bonded by the owner until the model is retired
+    pub stake: u64,
+    /// Current status
+    pub status: ModelStatus,
+    /// Height the model was submitted at
+    pub submitted_height: u64,
+    /// Height the status last changed at
+    pub status_height: u64,
+    /// Last height a pending model can be approved at before its stake is released
+    pub pending_until: u64,
+}
+
+/// Models submitted for Proof of AI, by id
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelRegistry {
+    /// Registered models
+    models: BTreeMap<String, ModelRecord>,
+    /// Pending models by the last height they can be approved at
+    pending: BTreeSet<(u64, String)>,
+}
+
+impl ModelRegistry {
+    /// Register a model submitted by an account, pending approval for `approval_period` blocks
+    pub fn submit(
+        &mut self,
+        owner: &str,
+        submission: ModelSubmission,
+        stake: u64,
+        min_stake: u64,
+        height: u64,
+        approval_period: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if stake < min_stake {
+            return Err(format!("Model stake {} is below the minimum of {}", stake, min_stake).into());
+        }
+        
+        self.insert(ModelRecord {
+            model_id: submission.model_id,
+            model_hash: submission.model_hash,
+            architecture: submission.architecture,
+            owner: owner.to_string(),
+            stake,
+            status: ModelStatus::Pending,
+            submitted_height: height,
+            status_height: height,
+            pending_until: height.saturating_add(approval_period),
+        })
+    }
+    
+    /// Create the registry of models approved in the chain specification, rejecting invalid ones
+    pub fn genesis(models: &[ModelSubmission]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut registry = ModelRegistry::default();
+        for model in models {
+            registry
+                .register_genesis(ModelSubmission::decode(&model.encode())?)
+                .map_err(|e| format!("Invalid genesis model {}: {}", model.model_id, e))?;
+        }
+        
+        Ok(registry)
+    }
+    
+    /// Register a model approved in the chain specification
+    pub fn register_genesis(&mut self, submission: ModelSubmission) -> Result<(), Box<dyn std::error::Error>> {
+        self.insert(ModelRecord {
+            model_id: submission.model_id,
+            model_hash: submission.model_hash,
+            architecture: submission.architecture,
+            owner: GENESIS_OWNER.to_string(),
+            stake: 0,
+            status: ModelStatus::Approved,
+            submitted_height: 0,
+            status_height: 0,
+            pending_until: 0,
+        })
+    }
+    
+    /// Add a record, rejecting reused ids and model files
+    fn insert(&mut self, record: ModelRecord) -> Result<(), Box<dyn std::error::Error>> {
+        if self.models.contains_key(&record.model_id) {
+            return Err(format!("Model {} is already registered", record.model_id).into());
+        }
+        
+        if self.models.values().any(|model| model.model_hash == record.model_hash) {
+            return Err(format!("Model file {} is already registered", record.model_hash).into());
+        }
+        
+        if record.status == ModelStatus::Pending {
+            self.pending.insert((record.pending_until, record.model_id.clone()));
+        }
+        
+        self.models.insert(record.model_id.clone(), record);
+        
+        Ok(())
+    }
+    
+    /// Get a registered model
+    pub fn model(&self, model_id: &str) -> Option<&ModelRecord> {
+        self.models.get(model_id)
+    }
+    
+    /// Iterate over all registered models
+    pub fn models(&self) -> impl Iterator<Item = &ModelRecord> {
+        self.models.values()
+    }
+    
+    /// Check a model exists with a status
+    pub fn check_status(&self, model_id: &str, status: ModelStatus) -> Result<&ModelRecord, Box<dyn std::error::Error>> {
+        let record = self.model(model_id).ok_or_else(|| format!("Model not found: {}", model_id))?;
+        
+        if record.status != status {
+            return Err(format!("Model {} is {:?}, expected {:?}", model_id, record.status, status).into());
+        }
+        
+        Ok(record)
+    }
+    
+    /// Approve a pending model for consensus use
+    pub fn approve(&mut self, model_id: &str, height: u64) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_status(model_id, ModelStatus::Pending)?;
+        
+        let record = self.models.get_mut(model_id).unwrap();
+        record.status = ModelStatus::Approved;
+        record.status_height = height;
+        self.pending.remove(&(record.pending_until, record.model_id.clone()));
+        
+        Ok(())
+    }
+    
+    /// Keep a pending model until at least a height, while a proposal to approve it is open
+    pub fn extend_pending(&mut self, model_id: &str, until: u64) -> Result<(), Box<dyn std::error::Error>> {
+        self.check_status(model_id, ModelStatus::Pending)?;
+        
+        let record = self.models.get_mut(model_id).unwrap();
+        if until > record.pending_until {
+            self.pending.remove(&(record.pending_until, record.model_id.clone()));
+            record.pending_until = until;
+            self.pending.insert((until, record.model_id.clone()));
+        }
+        
+        Ok(())
+    }
+    
+    /// Remove models left pending past their deadline before a height, returning them so their stake can be released
+    ///
+    /// Their ids and model files can then be registered again.
+    pub fn expire(&mut self, height: u64) -> Vec<ModelRecord> {
+        let mut expired = Vec::new();
+        
+        while let Some((until, model_id)) = self.pending.first().cloned() {
+            if until >= height {
+                break;
+            }
+            
+            self.pending.remove(&(until, model_id.clone()));
+            expired.extend(self.models.remove(&model_id));
+        }
+        
+        expired
+    }
+    
+    /// Retire an approved model, returning the stake to release to its owner
+    pub fn retire(&mut self, model_id: &str, height: u64) -> Result<(String, u64), Box<dyn std::error::Error>> {
+        self.check_status(model_id, ModelStatus::Approved)?;
+        
+        let record = self.models.get_mut(model_id).unwrap();
+        record.status = ModelStatus::Retired;
+        record.status_height = height;
+        
+        Ok((record.owner.clone(), std::mem::take(&mut record.stake)))
+    }
+}
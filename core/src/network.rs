@@ -1,95 +1,1435 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
-use log::{info, error};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use log::{debug, info, warn, error};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::Serialize;
+use crate::addressbook::AddressBook;
+use crate::crypto::KeyPair;
+use crate::discovery::{self, DiscoveryMessage, NodeRecord, RoutingTable, BUCKET_SIZE, LOOKUP_PARALLELISM};
+use crate::gossip::{GossipMessage, GossipValidator, SeenCache, Topic, Verdict};
+use crate::handshake::{ChainStatus, DisconnectReason, HandshakeMessage, Hello, PeerInfo, SERVICE_FULL_NODE};
+use crate::noise::{self, NoiseKey, SecureReceiver, SecureSender};
+use crate::ratelimit::{Rate, TokenBucket};
+use crate::reputation::{Ban, PeerEvent, Reputation, DEFAULT_BAN_DURATION};
+use crate::sync::{BlockSource, SyncMessage, SyncRequest, SyncResponse};
+use crate::transport::{PeerStream, TcpTransport, Transport};
+use crate::utils;
+
+/// Largest message accepted from a peer, in bytes
+pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Largest handshake message accepted from a peer, in bytes
+const MAX_HANDSHAKE_SIZE: usize = 4096;
+
+/// Messages queued for a peer before sends to it fail, however small
+const PEER_QUEUE_SIZE: usize = 1024;
+
+/// Time allowed for an outbound connection to be established
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time allowed for a peer to complete the handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a rejected peer has to read why before the connection is dropped
+const REJECT_LINGER: Duration = Duration::from_secs(1);
+
+/// Most nodes a peer may return for one lookup
+const MAX_NODES_PER_REPLY: usize = BUCKET_SIZE;
+
+/// Most nodes learned from one lookup reply, so no single peer fills the routing table
+const MAX_NODES_LEARNED_PER_REPLY: usize = BUCKET_SIZE / 2;
+
+/// Leading byte of messages delivered to the node
+const APPLICATION_CHANNEL: u8 = 0;
+
+/// Leading byte of discovery protocol messages
+const DISCOVERY_CHANNEL: u8 = 1;
+
+/// Leading byte of gossip messages
+const GOSSIP_CHANNEL: u8 = 2;
+
+/// Leading byte of sync requests and responses
+const SYNC_CHANNEL: u8 = 3;
+
+/// Error raised by network tasks
+pub type NetworkError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Message received from a peer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InboundMessage {
+    /// Node public key of the peer that sent the message
+    pub peer: String,
+    /// Message payload
+    pub data: Vec<u8>,
+}
+
+/// Kind of message, telling the channel it travels on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageKind {
+    /// Messages delivered to the node
+    Application,
+    /// Discovery protocol messages
+    Discovery,
+    /// Gossip messages
+    Gossip,
+    /// Sync requests and responses
+    Sync,
+}
+
+impl MessageKind {
+    /// Get the leading byte of messages of this kind
+    fn channel(&self) -> u8 {
+        match self {
+            MessageKind::Application => APPLICATION_CHANNEL,
+            MessageKind::Discovery => DISCOVERY_CHANNEL,
+            MessageKind::Gossip => GOSSIP_CHANNEL,
+            MessageKind::Sync => SYNC_CHANNEL,
+        }
+    }
+    
+    /// Get the kind of messages with a leading byte
+    fn from_channel(channel: u8) -> Option<Self> {
+        match channel {
+            APPLICATION_CHANNEL => Some(MessageKind::Application),
+            DISCOVERY_CHANNEL => Some(MessageKind::Discovery),
+            GOSSIP_CHANNEL => Some(MessageKind::Gossip),
+            SYNC_CHANNEL => Some(MessageKind::Sync),
+            _ => None,
+        }
+    }
+}
+
+/// Messages a peer may send per second of each kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRates {
+    /// Rate of messages delivered to the node
+    pub application: Rate,
+    /// Rate of discovery messages
+    pub discovery: Rate,
+    /// Rate of gossip messages
+    pub gossip: Rate,
+    /// Rate of sync requests and responses
+    pub sync: Rate,
+}
+
+impl MessageRates {
+    /// Get the rate of a kind of message
+    pub fn get(&self, kind: MessageKind) -> Rate {
+        match kind {
+            MessageKind::Application => self.application,
+            MessageKind::Discovery => self.discovery,
+            MessageKind::Gossip => self.gossip,
+            MessageKind::Sync => self.sync,
+        }
+    }
+}
+
+impl Default for MessageRates {
+    fn default() -> Self {
+        MessageRates {
+            application: Rate::new(100, 1000),
+            discovery: Rate::new(10, 100),
+            gossip: Rate::new(500, 2000),
+            sync: Rate::new(50, 200),
+        }
+    }
+}
+
+/// Traffic counters of the network
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct NetworkMetrics {
+    /// Message bytes received from peers
+    pub bytes_received: u64,
+    /// Message bytes written to peers
+    pub bytes_sent: u64,
+    /// Messages from peers dropped for exceeding their rate, by kind
+    pub rate_limited: HashMap<MessageKind, u64>,
+    /// Messages to peers dropped because their send queue was full, by kind
+    pub queue_full: HashMap<MessageKind, u64>,
+    /// Messages from peers dropped because the node was not taking them fast enough, by kind
+    pub undelivered: HashMap<MessageKind, u64>,
+}
+
+impl NetworkMetrics {
+    /// Get the number of messages dropped in either direction
+    pub fn dropped(&self) -> u64 {
+        self.rate_limited
+            .values()
+            .chain(self.queue_full.values())
+            .chain(self.undelivered.values())
+            .sum()
+    }
+}
+
+/// Configuration of the peer-to-peer network
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    /// Most peers connected at once
+    pub max_peers: usize,
+    /// Number of outbound peers discovery tries to keep, at most `max_peers`
+    pub target_outbound: usize,
+    /// Nodes dialed to join the network, as host:port
+    pub bootnodes: Vec<String>,
+    /// Time between discovery rounds
+    pub discovery_interval: Duration,
+    /// Time after which a routing table bucket is refreshed with a lookup
+    pub bucket_refresh_interval: Duration,
+    /// Number of peers each gossip message is sent to
+    pub gossip_fanout: usize,
+    /// Number of gossip message ids remembered to drop duplicates
+    pub gossip_cache_size: usize,
+    /// Time a peer has to answer a sync request
+    pub request_timeout: Duration,
+    /// Messages each peer may send per second of each kind, more are dropped
+    pub message_rates: MessageRates,
+    /// Bytes each peer may send per second, reading from faster peers is slowed down
+    pub peer_bandwidth: Rate,
+    /// Bytes per second read from all peers together, unlimited if `None`
+    pub max_inbound_bandwidth: Option<Rate>,
+    /// Bytes per second written to all peers together, unlimited if `None`
+    pub max_outbound_bandwidth: Option<Rate>,
+    /// Bytes queued for each peer before sends to it fail or wait
+    pub send_queue_bytes: usize,
+    /// Messages and accepted gossip waiting for the node to take them, more are dropped
+    pub delivery_queue_size: usize,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            max_peers: 50,
+            target_outbound: 8,
+            bootnodes: Vec::new(),
+            discovery_interval: Duration::from_secs(30),
+            bucket_refresh_interval: Duration::from_secs(600),
+            gossip_fanout: 8,
+            gossip_cache_size: 100_000,
+            request_timeout: Duration::from_secs(10),
+            message_rates: MessageRates::default(),
+            peer_bandwidth: Rate::new(8 * 1024 * 1024, 32 * 1024 * 1024),
+            max_inbound_bandwidth: None,
+            max_outbound_bandwidth: None,
+            send_queue_bytes: 4 * MAX_MESSAGE_SIZE,
+            delivery_queue_size: 4096,
+        }
+    }
+}
+
+/// Connection to a peer
+struct PeerConnection {
+    /// Identifier telling this connection apart from later ones to the same peer
+    id: u64,
+    /// Identity and chain announced by the peer
+    info: PeerInfo,
+    /// Queue of messages for the peer's writer task
+    queue: mpsc::Sender<QueuedMessage>,
+    /// Bytes that can still be queued for the peer
+    space: Arc<Semaphore>,
+    /// Task reading messages from the peer
+    reader: JoinHandle<()>,
+}
+
+/// Message waiting to be written to a peer
+struct QueuedMessage {
+    /// Message with its leading channel byte
+    data: Vec<u8>,
+    /// Queue space taken by the message, freed once it is written
+    _space: OwnedSemaphorePermit,
+}
+
+/// Connected peers by node public key
+type PeerMap = Arc<Mutex<HashMap<String, PeerConnection>>>;
+
+/// Sync requests awaiting a response by id, with the peer they were sent to
+type PendingRequests = Arc<Mutex<HashMap<u64, (String, oneshot::Sender<SyncResponse>)>>>;
+
+/// State shared with the tasks handling connections
+#[derive(Clone)]
+struct PeerContext {
+    peers: PeerMap,
+    next_connection: Arc<Mutex<u64>>,
+    inbound: mpsc::Sender<InboundMessage>,
+    keypair: Arc<KeyPair>,
+    noise_key: Arc<NoiseKey>,
+    status: Arc<Mutex<ChainStatus>>,
+    services: u64,
+    config: Arc<NetworkConfig>,
+    routing: Arc<Mutex<RoutingTable>>,
+    lookups: Arc<Mutex<HashMap<String, usize>>>,
+    local_addr: Arc<Mutex<Option<SocketAddr>>>,
+    seen: Arc<Mutex<SeenCache>>,
+    gossip: mpsc::Sender<GossipMessage>,
+    validator: Option<Arc<dyn GossipValidator>>,
+    requests: PendingRequests,
+    next_request: Arc<Mutex<u64>>,
+    block_source: Option<Arc<dyn BlockSource>>,
+    reputation: Arc<Mutex<Reputation>>,
+    address_book: Arc<Mutex<AddressBook>>,
+    transport: Arc<dyn Transport>,
+    metrics: Arc<Mutex<NetworkMetrics>>,
+    inbound_bandwidth: Option<Arc<Mutex<TokenBucket>>>,
+    outbound_bandwidth: Option<Arc<Mutex<TokenBucket>>>,
+    rng: Arc<Mutex<StdRng>>,
+    runtime: Handle,
+}
 
 /// P2P Network implementation for HyperNova Chain
 pub struct P2PNetwork {
     /// Network port
     port: u16,
-    /// Connected peers
-    peers: Arc<Mutex<HashSet<String>>>,
-    /// Maximum number of peers
-    max_peers: usize,
+    /// Connected peers, keys and configuration shared with network tasks
+    context: PeerContext,
+    /// Receiver of inbound messages, until the node takes it
+    inbound_receiver: Mutex<Option<mpsc::Receiver<InboundMessage>>>,
+    /// Receiver of accepted gossip messages, until the node takes it
+    gossip_receiver: Mutex<Option<mpsc::Receiver<GossipMessage>>>,
+    /// Runtime created for networks used outside of one
+    _owned_runtime: Option<Runtime>,
 }
 
 impl P2PNetwork {
-    /// Create a new P2P network instance
+    /// Create a new P2P network instance with a random node key
+    ///
+    /// Network tasks run on the current tokio runtime, or on a runtime of their own
+    /// when the network is created outside of one.
     pub fn new(port: u16) -> Result<Self, Box<dyn std::error::Error>> {
+        let (runtime, owned_runtime) = match Handle::try_current() {
+            Ok(handle) => (handle, None),
+            Err(_) => {
+                let runtime = tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(2)
+                    .enable_all()
+                    .build()?;
+                (runtime.handle().clone(), Some(runtime))
+            }
+        };
+        let config = NetworkConfig::default();
+        let (inbound, inbound_receiver) = mpsc::channel(config.delivery_queue_size);
+        let (gossip, gossip_receiver) = mpsc::channel(config.delivery_queue_size);
+        let keypair = KeyPair::generate()?;
+        
         Ok(P2PNetwork {
             port,
-            peers: Arc::new(Mutex::new(HashSet::new())),
-            max_peers: 50,
+            context: PeerContext {
+                peers: Arc::new(Mutex::new(HashMap::new())),
+                next_connection: Arc::new(Mutex::new(0)),
+                inbound,
+                routing: Arc::new(Mutex::new(RoutingTable::new(local_id(&keypair)))),
+                lookups: Arc::new(Mutex::new(HashMap::new())),
+                keypair: Arc::new(keypair),
+                noise_key: Arc::new(NoiseKey::generate()?),
+                status: Arc::new(Mutex::new(ChainStatus::default())),
+                services: SERVICE_FULL_NODE,
+                local_addr: Arc::new(Mutex::new(None)),
+                seen: Arc::new(Mutex::new(SeenCache::new(config.gossip_cache_size))),
+                gossip,
+                validator: None,
+                requests: Arc::new(Mutex::new(HashMap::new())),
+                next_request: Arc::new(Mutex::new(0)),
+                block_source: None,
+                reputation: Arc::new(Mutex::new(Reputation::new(DEFAULT_BAN_DURATION))),
+                address_book: Arc::new(Mutex::new(AddressBook::new())),
+                transport: Arc::new(TcpTransport),
+                metrics: Arc::new(Mutex::new(NetworkMetrics::default())),
+                inbound_bandwidth: bandwidth_limit(config.max_inbound_bandwidth),
+                outbound_bandwidth: bandwidth_limit(config.max_outbound_bandwidth),
+                config: Arc::new(config),
+                rng: Arc::new(Mutex::new(StdRng::from_entropy())),
+                runtime,
+            },
+            inbound_receiver: Mutex::new(Some(inbound_receiver)),
+            gossip_receiver: Mutex::new(Some(gossip_receiver)),
+            _owned_runtime: owned_runtime,
         })
     }
     
-    /// Start the P2P network
+    /// Use a node key to authenticate to peers
+    pub fn with_node_key(mut self, keypair: KeyPair) -> Self {
+        self.context.routing = Arc::new(Mutex::new(RoutingTable::new(local_id(&keypair))));
+        self.context.keypair = Arc::new(keypair);
+        self
+    }
+    
+    /// Announce services to peers
+    pub fn with_services(mut self, services: u64) -> Self {
+        self.context.services = services;
+        self
+    }
+    
+    /// Set the peer limits, bootnodes, discovery timings, gossip fan-out, rate limits and queue sizes
+    pub fn with_config(mut self, config: NetworkConfig) -> Self {
+        let (inbound, inbound_receiver) = mpsc::channel(config.delivery_queue_size);
+        let (gossip, gossip_receiver) = mpsc::channel(config.delivery_queue_size);
+        self.context.inbound = inbound;
+        self.context.gossip = gossip;
+        self.inbound_receiver = Mutex::new(Some(inbound_receiver));
+        self.gossip_receiver = Mutex::new(Some(gossip_receiver));
+        self.context.seen = Arc::new(Mutex::new(SeenCache::new(config.gossip_cache_size)));
+        self.context.inbound_bandwidth = bandwidth_limit(config.max_inbound_bandwidth);
+        self.context.outbound_bandwidth = bandwidth_limit(config.max_outbound_bandwidth);
+        self.context.config = Arc::new(config);
+        self
+    }
+    
+    /// Validate gossip messages before delivering and forwarding them
+    ///
+    /// Without a validator every well-formed message is accepted.
+    pub fn with_gossip_validator(mut self, validator: Arc<dyn GossipValidator>) -> Self {
+        self.context.validator = Some(validator);
+        self
+    }
+    
+    /// Serve headers and blocks to syncing peers
+    pub fn with_block_source(mut self, source: Arc<dyn BlockSource>) -> Self {
+        self.context.block_source = Some(source);
+        self
+    }
+    
+    /// Track peer scores and bans with a reputation tracker, such as one saving bans to disk
+    pub fn with_reputation(mut self, reputation: Reputation) -> Self {
+        self.context.reputation = Arc::new(Mutex::new(reputation));
+        self
+    }
+    
+    /// Remember the peers connected to with an address book, such as one saving them to disk
+    pub fn with_address_book(mut self, address_book: AddressBook) -> Self {
+        self.context.address_book = Arc::new(Mutex::new(address_book));
+        self
+    }
+    
+    /// Connect to peers over a transport other than TCP, such as an in-memory network
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.context.transport = Arc::new(transport);
+        self
+    }
+    
+    /// Draw gossip targets and discovery lookups from a random number generator, such as a seeded one in tests
+    pub fn with_rng(mut self, rng: StdRng) -> Self {
+        self.context.rng = Arc::new(Mutex::new(rng));
+        self
+    }
+    
+    /// Set the chain announced to peers, which must match theirs
+    pub fn set_chain_status(&self, status: ChainStatus) {
+        *self.context.status.lock().unwrap() = status;
+    }
+    
+    /// Get the hex-encoded public key identifying this node
+    pub fn public_key(&self) -> String {
+        self.context.keypair.public_key_hex()
+    }
+    
+    /// Start the P2P network and peer discovery
     pub fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
         info!("Starting P2P network on port {}", self.port);
         
-        // In a real implementation, this would:
-        // 1. Start a TCP/UDP server
-        // 2. Set up peer discovery
-        // 3. Initialize message handling
+        // Listen before returning so a port in use is reported to the caller
+        let mut listener = {
+            let _guard = self.context.runtime.enter();
+            self.context
+                .transport
+                .listen(self.port)
+                .map_err(|e| e as Box<dyn std::error::Error>)?
+        };
+        *self.context.local_addr.lock().unwrap() = Some(listener.local_addr());
+        
+        let context = self.context.clone();
+        
+        self.context.runtime.spawn(async move {
+            loop {
+                let (stream, address) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        error!("Failed to accept peer connection: {}", e);
+                        continue;
+                    }
+                };
+                
+                let context = context.clone();
+                tokio::spawn(async move {
+                    match context.establish(stream, address.to_string(), address, false, None).await {
+                        Ok(info) => info!("Accepted peer {} from {}", info.public_key, address),
+                        Err(e) => warn!("Rejected peer {}: {}", address, e),
+                    }
+                });
+            }
+        });
+        
+        self.context.runtime.spawn(self.context.clone().run_discovery());
         
         Ok(())
     }
     
-    /// Connect to a peer
-    pub fn connect(&self, address: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut peers = self.peers.lock().unwrap();
+    /// Get the address the network is listening on, once started
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.context.local_addr.lock().unwrap()
+    }
+    
+    /// Take the receiver of messages sent by peers
+    ///
+    /// There is a single receiver, later calls return `None`. Messages arriving while
+    /// `delivery_queue_size` of them are waiting are dropped.
+    pub fn take_inbound(&self) -> Option<mpsc::Receiver<InboundMessage>> {
+        self.inbound_receiver.lock().unwrap().take()
+    }
+    
+    /// Take the receiver of gossip messages accepted from peers
+    ///
+    /// There is a single receiver, later calls return `None`. Gossip accepted while
+    /// `delivery_queue_size` messages are waiting is still forwarded but not delivered.
+    pub fn take_gossip(&self) -> Option<mpsc::Receiver<GossipMessage>> {
+        self.gossip_receiver.lock().unwrap().take()
+    }
+    
+    /// Connect to a peer and return its node public key
+    ///
+    /// Blocks until the handshake completes. Inside a current-thread tokio runtime
+    /// this fails, use `connect_async` there instead.
+    pub fn connect(&self, address: &str) -> Result<String, Box<dyn std::error::Error>> {
+        utils::block_on(Some(&self.context.runtime), self.connect_async(address))
+            .and_then(|result| result)
+            .map_err(|e| e as Box<dyn std::error::Error>)
+    }
+    
+    /// Connect to a peer and return its node public key once the handshake completes
+    pub async fn connect_async(&self, address: &str) -> Result<String, NetworkError> {
+        let info = self.context.dial(address, None).await?;
         
-        if peers.len() >= self.max_peers {
-            return Err("Maximum number of peers reached".into());
+        Ok(info.public_key)
+    }
+    
+    /// Disconnect from a peer
+    pub fn disconnect(&self, peer: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.context.drop_peer(peer) {
+            return Err(format!("Peer not found: {}", peer).into());
         }
         
-        // In a real implementation, this would establish a connection
+        info!("Disconnected from peer: {}", peer);
+        Ok(())
+    }
+    
+    /// Adjust a peer's score for its behaviour, banning and disconnecting it if the score drops too low
+    pub fn report_peer(&self, peer: &str, event: PeerEvent) {
+        self.context.report(peer, event);
+    }
+    
+    /// Get the score of a peer
+    pub fn peer_score(&self, peer: &str) -> i32 {
+        self.context.reputation.lock().unwrap().score(peer)
+    }
+    
+    /// Get the bans in force
+    pub fn banned_peers(&self) -> Vec<Ban> {
+        self.context.reputation.lock().unwrap().bans()
+    }
+    
+    /// Ban a peer for a duration, disconnecting it if connected
+    pub fn ban_peer(&self, peer: &str, duration: Duration) -> Ban {
+        let ban = self.context.update_reputation(|reputation| reputation.ban(peer, duration, "banned by operator"));
+        self.context.drop_peer(peer);
+        ban
+    }
+    
+    /// Lift a peer's ban, returning whether it was banned
+    pub fn unban_peer(&self, peer: &str) -> bool {
+        self.context.update_reputation(|reputation| reputation.unban(peer))
+    }
+    
+    /// Broadcast a message to all peers
+    pub fn broadcast(&self, message: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        check_size(message)?;
+        
+        let peers = self.context.peers.lock().unwrap();
         
-        peers.insert(address.to_string());
-        info!("Connected to peer: {}", address);
+        info!("Broadcasting message to {} peers", peers.len());
+        
+        for (peer, connection) in peers.iter() {
+            if let Err(e) = self.context.enqueue(connection, MessageKind::Application, message) {
+                warn!("Dropping broadcast to peer {}: {}", peer, e);
+            }
+        }
         
         Ok(())
     }
     
-    /// Disconnect from a peer
-    pub fn disconnect(&self, address: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let mut peers = self.peers.lock().unwrap();
+    /// Gossip a message to the network and return its id
+    ///
+    /// The message is sent to a random subset of peers, which forward it on once
+    /// validated. Messages already seen by this node are not sent again.
+    pub fn publish(&self, topic: Topic, data: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+        let message = GossipMessage::new(topic, data.to_vec());
+        let encoded = message.encode();
+        check_size(&encoded)?;
         
-        if peers.remove(address) {
-            info!("Disconnected from peer: {}", address);
-        } else {
-            return Err(format!("Peer not found: {}", address).into());
+        let id = message.id();
+        if self.context.seen.lock().unwrap().insert(&id) {
+            self.context.forward(&encoded, None);
         }
         
+        Ok(id)
+    }
+    
+    /// Send a message to a specific peer
+    pub fn send(&self, peer: &str, message: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        check_size(message)?;
+        
+        let peers = self.context.peers.lock().unwrap();
+        
+        let connection = peers
+            .get(peer)
+            .ok_or_else(|| format!("Peer not found: {}", peer))?;
+        
+        self.context
+            .enqueue(connection, MessageKind::Application, message)
+            .map_err(|e| format!("Failed to queue message for peer {}: {}", peer, e))?;
+        
         Ok(())
     }
     
-    /// Broadcast a message to all peers
-    pub fn broadcast(&self, message: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    /// Send a message to a specific peer, waiting for room in its send queue
+    ///
+    /// Where `send` fails when a peer does not keep up, this slows the caller down
+    /// to the rate the peer reads at.
+    pub async fn send_async(&self, peer: &str, message: &[u8]) -> Result<(), NetworkError> {
+        check_size(message).map_err(|e| e.to_string())?;
+        
+        let message = tagged(APPLICATION_CHANNEL, message);
+        if message.len() > self.context.config.send_queue_bytes {
+            return Err(format!("Message of {} bytes exceeds the send queue size", message.len()).into());
+        }
+        
+        let (queue, space) = {
+            let peers = self.context.peers.lock().unwrap();
+            let connection = peers.get(peer).ok_or_else(|| format!("Peer not found: {}", peer))?;
+            (connection.queue.clone(), connection.space.clone())
+        };
+        
+        let disconnected = || format!("Peer {} disconnected", peer);
+        let space = space.acquire_many_owned(message.len() as u32).await.map_err(|_| disconnected())?;
+        queue
+            .send(QueuedMessage { data: message, _space: space })
+            .await
+            .map_err(|_| disconnected())?;
+        
+        Ok(())
+    }
+    
+    /// Send a sync request to a peer and wait for its response
+    pub async fn request(&self, peer: &str, request: SyncRequest) -> Result<SyncResponse, NetworkError> {
+        let id = {
+            let mut next = self.context.next_request.lock().unwrap();
+            *next += 1;
+            *next
+        };
+        
+        let (responder, response) = oneshot::channel();
+        self.context.requests.lock().unwrap().insert(id, (peer.to_string(), responder));
+        
+        let queued = {
+            let peers = self.context.peers.lock().unwrap();
+            match peers.get(peer) {
+                Some(connection) => self
+                    .context
+                    .enqueue(connection, MessageKind::Sync, &SyncMessage::Request { id, request }.encode())
+                    .map_err(|e| format!("Failed to queue request for peer {}: {}", peer, e)),
+                None => Err(format!("Peer not found: {}", peer)),
+            }
+        };
+        
+        let result = match queued {
+            Ok(_) => tokio::time::timeout(self.context.config.request_timeout, response).await,
+            Err(e) => {
+                self.context.requests.lock().unwrap().remove(&id);
+                return Err(e.into());
+            }
+        };
+        self.context.requests.lock().unwrap().remove(&id);
+        
+        match result {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(format!("Request to peer {} was dropped", peer).into()),
+            Err(_) => Err(format!("Peer {} did not answer in time", peer).into()),
+        }
+    }
+    
+    /// Get the bytes sent and received and the messages dropped so far
+    pub fn metrics(&self) -> NetworkMetrics {
+        self.context.metrics.lock().unwrap().clone()
+    }
+    
+    /// Get the node public keys of connected peers
+    pub fn get_peers(&self) -> HashSet<String> {
+        let peers = self.context.peers.lock().unwrap();
+        peers.keys().cloned().collect()
+    }
+    
+    /// Get the address, chain and services a connected peer announced in its handshake
+    pub fn get_peer_info(&self, peer: &str) -> Option<PeerInfo> {
+        let peers = self.context.peers.lock().unwrap();
+        peers.get(peer).map(|connection| connection.info.clone())
+    }
+    
+    /// Get the nodes in the discovery routing table
+    pub fn known_nodes(&self) -> Vec<NodeRecord> {
+        let routing = self.context.routing.lock().unwrap();
+        routing.records().cloned().collect()
+    }
+}
+
+impl PeerContext {
+    /// Get the node id of this node
+    fn local_id(&self) -> [u8; 32] {
+        local_id(&self.keypair)
+    }
+    
+    /// Check there is room for a peer with this identity
+    fn check_capacity(&self, public_key: &str) -> Result<(), DisconnectReason> {
+        if self.reputation.lock().unwrap().is_banned(public_key) {
+            return Err(DisconnectReason::Banned);
+        }
+        
         let peers = self.peers.lock().unwrap();
         
-        info!("Broadcasting message to {} peers", peers.len());
+        if peers.len() >= self.config.max_peers {
+            return Err(DisconnectReason::TooManyPeers);
+        }
         
-        // In a real implementation, this would send the message to all peers
+        if peers.values().any(|connection| connection.info.public_key == public_key) {
+            return Err(DisconnectReason::AlreadyConnected);
+        }
         
         Ok(())
     }
     
-    /// Send a message to a specific peer
-    pub fn send(&self, address: &str, message: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    /// Dial a peer and secure the connection, checking it belongs to the node with a key if one is expected
+    async fn dial(&self, address: &str, expected_key: Option<&str>) -> Result<PeerInfo, NetworkError> {
+        {
+            let peers = self.peers.lock().unwrap();
+            
+            if peers.len() >= self.config.max_peers {
+                return Err("Maximum number of peers reached".into());
+            }
+            
+            if peers.values().any(|connection| connection.info.address == address) {
+                return Err(format!("Already connected to peer: {}", address).into());
+            }
+        }
+        
+        let connection = match tokio::time::timeout(CONNECT_TIMEOUT, self.transport.dial(address)).await {
+            Ok(connection) => connection,
+            Err(_) => Err(format!("Timed out connecting to {}", address).into()),
+        };
+        let (stream, remote) = match connection {
+            Ok(connection) => connection,
+            Err(e) => {
+                self.update_address_book(|book| book.record_failure(address));
+                return Err(e);
+            }
+        };
+        
+        let info = self.establish(stream, address.to_string(), remote, true, expected_key).await?;
+        info!("Connected to peer {} at {}", info.public_key, address);
+        
+        Ok(info)
+    }
+    
+    /// Secure a new connection, exchange hellos and add the peer, giving up on peers that take too long
+    async fn establish(
+        &self,
+        mut stream: PeerStream,
+        address: String,
+        remote: SocketAddr,
+        outbound: bool,
+        expected_key: Option<&str>,
+    ) -> Result<PeerInfo, NetworkError> {
+        let handshake = self.handshake(&mut stream, address, outbound, expected_key);
+        let (info, listen_port, sender, receiver) = match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await {
+            Ok(result) => result?,
+            Err(_) => return Err(format!("Disconnected peer: {}", DisconnectReason::Timeout).into()),
+        };
+        
+        // Remember where the peer can be dialed, which for inbound peers is its listening port
+        let dialable = match (outbound, listen_port) {
+            (true, _) => Some(info.address.clone()),
+            (false, 0) => None,
+            (false, port) => Some(SocketAddr::new(remote.ip(), port).to_string()),
+        };
+        let record = dialable.map(|address| NodeRecord { public_key: info.public_key.clone(), address });
+        if let Some(record) = &record {
+            self.routing.lock().unwrap().insert(record.clone());
+        }
+        
+        self.register(info.clone(), stream, sender, receiver)?;
+        
+        // Only a peer this node dialed has proven it can be reached at its address
+        if let (true, Some(record)) = (outbound, &record) {
+            self.update_address_book(|book| book.record_success(record, info.services));
+        }
+        
+        Ok(info)
+    }
+    
+    /// Run the Noise handshake, then exchange hellos over the encrypted channel
+    ///
+    /// The Noise handshake authenticates the peer's node key, which must be the
+    /// expected one if any and which its hello must announce. Each side then tells
+    /// the other whether it accepts it, sending incompatible or unwelcome peers the
+    /// reason they are disconnected.
+    async fn handshake<S>(
+        &self,
+        stream: &mut S,
+        address: String,
+        outbound: bool,
+        expected_key: Option<&str>,
+    ) -> Result<(PeerInfo, u16, SecureSender, SecureReceiver), NetworkError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let channel = noise::handshake(stream, outbound, &self.noise_key, &self.keypair).await?;
+        let authenticated_key = channel.remote_public_key.clone();
+        let (mut sender, mut receiver) = channel.split();
+        
+        // Another node answering at a known node's address is not that node
+        if expected_key.is_some_and(|expected| expected != authenticated_key) {
+            return Err(reject(stream, &mut sender, DisconnectReason::AuthenticationFailed).await);
+        }
+        
+        let status = self.status.lock().unwrap().clone();
+        let mut local = Hello::new(&self.keypair, status, self.services);
+        local.listen_port = self.local_addr.lock().unwrap().map_or(0, |addr| addr.port());
+        sender.send(stream, &HandshakeMessage::Hello(local.clone()).encode()).await?;
+        
+        let message = receiver
+            .receive(stream, MAX_HANDSHAKE_SIZE)
+            .await?
+            .ok_or("Peer closed the connection during the handshake")?;
+        let remote = match HandshakeMessage::decode(&message).ok() {
+            Some(HandshakeMessage::Hello(hello)) => hello,
+            Some(HandshakeMessage::Disconnect(reason)) => return Err(format!("Peer disconnected: {}", reason).into()),
+            _ => return Err(reject(stream, &mut sender, DisconnectReason::ProtocolViolation).await),
+        };
+        
+        let checked = local
+            .check_compatible(&remote)
+            .and_then(|_| match remote.public_key == authenticated_key {
+                true => Ok(()),
+                false => Err(DisconnectReason::AuthenticationFailed),
+            })
+            .and_then(|_| self.check_capacity(&remote.public_key));
+        if let Err(reason) = checked {
+            return Err(reject(stream, &mut sender, reason).await);
+        }
+        
+        // The peer may still turn this node away, for reasons only it knows about, in
+        // which case it may have closed the connection before reading this answer
+        let sent = sender.send(stream, &HandshakeMessage::Ready.encode()).await;
+        let message = receiver
+            .receive(stream, MAX_HANDSHAKE_SIZE)
+            .await?
+            .ok_or("Peer closed the connection during the handshake")?;
+        match HandshakeMessage::decode(&message).ok() {
+            Some(HandshakeMessage::Ready) => sent?,
+            Some(HandshakeMessage::Disconnect(reason)) => return Err(format!("Peer disconnected: {}", reason).into()),
+            _ => return Err(reject(stream, &mut sender, DisconnectReason::ProtocolViolation).await),
+        }
+        
+        let listen_port = remote.listen_port;
+        Ok((PeerInfo::from_hello(remote, address, outbound), listen_port, sender, receiver))
+    }
+    
+    /// Start the reader and writer tasks of a new connection and add it to the peers
+    ///
+    /// Two nodes dialing each other at once may both end up with two connections.
+    /// Both then keep the one dialed by the node with the lower key. Capacity and
+    /// bans are checked again here, as other handshakes or a ban may have completed
+    /// since the handshake checked them.
+    fn register(&self, info: PeerInfo, stream: PeerStream, sender: SecureSender, receiver: SecureReceiver) -> Result<(), NetworkError> {
+        let peer = info.public_key.clone();
+        let mut peers = self.peers.lock().unwrap();
+        
+        if self.reputation.lock().unwrap().is_banned(&peer) {
+            return Err(format!("Disconnected peer: {}", DisconnectReason::Banned).into());
+        }
+        
+        if !peers.contains_key(&peer) && peers.len() >= self.config.max_peers {
+            return Err(format!("Disconnected peer: {}", DisconnectReason::TooManyPeers).into());
+        }
+        
+        if let Some(existing) = peers.get(&peer) {
+            let keep_outbound = self.keypair.public_key_hex() < peer;
+            if existing.info.outbound == keep_outbound || info.outbound != keep_outbound {
+                return Err(format!("Disconnected peer: {}", DisconnectReason::AlreadyConnected).into());
+            }
+            
+            if let Some(replaced) = peers.remove(&peer) {
+                replaced.reader.abort();
+            }
+        }
+        
+        let id = {
+            let mut next = self.next_connection.lock().unwrap();
+            *next += 1;
+            *next
+        };
+        
+        let (read_half, write_half) = tokio::io::split(stream);
+        let (queue, queued) = mpsc::channel(PEER_QUEUE_SIZE);
+        let space = Arc::new(Semaphore::new(self.config.send_queue_bytes));
+        
+        self.runtime.spawn(write_messages(write_half, sender, queued, self.clone(), peer.clone()));
+        let reader = self.runtime.spawn(read_messages(read_half, receiver, self.clone(), peer.clone(), id));
+        
+        peers.insert(peer, PeerConnection { id, info, queue, space, reader });
+        
+        Ok(())
+    }
+    
+    /// Remove a peer's connection, returning whether it was connected
+    fn drop_peer(&self, peer: &str) -> bool {
+        match self.peers.lock().unwrap().remove(peer) {
+            Some(connection) => {
+                // Dropping the queue ends the writer task, which closes the socket
+                connection.reader.abort();
+                self.lookups.lock().unwrap().remove(peer);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Adjust a peer's score, banning and disconnecting it if the score drops too low
+    fn report(&self, peer: &str, event: PeerEvent) {
+        let ban = self.update_reputation(|reputation| reputation.record(peer, event));
+        
+        if let Some(ban) = ban {
+            warn!("Banned peer {}: {}", peer, ban.reason);
+            self.drop_peer(peer);
+        }
+    }
+    
+    /// Change the peer scores and bans, saving the bans once the lock is released
+    fn update_reputation<T>(&self, change: impl FnOnce(&mut Reputation) -> T) -> T {
+        let (result, unsaved) = {
+            let mut reputation = self.reputation.lock().unwrap();
+            let result = change(&mut reputation);
+            (result, reputation.take_unsaved())
+        };
+        
+        if let Some(snapshot) = unsaved {
+            snapshot.save();
+        }
+        result
+    }
+    
+    /// Change the address book, saving it once the lock is released
+    fn update_address_book<T>(&self, change: impl FnOnce(&mut AddressBook) -> T) -> T {
+        let (result, unsaved) = {
+            let mut book = self.address_book.lock().unwrap();
+            let result = change(&mut book);
+            (result, book.take_unsaved())
+        };
+        
+        if let Some(snapshot) = unsaved {
+            snapshot.save();
+        }
+        result
+    }
+    
+    /// Queue a message of a kind for a peer
+    fn send_to(&self, peer: &str, kind: MessageKind, message: &[u8]) {
         let peers = self.peers.lock().unwrap();
         
-        if !peers.contains(address) {
-            return Err(format!("Peer not found: {}", address).into());
+        if let Some(connection) = peers.get(peer) {
+            if let Err(e) = self.enqueue(connection, kind, message) {
+                debug!("Dropping message to peer {}: {}", peer, e);
+            }
+        }
+    }
+    
+    /// Queue a message for a peer without waiting, counting it as dropped if the peer's queue is full
+    fn enqueue(&self, connection: &PeerConnection, kind: MessageKind, message: &[u8]) -> Result<(), String> {
+        let message = tagged(kind.channel(), message);
+        
+        let space = u32::try_from(message.len())
+            .ok()
+            .and_then(|size| connection.space.clone().try_acquire_many_owned(size).ok());
+        if let Some(space) = space {
+            match connection.queue.try_send(QueuedMessage { data: message, _space: space }) {
+                Ok(()) => return Ok(()),
+                Err(mpsc::error::TrySendError::Closed(_)) => return Err("peer disconnected".to_string()),
+                Err(mpsc::error::TrySendError::Full(_)) => {}
+            }
         }
         
-        // In a real implementation, this would send the message to the peer
+        *self.metrics.lock().unwrap().queue_full.entry(kind).or_insert(0) += 1;
+        Err("send queue is full".to_string())
+    }
+    
+    /// Handle a message received from a peer
+    fn handle_message(&self, peer: &str, mut data: Vec<u8>) -> Result<(), NetworkError> {
+        if data.is_empty() {
+            return Err("Empty message".into());
+        }
+        
+        match data.remove(0) {
+            APPLICATION_CHANNEL => {
+                let message = InboundMessage { peer: peer.to_string(), data };
+                self.deliver(&self.inbound, MessageKind::Application, peer, message);
+            }
+            DISCOVERY_CHANNEL => self.handle_discovery(peer, &data)?,
+            GOSSIP_CHANNEL => self.handle_gossip(peer, data)?,
+            SYNC_CHANNEL => self.handle_sync(peer, &data)?,
+            channel => return Err(format!("Unknown message channel {}", channel).into()),
+        }
         
         Ok(())
     }
     
-    /// Get connected peers
-    pub fn get_peers(&self) -> HashSet<String> {
+    /// Hand a message from a peer to the node, dropping it if the node is not keeping up
+    fn deliver<T>(&self, channel: &mpsc::Sender<T>, kind: MessageKind, peer: &str, message: T) {
+        match channel.try_send(message) {
+            Ok(()) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                debug!("Dropping {:?} message from peer {}, the node is not keeping up", kind, peer);
+                *self.metrics.lock().unwrap().undelivered.entry(kind).or_insert(0) += 1;
+            }
+            // The node may not be interested in messages it does not take
+            Err(mpsc::error::TrySendError::Closed(_)) => {}
+        }
+    }
+    
+    /// Answer node lookups and learn the nodes peers return
+    fn handle_discovery(&self, peer: &str, data: &[u8]) -> Result<(), NetworkError> {
+        let message = DiscoveryMessage::decode(data).map_err(|e| format!("Invalid discovery message: {}", e))?;
+        
+        match message {
+            DiscoveryMessage::FindNode { target } => {
+                let target = discovery::node_id(&target).ok_or("Invalid lookup target")?;
+                let nodes = self
+                    .routing
+                    .lock()
+                    .unwrap()
+                    .closest(&target, BUCKET_SIZE + 1)
+                    .into_iter()
+                    .filter(|record| record.public_key != peer)
+                    .take(BUCKET_SIZE)
+                    .collect();
+                
+                self.send_to(peer, MessageKind::Discovery, &DiscoveryMessage::Nodes { nodes }.encode());
+            }
+            DiscoveryMessage::Nodes { nodes } => {
+                if nodes.len() > MAX_NODES_PER_REPLY {
+                    return Err(format!("Lookup reply with {} nodes", nodes.len()).into());
+                }
+                
+                // Only replies to this node's lookups are taken, one per lookup
+                {
+                    let mut lookups = self.lookups.lock().unwrap();
+                    match lookups.get_mut(peer) {
+                        Some(pending) if *pending > 1 => *pending -= 1,
+                        Some(_) => {
+                            lookups.remove(peer);
+                        }
+                        None => {
+                            debug!("Ignoring unrequested nodes from peer {}", peer);
+                            return Ok(());
+                        }
+                    }
+                }
+                
+                let local = self.keypair.public_key_hex();
+                let mut routing = self.routing.lock().unwrap();
+                let mut learned = 0;
+                
+                for record in nodes {
+                    if learned == MAX_NODES_LEARNED_PER_REPLY {
+                        break;
+                    }
+                    if record.public_key != local
+                        && routing.get(&record.public_key).is_none()
+                        && record.address.parse::<SocketAddr>().is_ok()
+                        && routing.insert(record)
+                    {
+                        learned += 1;
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Deliver and forward new gossip messages that pass validation
+    fn handle_gossip(&self, peer: &str, data: Vec<u8>) -> Result<(), NetworkError> {
+        let message = GossipMessage::decode(&data).map_err(|e| format!("Invalid gossip message: {}", e))?;
+        
+        let id = message.id();
+        if !self.seen.lock().unwrap().insert(&id) {
+            return Ok(());
+        }
+        
+        let verdict = self
+            .validator
+            .as_ref()
+            .map_or(Verdict::Accept, |validator| validator.validate(&message));
+        
+        match verdict {
+            Verdict::Accept => {
+                self.report(peer, PeerEvent::ValidGossip);
+                self.forward(&data, Some(peer));
+                self.deliver(&self.gossip, MessageKind::Gossip, peer, message);
+            }
+            Verdict::Ignore => debug!("Ignoring gossip {} from peer {}", id, peer),
+            Verdict::Reject => {
+                warn!("Peer {} sent invalid gossip {} on {:?}", peer, id, message.topic);
+                self.report(peer, PeerEvent::InvalidGossip);
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Send an encoded gossip message to a random subset of peers, except the one it came from
+    fn forward(&self, encoded: &[u8], from: Option<&str>) {
         let peers = self.peers.lock().unwrap();
-        peers.clone()
+        
+        // Peers are put in order first so a seeded generator always picks the same ones
+        let mut candidates: Vec<&String> = peers.keys().filter(|peer| Some(peer.as_str()) != from).collect();
+        candidates.sort();
+        
+        let mut rng = self.rng.lock().unwrap();
+        for peer in candidates.choose_multiple(&mut *rng, self.config.gossip_fanout) {
+            if let Err(e) = self.enqueue(&peers[*peer], MessageKind::Gossip, encoded) {
+                debug!("Dropping gossip to peer {}: {}", peer, e);
+            }
+        }
+    }
+    
+    /// Answer sync requests and hand responses to the requests waiting for them
+    fn handle_sync(&self, peer: &str, data: &[u8]) -> Result<(), NetworkError> {
+        let message = SyncMessage::decode(data).map_err(|e| format!("Invalid sync message: {}", e))?;
+        
+        match message {
+            SyncMessage::Request { id, request } => {
+                // Nodes without a block source have no blocks to serve
+                let response = match (&self.block_source, request) {
+                    (Some(source), request) => request.answer(source.as_ref()),
+                    (None, SyncRequest::Status) => SyncResponse::Status(self.status.lock().unwrap().clone()),
+                    (None, SyncRequest::Headers { .. }) => SyncResponse::Headers(Vec::new()),
+                    (None, SyncRequest::Bodies { .. }) => SyncResponse::Bodies(Vec::new()),
+                };
+                
+                self.send_to(peer, MessageKind::Sync, &SyncMessage::Response { id, response }.encode());
+            }
+            SyncMessage::Response { id, response } => {
+                let mut requests = self.requests.lock().unwrap();
+                
+                // Responses are only taken from the peer that was asked, late ones are dropped
+                if requests.get(&id).is_some_and(|(asked, _)| asked == peer) {
+                    if let Some((_, responder)) = requests.remove(&id) {
+                        let _ = responder.send(response);
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Run discovery rounds for as long as the network runs
+    async fn run_discovery(self) {
+        let mut interval = tokio::time::interval(self.config.discovery_interval);
+        
+        loop {
+            interval.tick().await;
+            self.discover().await;
+        }
+    }
+    
+    /// Join the network, look up nodes and dial them until enough peers are outbound
+    ///
+    /// A node knowing no nodes joins through the peers in its address book, and
+    /// through the bootnodes if none of those can be reached.
+    async fn discover(&self) {
+        if self.routing.lock().unwrap().is_empty() {
+            self.fill_outbound().await;
+        }
+        
+        if self.routing.lock().unwrap().is_empty() {
+            for bootnode in &self.config.bootnodes {
+                if let Err(e) = self.dial(bootnode, None).await {
+                    debug!("Failed to dial bootnode {}: {}", bootnode, e);
+                }
+            }
+        }
+        
+        // Look up this node's own id to find its neighbours, and refresh idle buckets
+        let mut targets = vec![self.local_id()];
+        targets.extend(
+            self.routing
+                .lock()
+                .unwrap()
+                .refresh_targets(self.config.bucket_refresh_interval, &mut *self.rng.lock().unwrap()),
+        );
+        for target in targets {
+            self.lookup(&target);
+        }
+        
+        self.fill_outbound().await;
+    }
+    
+    /// Ask the connected peers closest to a target for the nodes they know near it
+    fn lookup(&self, target: &[u8; 32]) {
+        let connected = self.get_connected();
+        let mut queried: Vec<String> = self
+            .routing
+            .lock()
+            .unwrap()
+            .closest(target, usize::MAX)
+            .into_iter()
+            .map(|record| record.public_key)
+            .filter(|peer| connected.contains(peer))
+            .take(LOOKUP_PARALLELISM)
+            .collect();
+        
+        // Peers missing from the routing table can still answer
+        if queried.is_empty() {
+            queried = connected.into_iter().collect();
+            queried.sort();
+            queried.truncate(LOOKUP_PARALLELISM);
+        }
+        
+        let message = DiscoveryMessage::FindNode { target: hex::encode(target) }.encode();
+        for peer in queried {
+            *self.lookups.lock().unwrap().entry(peer.clone()).or_insert(0) += 1;
+            self.send_to(&peer, MessageKind::Discovery, &message);
+        }
+    }
+    
+    /// Dial known nodes until the target of outbound peers is reached
+    ///
+    /// Peers from the address book that have proven reachable are dialed first, then
+    /// the nodes in the routing table, closest first.
+    async fn fill_outbound(&self) {
+        let (connected, outbound) = {
+            let peers = self.peers.lock().unwrap();
+            let outbound = peers.values().filter(|connection| connection.info.outbound).count();
+            (peers.keys().cloned().collect::<HashSet<_>>(), outbound)
+        };
+        
+        let target = self.config.target_outbound.min(self.config.max_peers);
+        let mut missing = target.saturating_sub(outbound);
+        if missing == 0 {
+            return;
+        }
+        
+        let candidates: Vec<NodeRecord> = {
+            let reputation = self.reputation.lock().unwrap();
+            let known = self
+                .address_book
+                .lock()
+                .unwrap()
+                .best()
+                .into_iter()
+                .map(|entry| NodeRecord { public_key: entry.public_key, address: entry.address });
+            let closest = self.routing.lock().unwrap().closest(&self.local_id(), usize::MAX);
+            
+            let mut seen = HashSet::new();
+            known
+                .chain(closest)
+                .filter(|record| !connected.contains(&record.public_key) && !reputation.is_banned(&record.public_key))
+                .filter(|record| seen.insert(record.public_key.clone()))
+                .collect()
+        };
+        
+        for record in candidates {
+            if missing == 0 {
+                break;
+            }
+            
+            match self.dial(&record.address, Some(&record.public_key)).await {
+                Ok(_) => missing -= 1,
+                Err(e) => {
+                    // Forget nodes that cannot be reached
+                    debug!("Failed to dial node {} at {}: {}", record.public_key, record.address, e);
+                    self.routing.lock().unwrap().remove(&record.public_key);
+                }
+            }
+        }
+    }
+    
+    /// Get the node public keys of connected peers
+    fn get_connected(&self) -> HashSet<String> {
+        self.peers.lock().unwrap().keys().cloned().collect()
     }
-}
\ No newline at end of file
+}
+
+/// Get the node id of a node key
+fn local_id(keypair: &KeyPair) -> [u8; 32] {
+    discovery::node_id(&keypair.public_key_hex()).expect("node public keys are 32 bytes")
+}
+
+/// Create the bucket enforcing a bandwidth cap, shared by all connections
+fn bandwidth_limit(rate: Option<Rate>) -> Option<Arc<Mutex<TokenBucket>>> {
+    rate.map(|rate| Arc::new(Mutex::new(TokenBucket::new(rate))))
+}
+
+/// Wait until a shared bandwidth cap allows a transfer
+async fn throttle(limit: &Mutex<TokenBucket>, bytes: usize) {
+    let wait = limit.lock().unwrap().reserve(bytes as u64);
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Prefix a message with the channel it belongs to
+fn tagged(channel: u8, message: &[u8]) -> Vec<u8> {
+    let mut tagged = Vec::with_capacity(message.len() + 1);
+    tagged.push(channel);
+    tagged.extend_from_slice(message);
+    tagged
+}
+
+/// Check a message fits in a frame
+fn check_size(message: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    if message.len() > MAX_MESSAGE_SIZE {
+        return Err(format!("Message of {} bytes exceeds the maximum of {}", message.len(), MAX_MESSAGE_SIZE).into());
+    }
+    
+    Ok(())
+}
+
+/// Tell a peer why it is being disconnected and get the handshake error
+///
+/// The connection is closed gracefully, reading what the peer still sends for a
+/// moment, so the reason is not lost to a reset connection.
+async fn reject<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S, sender: &mut SecureSender, reason: DisconnectReason) -> NetworkError {
+    let _ = sender.send(stream, &HandshakeMessage::Disconnect(reason).encode()).await;
+    let _ = stream.shutdown().await;
+    
+    let drain = async {
+        let mut buffer = [0u8; 1024];
+        while let Ok(1..) = stream.read(&mut buffer).await {}
+    };
+    let _ = tokio::time::timeout(REJECT_LINGER, drain).await;
+    
+    format!("Disconnected peer: {}", reason).into()
+}
+
+/// Encrypt and write queued messages to a peer, within the outbound bandwidth cap
+async fn write_messages(mut stream: WriteHalf<PeerStream>, mut sender: SecureSender, mut queue: mpsc::Receiver<QueuedMessage>, context: PeerContext, peer: String) {
+    while let Some(message) = queue.recv().await {
+        if let Some(limit) = &context.outbound_bandwidth {
+            throttle(limit, message.data.len()).await;
+        }
+        
+        if let Err(e) = sender.send(&mut stream, &message.data).await {
+            warn!("Failed to write to peer {}: {}", peer, e);
+            break;
+        }
+        context.metrics.lock().unwrap().bytes_sent += message.data.len() as u64;
+    }
+    
+    let _ = stream.shutdown().await;
+}
+
+/// Read and decrypt messages from a peer until it disconnects
+///
+/// Reading is slowed down while the peer or all peers together send faster than
+/// their bandwidth allows, which holds senders back through TCP flow control.
+/// Messages beyond the peer's rate for their kind are dropped as spam, which
+/// lowers the peer's score at most once per refill period of the rate, so only
+/// sustained flooding gets it banned.
+async fn read_messages(mut stream: ReadHalf<PeerStream>, mut receiver: SecureReceiver, context: PeerContext, peer: String, id: u64) {
+    let mut bandwidth = TokenBucket::new(context.config.peer_bandwidth);
+    let mut rates: HashMap<MessageKind, (TokenBucket, Option<Instant>)> = HashMap::new();
+    
+    loop {
+        match receiver.receive(&mut stream, MAX_MESSAGE_SIZE + 1).await {
+            Ok(Some(data)) => {
+                context.metrics.lock().unwrap().bytes_received += data.len() as u64;
+                
+                let mut wait = bandwidth.reserve(data.len() as u64);
+                if let Some(limit) = &context.inbound_bandwidth {
+                    wait = wait.max(limit.lock().unwrap().reserve(data.len() as u64));
+                }
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+                
+                if let Some(kind) = data.first().copied().and_then(MessageKind::from_channel) {
+                    let (rate, penalized) = rates
+                        .entry(kind)
+                        .or_insert_with(|| (TokenBucket::new(context.config.message_rates.get(kind)), None));
+                    if !rate.try_take(1) {
+                        *context.metrics.lock().unwrap().rate_limited.entry(kind).or_insert(0) += 1;
+                        if penalized.is_none_or(|at| at.elapsed() >= rate.refill_period()) {
+                            debug!("Dropping {:?} messages from peer {} over its rate limit", kind, peer);
+                            context.report(&peer, PeerEvent::Spam);
+                            *penalized = Some(Instant::now());
+                        }
+                        continue;
+                    }
+                }
+                
+                if let Err(e) = context.handle_message(&peer, data) {
+                    warn!("Dropping peer {}: {}", peer, e);
+                    context.report(&peer, PeerEvent::ProtocolViolation);
+                    break;
+                }
+            }
+            Ok(None) => {
+                info!("Peer {} closed the connection", peer);
+                break;
+            }
+            Err(e) => {
+                warn!("Dropping peer {}: {}", peer, e);
+                break;
+            }
+        }
+    }
+    
+    // A newer connection to the same peer may have replaced this one
+    let mut peers = context.peers.lock().unwrap();
+    if peers.get(&peer).is_some_and(|connection| connection.id == id) {
+        peers.remove(&peer);
+        context.lookups.lock().unwrap().remove(&peer);
+    }
+}
+
+/// Write one length-prefixed frame
+pub(crate) async fn write_frame<S: AsyncWrite + Unpin>(stream: &mut S, data: &[u8]) -> std::io::Result<()> {
+    stream.write_all(&(data.len() as u32).to_be_bytes()).await?;
+    stream.write_all(data).await?;
+    stream.flush().await
+}
+
+/// Read one frame, or `None` if the peer closed the connection between frames
+pub(crate) async fn read_frame<S: AsyncRead + Unpin>(stream: &mut S, max_size: usize) -> Result<Option<Vec<u8>>, NetworkError> {
+    let mut length = [0u8; 4];
+    match stream.read_exact(&mut length).await {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    
+    let length = u32::from_be_bytes(length) as usize;
+    if length > max_size {
+        return Err(format!("Frame of {} bytes exceeds the maximum of {}", length, max_size).into());
+    }
+    
+    let mut data = vec![0u8; length];
+    stream.read_exact(&mut data).await?;
+    
+    Ok(Some(data))
+}
@@ -0,0 +1,254 @@
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use snow::{Builder, HandshakeState, StatelessTransportState};
+use tokio::io::{AsyncRead, AsyncWrite};
+use crate::crypto::{KeyPair, Signature};
+use crate::network::{read_frame, write_frame, NetworkError};
+
+/// Noise protocol run on every peer connection
+const NOISE_PARAMS: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
+
+/// Largest Noise message
+const MAX_NOISE_MESSAGE: usize = 65535;
+
+/// Size of the authentication tag added to each encrypted chunk
+const TAG_SIZE: usize = 16;
+
+/// Largest plaintext carried by one encrypted chunk
+const MAX_CHUNK: usize = MAX_NOISE_MESSAGE - TAG_SIZE;
+
+/// Static Diffie-Hellman key of a node's Noise handshakes
+pub struct NoiseKey {
+    /// X25519 private key
+    private: Vec<u8>,
+    /// X25519 public key
+    public: Vec<u8>,
+}
+
+impl NoiseKey {
+    /// Generate a new static key
+    pub fn generate() -> Result<Self, Box<dyn std::error::Error>> {
+        let keypair = Builder::new(NOISE_PARAMS.parse()?).generate_keypair()?;
+        
+        Ok(NoiseKey {
+            private: keypair.private,
+            public: keypair.public,
+        })
+    }
+    
+    /// Get the public key
+    pub fn public_key(&self) -> &[u8] {
+        &self.public
+    }
+}
+
+/// Proof sent during the Noise handshake that a node key owns the Noise static key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoiseIdentity {
+    /// Hex-encoded Ed25519 node public key
+    pub public_key: String,
+    /// Signature of the Noise static key with the node key
+    pub signature: Signature,
+}
+
+impl NoiseIdentity {
+    /// Bind a Noise static key to a node key
+    pub fn new(node_key: &KeyPair, noise_static: &[u8]) -> Self {
+        NoiseIdentity {
+            public_key: node_key.public_key_hex(),
+            signature: node_key.sign(&identity_message(noise_static)),
+        }
+    }
+    
+    /// Check the node key signed a Noise static key
+    pub fn verify(&self, noise_static: &[u8]) -> bool {
+        hex::decode(&self.public_key).is_ok_and(|key| key.len() == 32)
+            && self.signature.verify(&self.public_key, &identity_message(noise_static)).unwrap_or(false)
+    }
+}
+
+/// Get the message a node key signs to claim a Noise static key
+fn identity_message(noise_static: &[u8]) -> Vec<u8> {
+    [b"hypernova-noise-static:".as_slice(), noise_static].concat()
+}
+
+/// Encrypted connection to a peer whose node key has been authenticated
+pub struct SecureChannel {
+    /// Hex-encoded node public key of the peer
+    pub remote_public_key: String,
+    /// Cipher states shared by the sending and receiving sides
+    transport: Arc<StatelessTransportState>,
+}
+
+impl SecureChannel {
+    /// Split the channel into independent sending and receiving sides
+    pub fn split(self) -> (SecureSender, SecureReceiver) {
+        (
+            SecureSender { transport: self.transport.clone(), nonce: 0 },
+            SecureReceiver { transport: self.transport, nonce: 0 },
+        )
+    }
+}
+
+/// Sending side of a secure channel
+pub struct SecureSender {
+    transport: Arc<StatelessTransportState>,
+    nonce: u64,
+}
+
+impl SecureSender {
+    /// Encrypt and write a message of any size
+    ///
+    /// The message length is sent in its own encrypted chunk, followed by the
+    /// message split into chunks that fit in a Noise message.
+    pub async fn send<S: AsyncWrite + Unpin>(&mut self, stream: &mut S, message: &[u8]) -> Result<(), NetworkError> {
+        self.send_chunk(stream, &(message.len() as u32).to_be_bytes()).await?;
+        
+        for chunk in message.chunks(MAX_CHUNK) {
+            self.send_chunk(stream, chunk).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Encrypt and write one chunk
+    async fn send_chunk<S: AsyncWrite + Unpin>(&mut self, stream: &mut S, chunk: &[u8]) -> Result<(), NetworkError> {
+        let mut ciphertext = vec![0u8; chunk.len() + TAG_SIZE];
+        let length = self.transport.write_message(self.nonce, chunk, &mut ciphertext)?;
+        self.nonce += 1;
+        
+        write_frame(stream, &ciphertext[..length]).await?;
+        
+        Ok(())
+    }
+}
+
+/// Receiving side of a secure channel
+pub struct SecureReceiver {
+    transport: Arc<StatelessTransportState>,
+    nonce: u64,
+}
+
+impl SecureReceiver {
+    /// Read and decrypt a message, or `None` if the peer closed the connection between messages
+    pub async fn receive<S: AsyncRead + Unpin>(&mut self, stream: &mut S, max_size: usize) -> Result<Option<Vec<u8>>, NetworkError> {
+        let header = match self.receive_chunk(stream).await? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        
+        let length: [u8; 4] = header.try_into().map_err(|_| "Invalid message header")?;
+        let length = u32::from_be_bytes(length) as usize;
+        if length > max_size {
+            return Err(format!("Message of {} bytes exceeds the maximum of {}", length, max_size).into());
+        }
+        
+        let mut message = Vec::with_capacity(length);
+        while message.len() < length {
+            let chunk = self
+                .receive_chunk(stream)
+                .await?
+                .ok_or("Peer closed the connection mid-message")?;
+            
+            if chunk.is_empty() || message.len() + chunk.len() > length {
+                return Err("Message chunks do not match its length".into());
+            }
+            
+            message.extend_from_slice(&chunk);
+        }
+        
+        Ok(Some(message))
+    }
+    
+    /// Read and decrypt one chunk
+    async fn receive_chunk<S: AsyncRead + Unpin>(&mut self, stream: &mut S) -> Result<Option<Vec<u8>>, NetworkError> {
+        let ciphertext = match read_frame(stream, MAX_NOISE_MESSAGE).await? {
+            Some(ciphertext) => ciphertext,
+            None => return Ok(None),
+        };
+        
+        let mut chunk = vec![0u8; ciphertext.len()];
+        let length = self
+            .transport
+            .read_message(self.nonce, &ciphertext, &mut chunk)
+            .map_err(|_| "Failed to decrypt message from peer")?;
+        self.nonce += 1;
+        chunk.truncate(length);
+        
+        Ok(Some(chunk))
+    }
+}
+
+/// Run a Noise XX handshake and authenticate the peer's node key
+///
+/// Each side's static key is sent encrypted along with a signature of it by the
+/// node key, so the channel is bound to the node identities at both ends.
+pub async fn handshake<S>(
+    stream: &mut S,
+    initiator: bool,
+    noise_key: &NoiseKey,
+    node_key: &KeyPair,
+) -> Result<SecureChannel, NetworkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let builder = Builder::new(NOISE_PARAMS.parse()?).local_private_key(&noise_key.private);
+    let identity = serde_json::to_vec(&NoiseIdentity::new(node_key, &noise_key.public))?;
+    
+    let (state, remote_identity) = if initiator {
+        // -> e
+        let mut state = builder.build_initiator()?;
+        write_handshake_message(stream, &mut state, &[]).await?;
+        // <- e, ee, s, es
+        let remote_identity = read_handshake_message(stream, &mut state).await?;
+        // -> s, se
+        write_handshake_message(stream, &mut state, &identity).await?;
+        (state, remote_identity)
+    } else {
+        let mut state = builder.build_responder()?;
+        read_handshake_message(stream, &mut state).await?;
+        write_handshake_message(stream, &mut state, &identity).await?;
+        let remote_identity = read_handshake_message(stream, &mut state).await?;
+        (state, remote_identity)
+    };
+    
+    let remote_identity: NoiseIdentity = serde_json::from_slice(&remote_identity)
+        .map_err(|_| "Peer sent an invalid identity")?;
+    let remote_static = state.get_remote_static().ok_or("Peer sent no static key")?.to_vec();
+    if !remote_identity.verify(&remote_static) {
+        return Err("Peer identity does not match its static key".into());
+    }
+    
+    Ok(SecureChannel {
+        remote_public_key: remote_identity.public_key,
+        transport: Arc::new(state.into_stateless_transport_mode()?),
+    })
+}
+
+/// Write a handshake message carrying a payload
+async fn write_handshake_message<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    state: &mut HandshakeState,
+    payload: &[u8],
+) -> Result<(), NetworkError> {
+    let mut message = vec![0u8; MAX_NOISE_MESSAGE];
+    let length = state.write_message(payload, &mut message)?;
+    write_frame(stream, &message[..length]).await?;
+    
+    Ok(())
+}
+
+/// Read a handshake message and return its payload
+async fn read_handshake_message<S: AsyncRead + Unpin>(stream: &mut S, state: &mut HandshakeState) -> Result<Vec<u8>, NetworkError> {
+    let message = read_frame(stream, MAX_NOISE_MESSAGE)
+        .await?
+        .ok_or("Peer closed the connection during the handshake")?;
+    
+    let mut payload = vec![0u8; MAX_NOISE_MESSAGE];
+    let length = state
+        .read_message(&message, &mut payload)
+        .map_err(|_| "Invalid Noise handshake message")?;
+    payload.truncate(length);
+    
+    Ok(payload)
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Sustained rate and burst allowed by a token bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rate {
+    /// Tokens added per second
+    pub per_second: u64,
+    /// Most tokens that can be saved up
+    pub burst: u64,
+}
+
+impl Rate {
+    /// Create a rate
+    pub fn new(per_second: u64, burst: u64) -> Self {
+        Rate { per_second, burst }
+    }
+}
+
+/// Token bucket limiting how fast messages or bytes may pass
+///
+/// Time is read from the Tokio clock, so buckets follow a paused clock in tests.
+pub struct TokenBucket {
+    /// Rate tokens are added at and the most that can be saved up
+    rate: Rate,
+    /// Available tokens, negative while reserved tokens are being paid back
+    tokens: f64,
+    /// Last time tokens were added
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket
+    pub fn new(rate: Rate) -> Self {
+        TokenBucket {
+            rate,
+            tokens: rate.burst as f64,
+            updated: Instant::now(),
+        }
+    }
+    
+    /// Get the time an empty bucket takes to fill up again
+    pub fn refill_period(&self) -> Duration {
+        if self.rate.per_second == 0 {
+            return Duration::MAX;
+        }
+        
+        Duration::from_secs_f64(self.rate.burst as f64 / self.rate.per_second as f64)
+    }
+    
+    /// Add the tokens earned since the last update
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let earned = now.duration_since(self.updated).as_secs_f64() * self.rate.per_second as f64;
+        self.tokens = (self.tokens + earned).min(self.rate.burst as f64);
+        self.updated = now;
+    }
+    
+    /// Take tokens if enough are available, returning whether they were taken
+    pub fn try_take(&mut self, amount: u64) -> bool {
+        self.refill();
+        
+        if self.tokens < amount as f64 {
+            return false;
+        }
+        
+        self.tokens -= amount as f64;
+        true
+    }
+    
+    /// Take tokens even if not enough are available, returning how long to wait until they are paid back
+    ///
+    /// Used to slow a stream down to the rate rather than drop what exceeds it.
+    pub fn reserve(&mut self, amount: u64) -> Duration {
+        self.refill();
+        self.tokens -= amount as f64;
+        
+        if self.tokens >= 0.0 || self.rate.per_second == 0 {
+            return Duration::ZERO;
+        }
+        
+        Duration::from_secs_f64(-self.tokens / self.rate.per_second as f64)
+    }
+}
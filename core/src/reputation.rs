@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::utils::{now, JsonFile, Snapshot};
+
+/// Highest score a peer can earn
+pub const MAX_SCORE: i32 = 100;
+
+/// Score at or below which a peer is banned
+pub const BAN_THRESHOLD: i32 = -100;
+
+/// Time a peer stays banned unless told otherwise
+pub const DEFAULT_BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// Behaviour of a peer that changes its score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerEvent {
+    /// Peer relayed a gossip message that passed validation
+    ValidGossip,
+    /// Peer answered a request with the data asked for
+    UsefulResponse,
+    /// Peer did not answer a request in time
+    Unresponsive,
+    /// Peer sent more messages than it is allowed to
+    Spam,
+    /// Peer relayed a gossip message that failed validation
+    InvalidGossip,
+    /// Peer served headers or blocks that do not check out
+    InvalidSyncData,
+    /// Peer sent a message that breaks the wire protocol
+    ProtocolViolation,
+}
+
+impl PeerEvent {
+    /// Get the change in score caused by the event
+    ///
+    /// Serving invalid chain data and breaking the protocol get even the best scored
+    /// peers banned.
+    pub fn score_change(&self) -> i32 {
+        match self {
+            PeerEvent::ValidGossip => 1,
+            PeerEvent::UsefulResponse => 2,
+            PeerEvent::Unresponsive => -10,
+            PeerEvent::Spam => -20,
+            PeerEvent::InvalidGossip => -50,
+            PeerEvent::InvalidSyncData => -200,
+            PeerEvent::ProtocolViolation => -200,
+        }
+    }
+}
+
+/// Ban of a peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ban {
+    /// Hex-encoded node public key of the banned peer
+    pub public_key: String,
+    /// Unix time in seconds at which the ban ends
+    pub until: u64,
+    /// Why the peer was banned
+    pub reason: String,
+}
+
+/// Scores of peers and the peers banned for misbehaving
+///
+/// Bans are saved to a file, when given one, so they outlive restarts. Scores are
+/// kept in memory, so a peer reconnecting keeps its score until the node restarts.
+pub struct Reputation {
+    /// Scores by node public key, peers without one have a score of 0
+    scores: HashMap<String, i32>,
+    /// Bans by node public key, including expired ones until the next ban
+    bans: HashMap<String, Ban>,
+    /// Time a peer stays banned when its score drops too low
+    ban_duration: Duration,
+    /// File the bans are saved to
+    file: Option<JsonFile>,
+    /// Whether the bans changed since they were last saved
+    unsaved: bool,
+}
+
+impl Reputation {
+    /// Create a reputation tracker that keeps bans in memory only
+    pub fn new(ban_duration: Duration) -> Self {
+        Reputation {
+            scores: HashMap::new(),
+            bans: HashMap::new(),
+            ban_duration,
+            file: None,
+            unsaved: false,
+        }
+    }
+    
+    /// Open a reputation tracker saving bans to a file, loading the bans still in force
+    ///
+    /// A file that cannot be read is logged and replaced rather than keeping the node
+    /// from starting.
+    pub fn open(path: &Path, ban_duration: Duration) -> Self {
+        let file = JsonFile::new(path, "banned peers");
+        let now = now();
+        let bans = file
+            .load::<Ban>()
+            .into_iter()
+            .filter(|ban| ban.until > now)
+            .map(|ban| (ban.public_key.clone(), ban))
+            .collect();
+        
+        Reputation {
+            bans,
+            file: Some(file),
+            ..Reputation::new(ban_duration)
+        }
+    }
+    
+    /// Get the score of a peer
+    pub fn score(&self, peer: &str) -> i32 {
+        self.scores.get(peer).copied().unwrap_or(0)
+    }
+    
+    /// Adjust a peer's score for an event, banning it if the score drops too low
+    ///
+    /// Returns the ban if the event got the peer banned.
+    pub fn record(&mut self, peer: &str, event: PeerEvent) -> Option<Ban> {
+        if self.is_banned(peer) {
+            return None;
+        }
+        
+        let score = self.scores.entry(peer.to_string()).or_insert(0);
+        *score = (*score + event.score_change()).min(MAX_SCORE);
+        let score = *score;
+        
+        if score > BAN_THRESHOLD {
+            return None;
+        }
+        
+        Some(self.ban(peer, self.ban_duration, &format!("score dropped to {} after {:?}", score, event)))
+    }
+    
+    /// Ban a peer for a duration, resetting its score
+    pub fn ban(&mut self, peer: &str, duration: Duration, reason: &str) -> Ban {
+        let ban = Ban {
+            public_key: peer.to_string(),
+            until: now().saturating_add(duration.as_secs()),
+            reason: reason.to_string(),
+        };
+        
+        let now = now();
+        self.bans.retain(|_, ban| ban.until > now);
+        self.scores.remove(peer);
+        self.bans.insert(peer.to_string(), ban.clone());
+        self.unsaved = true;
+        
+        ban
+    }
+    
+    /// Lift a peer's ban, returning whether it was banned
+    pub fn unban(&mut self, peer: &str) -> bool {
+        let banned = self.is_banned(peer);
+        
+        if self.bans.remove(peer).is_some() {
+            self.unsaved = true;
+        }
+        
+        banned
+    }
+    
+    /// Check whether a peer is banned
+    pub fn is_banned(&self, peer: &str) -> bool {
+        self.bans.get(peer).is_some_and(|ban| ban.until > now())
+    }
+    
+    /// Get the bans in force
+    pub fn bans(&self) -> Vec<Ban> {
+        let now = now();
+        let mut bans: Vec<Ban> = self.bans.values().filter(|ban| ban.until > now).cloned().collect();
+        bans.sort_by(|a, b| a.public_key.cmp(&b.public_key));
+        bans
+    }
+    
+    /// Take the bans in force to save if they changed, so the file is written outside any lock
+    pub fn take_unsaved(&mut self) -> Option<Snapshot<Ban>> {
+        if !std::mem::take(&mut self.unsaved) {
+            return None;
+        }
+        
+        let bans = self.bans();
+        Some(self.file.as_mut()?.snapshot(bans))
+    }
+    
+    /// Save the bans in force if they changed, keeping the node running if the file cannot be written
+    pub fn save(&mut self) {
+        if let Some(snapshot) = self.take_unsaved() {
+            snapshot.save();
+        }
+    }
+}
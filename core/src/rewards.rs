@@ -0,0 +1,203 @@
+use serde::{Serialize, Deserialize};
+
+/// Shares of transaction fees paid to each recipient, in basis points
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeDistribution {
+    /// Share paid to the block producer and its delegators
+    pub producer_bps: u64,
+    /// Share paid to the owner of the model that made the block's AI proof
+    pub model_provider_bps: u64,
+    /// Share paid to the treasury
+    pub treasury_bps: u64,
+    /// Share removed from the supply
+    pub burn_bps: u64,
+}
+
+impl Default for FeeDistribution {
+    fn default() -> Self {
+        FeeDistribution {
+            producer_bps: 5000,
+            model_provider_bps: 2000,
+            treasury_bps: 2000,
+            burn_bps: 1000,
+        }
+    }
+}
+
+impl FeeDistribution {
+    /// Split an amount of fees between the recipients, rounding in favour of the treasury
+    pub fn split(&self, fees: u64) -> FeeSplit {
+        let share = |bps: u64| (u128::from(fees) * u128::from(bps) / 10000) as u64;
+        
+        let producer = share(self.producer_bps);
+        let model_provider = share(self.model_provider_bps);
+        let burned = share(self.burn_bps);
+        
+        FeeSplit {
+            producer,
+            model_provider,
+            treasury: fees - producer - model_provider - burned,
+            burned,
+        }
+    }
+}
+
+/// Fees of a block split between their recipients
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeSplit {
+    /// Paid to the block producer and its delegators
+    pub producer: u64,
+    /// Paid to the model provider
+    pub model_provider: u64,
+    /// Paid to the treasury
+    pub treasury: u64,
+    /// Removed from the supply
+    pub burned: u64,
+}
+
+/// Issuance schedule and fee distribution of the chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MonetaryPolicy {
+    /// Tokens issued to the producer of each block before any decay
+    pub block_reward: u64,
+    /// Number of blocks after which the block reward decays, 0 keeps it constant
+    pub reward_decay_interval: u64,
+    /// Share of the block reward kept at each decay, in basis points (5000 halves it)
+    pub reward_decay_bps: u64,
+    /// Most tokens that may ever exist, including the genesis supply
+    pub max_supply: Option<u64>,
+    /// Most the supply may grow by per year, in basis points
+    pub max_annual_inflation_bps: Option<u64>,
+    /// Number of blocks produced per year, used to cap inflation
+    pub blocks_per_year: u64,
+    /// How transaction fees are shared
+    pub fee_distribution: FeeDistribution,
+    /// Account receiving the treasury's share of fees
+    pub treasury: String,
+}
+
+impl Default for MonetaryPolicy {
+    fn default() -> Self {
+        MonetaryPolicy {
+            block_reward: 100,
+            // Halve roughly every four years of 12-second blocks
+            reward_decay_interval: 10_512_000,
+            reward_decay_bps: 5000,
+            max_supply: None,
+            max_annual_inflation_bps: None,
+            blocks_per_year: 2_628_000,
+            fee_distribution: FeeDistribution::default(),
+            treasury: "treasury".to_string(),
+        }
+    }
+}
+
+impl MonetaryPolicy {
+    /// Check the policy is consistent
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let distribution = &self.fee_distribution;
+        let total = distribution.producer_bps
+            + distribution.model_provider_bps
+            + distribution.treasury_bps
+            + distribution.burn_bps;
+        
+        if total != 10000 {
+            return Err(format!("Fee distribution must add up to 10000 basis points, got {}", total).into());
+        }
+        
+        if self.reward_decay_bps > 10000 {
+            return Err("Reward decay cannot exceed 10000 basis points".into());
+        }
+        
+        if self.max_annual_inflation_bps.is_some() && self.blocks_per_year == 0 {
+            return Err("An inflation cap requires the number of blocks per year".into());
+        }
+        
+        if self.treasury.is_empty() {
+            return Err("Treasury account must be set".into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the scheduled block reward at a height, before supply and inflation caps
+    pub fn scheduled_reward(&self, height: u64) -> u64 {
+        if self.reward_decay_interval == 0 || self.reward_decay_bps == 10000 {
+            return self.block_reward;
+        }
+        
+        let mut reward = self.block_reward;
+        for _ in 0..height / self.reward_decay_interval {
+            reward = (u128::from(reward) * u128::from(self.reward_decay_bps) / 10000) as u64;
+            
+            if reward == 0 {
+                break;
+            }
+        }
+        
+        reward
+    }
+    
+    /// Get the block reward at a height, capped by the maximum supply and annual inflation
+    pub fn block_reward(&self, height: u64, totals: &RewardTotals) -> u64 {
+        let mut reward = self.scheduled_reward(height);
+        
+        if let Some(max_supply) = self.max_supply {
+            let minted = totals.genesis_supply.saturating_add(totals.issued);
+            reward = reward.min(max_supply.saturating_sub(minted));
+        }
+        
+        if let Some(max_inflation_bps) = self.max_annual_inflation_bps {
+            let per_block = u128::from(totals.supply()) * u128::from(max_inflation_bps)
+                / 10000
+                / u128::from(self.blocks_per_year);
+            reward = reward.min(per_block as u64);
+        }
+        
+        reward
+    }
+}
+
+/// Running totals of issuance and fee distribution
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardTotals {
+    /// Tokens funded in the genesis state
+    pub genesis_supply: u64,
+    /// Tokens issued as block rewards
+    pub issued: u64,
+    /// Transaction fees paid
+    pub fees: u64,
+    /// Fees paid to block producers and their delegators
+    pub producer_fees: u64,
+    /// Fees paid to model providers
+    pub model_provider_fees: u64,
+    /// Fees paid to the treasury
+    pub treasury_fees: u64,
+    /// Fees removed from the supply
+    pub burned: u64,
+    /// Stake removed from the supply by slashing
+    pub slashed: u64,
+}
+
+impl RewardTotals {
+    /// Get the number of tokens in existence
+    pub fn supply(&self) -> u64 {
+        self.genesis_supply
+            .saturating_add(self.issued)
+            .saturating_sub(self.burned)
+            .saturating_sub(self.slashed)
+    }
+    
+    /// Add a block's fees to the totals
+    pub fn record_fees(&mut self, fees: u64, split: &FeeSplit) -> Result<(), Box<dyn std::error::Error>> {
+        let add = |total: u64, amount: u64| total.checked_add(amount).ok_or("Fee totals overflow");
+        self.fees = add(self.fees, fees)?;
+        self.producer_fees = add(self.producer_fees, split.producer)?;
+        self.model_provider_fees = add(self.model_provider_fees, split.model_provider)?;
+        self.treasury_fees = add(self.treasury_fees, split.treasury)?;
+        self.burned = add(self.burned, split.burned)?;
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use log::{info, error};
+
+use crate::chain::Blockchain;
+use crate::network::P2PNetwork;
+use crate::reputation::DEFAULT_BAN_DURATION;
+use crate::transaction::Transaction;
+
+/// JSON-RPC error code for malformed JSON
+const PARSE_ERROR: i64 = -32700;
+/// JSON-RPC error code for unknown methods
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for bad parameters
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for transactions rejected by the node
+const TRANSACTION_REJECTED: i64 = -32003;
+/// JSON-RPC error code for admin methods called by remote clients
+const ADMIN_ONLY: i64 = -32004;
+
+/// JSON-RPC request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcRequest {
+    /// Protocol version, always "2.0"
+    pub jsonrpc: String,
+    /// Method name
+    pub method: String,
+    /// Positional parameters
+    #[serde(default)]
+    pub params: Value,
+    /// Request identifier echoed in the response
+    #[serde(default)]
+    pub id: Value,
+}
+
+/// JSON-RPC error object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcError {
+    /// Error code
+    pub code: i64,
+    /// Error message
+    pub message: String,
+}
+
+/// JSON-RPC response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcResponse {
+    /// Protocol version, always "2.0"
+    pub jsonrpc: String,
+    /// Result of a successful call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Error of a failed call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    /// Identifier of the request
+    pub id: Value,
+}
+
+impl RpcResponse {
+    /// Build a response from a call result
+    fn new(id: Value, outcome: Result<Value, RpcError>) -> Self {
+        let (result, error) = match outcome {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        
+        RpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result,
+            error,
+            id,
+        }
+    }
+}
+
+/// JSON-RPC server for HyperNova Chain
+pub struct RpcServer {
+    /// Blockchain queried by RPC calls
+    blockchain: Arc<Blockchain>,
+    /// Network managed by admin calls
+    network: Option<Arc<P2PNetwork>>,
+    /// RPC port
+    port: u16,
+}
+
+impl RpcServer {
+    /// Create a new RPC server
+    pub fn new(blockchain: Arc<Blockchain>, port: u16) -> Self {
+        RpcServer { blockchain, network: None, port }
+    }
+    
+    /// Serve admin calls inspecting and managing the peers of a network
+    pub fn with_network(mut self, network: Arc<P2PNetwork>) -> Self {
+        self.network = Some(network);
+        self
+    }
+    
+    /// Serve JSON-RPC over HTTP until the server fails
+    pub async fn start(self: Arc<Self>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let addr = SocketAddr::from(([0, 0, 0, 0], self.port));
+        
+        let make_service = make_service_fn(move |conn: &AddrStream| {
+            let server = self.clone();
+            let remote = conn.remote_addr();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let server = server.clone();
+                    async move { Ok::<_, Infallible>(server.handle_http(request, remote).await) }
+                }))
+            }
+        });
+        
+        info!("RPC server listening on {}", addr);
+        
+        Server::try_bind(&addr)?.serve(make_service).await?;
+        
+        Ok(())
+    }
+    
+    /// Handle an HTTP request carrying a JSON-RPC call
+    ///
+    /// Admin calls are only served to clients on the same host.
+    async fn handle_http(&self, request: Request<Body>, remote: SocketAddr) -> Response<Body> {
+        if request.method() != Method::POST {
+            return Response::builder()
+                .status(StatusCode::METHOD_NOT_ALLOWED)
+                .body(Body::empty())
+                .unwrap();
+        }
+        
+        let response = match hyper::body::to_bytes(request.into_body()).await {
+            Ok(body) => match serde_json::from_slice::<RpcRequest>(&body) {
+                Ok(rpc_request) if rpc_request.method.starts_with("admin_") && !remote.ip().is_loopback() => {
+                    RpcResponse::new(rpc_request.id, Err(RpcError {
+                        code: ADMIN_ONLY,
+                        message: "Admin methods are only served to local clients".to_string(),
+                    }))
+                }
+                Ok(rpc_request) => self.handle(rpc_request),
+                Err(e) => RpcResponse::new(Value::Null, Err(RpcError {
+                    code: PARSE_ERROR,
+                    message: e.to_string(),
+                })),
+            },
+            Err(e) => {
+                error!("Failed to read RPC request body: {}", e);
+                RpcResponse::new(Value::Null, Err(RpcError {
+                    code: PARSE_ERROR,
+                    message: e.to_string(),
+                }))
+            }
+        };
+        
+        Response::builder()
+            .header("Content-Type", "application/json")
+            .body(Body::from(serde_json::to_vec(&response).unwrap()))
+            .unwrap()
+    }
+    
+    /// Handle a JSON-RPC call
+    pub fn handle(&self, request: RpcRequest) -> RpcResponse {
+        let outcome = self.dispatch(&request.method, &request.params);
+        RpcResponse::new(request.id, outcome)
+    }
+    
+    /// Run an RPC method
+    fn dispatch(&self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "hnc_sendRawTransaction" => {
+                let transaction: Transaction = params
+                    .get(0)
+                    .cloned()
+                    .and_then(|value| serde_json::from_value(value).ok())
+                    .ok_or_else(|| RpcError {
+                        code: INVALID_PARAMS,
+                        message: "Expected a signed transaction as parameter 0".to_string(),
+                    })?;
+                let hash = transaction.hash();
+                
+                self.blockchain.add_transaction(transaction).map_err(|e| RpcError {
+                    code: TRANSACTION_REJECTED,
+                    message: e.to_string(),
+                })?;
+                
+                to_value(hash)
+            }
+            "hnc_getTransactionScore" => {
+                let hash = string_param(params, 0)?;
+                to_value(self.blockchain.get_transaction_score(&hash))
+            }
+            "hnc_getPendingScores" => to_value(self.blockchain.get_pending_scores()),
+            "hnc_getAccount" => {
+                let address = string_param(params, 0)?;
+                let account = self.blockchain.get_account(&address);
+                Ok(json!({
+                    "address": address,
+                    "balance": account.balance,
+                    "nonce": account.nonce,
+                }))
+            }
+            "hnc_getValidators" => to_value(self.blockchain.get_validators()),
+            "hnc_getDelegations" => {
+                let delegator = string_param(params, 0)?;
+                to_value(self.blockchain.get_delegations(&delegator))
+            }
+            "hnc_getValidatorUptime" => {
+                let address = string_param(params, 0)?;
+                to_value(self.blockchain.get_uptime(&address))
+            }
+            "hnc_getUptimeStats" => to_value(self.blockchain.get_uptime_reports()),
+            "hnc_getValidatorSet" => {
+                let height = match params.get(0) {
+                    Some(_) => u64_param(params, 0)?,
+                    None => self.blockchain.get_height(),
+                };
+                to_value(self.blockchain.get_validator_set_at(height))
+            }
+            "hnc_getIssuance" => {
+                let totals = self.blockchain.get_reward_totals();
+                Ok(json!({
+                    "supply": totals.supply(),
+                    "next_block_reward": self.blockchain.get_next_block_reward(),
+                    "totals": totals,
+                }))
+            }
+            "hnc_getRandomness" => {
+                let height = match params.get(0) {
+                    Some(_) => u64_param(params, 0)?,
+                    None => self.blockchain.get_height(),
+                };
+                to_value(self.blockchain.get_randomness_at(height))
+            }
+            "hnc_getVerificationCommittee" => {
+                let height = match params.get(0) {
+                    Some(_) => u64_param(params, 0)?,
+                    None => self.blockchain.get_height() + 1,
+                };
+                to_value(self.blockchain.get_verification_committee(height))
+            }
+            "hnc_getModel" => {
+                let model_id = string_param(params, 0)?;
+                to_value(self.blockchain.get_model(&model_id))
+            }
+            "hnc_getModels" => to_value(self.blockchain.get_models()),
+            "hnc_getProposal" => {
+                let id = u64_param(params, 0)?;
+                to_value(self.blockchain.get_proposal(id))
+            }
+            "admin_peers" => {
+                let network = self.network()?;
+                let peers: Vec<Value> = network
+                    .get_peers()
+                    .into_iter()
+                    .filter_map(|peer| network.get_peer_info(&peer))
+                    .map(|info| {
+                        let score = network.peer_score(&info.public_key);
+                        json!({ "info": info, "score": score })
+                    })
+                    .collect();
+                to_value(peers)
+            }
+            "admin_bannedPeers" => to_value(self.network()?.banned_peers()),
+            "admin_banPeer" => {
+                let peer = string_param(params, 0)?;
+                let duration = match params.get(1) {
+                    Some(_) => Duration::from_secs(u64_param(params, 1)?),
+                    None => DEFAULT_BAN_DURATION,
+                };
+                to_value(self.network()?.ban_peer(&peer, duration))
+            }
+            "admin_unbanPeer" => {
+                let peer = string_param(params, 0)?;
+                to_value(self.network()?.unban_peer(&peer))
+            }
+            "admin_networkMetrics" => to_value(self.network()?.metrics()),
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Method not found: {}", method),
+            }),
+        }
+    }
+    
+    /// Get the network managed by admin calls
+    fn network(&self) -> Result<&P2PNetwork, RpcError> {
+        self.network.as_deref().ok_or_else(|| RpcError {
+            code: METHOD_NOT_FOUND,
+            message: "Networking is not enabled on this server".to_string(),
+        })
+    }
+}
+
+/// Read a positional string parameter
+fn string_param(params: &Value, index: usize) -> Result<String, RpcError> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| RpcError {
+            code: INVALID_PARAMS,
+            message: format!("Expected a string as parameter {}", index),
+        })
+}
+
+/// Read a positional integer parameter
+fn u64_param(params: &Value, index: usize) -> Result<u64, RpcError> {
+    params
+        .get(index)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| RpcError {
+            code: INVALID_PARAMS,
+            message: format!("Expected an integer as parameter {}", index),
+        })
+}
+
+/// Serialize a method result
+fn to_value<T: Serialize>(result: T) -> Result<Value, RpcError> {
+    Ok(serde_json::to_value(result).unwrap())
+}
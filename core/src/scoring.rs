@@ -0,0 +1,338 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+
+use crate::ai_client::{AiClient, AiClientError};
+use crate::state::ChainState;
+use crate::transaction::Transaction;
+use crate::utils;
+
+/// Highest anomaly score, in basis points
+pub const MAX_SCORE_BPS: u16 = 10000;
+
+/// State a transaction is scored against
+pub struct ScoringContext<'a> {
+    /// State after the latest block, holding at least the accounts of the scored transactions
+    pub state: &'a ChainState,
+    /// Transactions waiting in the pending pool
+    pub pending: &'a [Transaction],
+}
+
+/// Anomaly score of a transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionScore {
+    /// How anomalous the transaction looks, in basis points
+    pub score_bps: u16,
+    /// Patterns that contributed to the score
+    pub reasons: Vec<String>,
+}
+
+impl TransactionScore {
+    /// Raise the score for a matched pattern, capped at the maximum
+    pub fn add(&mut self, score_bps: u16, reason: String) {
+        self.score_bps = self.score_bps.saturating_add(score_bps).min(MAX_SCORE_BPS);
+        self.reasons.push(reason);
+    }
+}
+
+/// Scores transactions for anomalies before they are admitted or included in blocks
+pub trait TransactionScorer: Send + Sync {
+    /// Score a transaction against the current state and pending pool
+    fn score(&self, tx: &Transaction, context: &ScoringContext) -> Result<TransactionScore, Box<dyn std::error::Error>>;
+    
+    /// Get the name of this scorer
+    fn name(&self) -> &'static str;
+}
+
+/// Rule-based scorer looking at sending velocity, value outliers and new accounts
+#[derive(Debug, Clone)]
+pub struct HeuristicScorer {
+    /// Pending transactions a sender may have before it is sending too fast
+    pub max_pending_per_sender: usize,
+    /// Share of the sender's balance above which an amount is an outlier, in basis points
+    pub outlier_balance_bps: u64,
+    /// Multiple of the median pending amount above which an amount is an outlier
+    pub outlier_median_multiple: u64,
+}
+
+impl Default for HeuristicScorer {
+    fn default() -> Self {
+        HeuristicScorer {
+            max_pending_per_sender: 8,
+            outlier_balance_bps: 9000,
+            outlier_median_multiple: 100,
+        }
+    }
+}
+
+impl TransactionScorer for HeuristicScorer {
+    fn score(&self, tx: &Transaction, context: &ScoringContext) -> Result<TransactionScore, Box<dyn std::error::Error>> {
+        let mut score = TransactionScore::default();
+        let hash = tx.hash();
+        let amount = tx.amount.unwrap_or(0);
+        let sender = context.state.account(&tx.from);
+        
+        // Velocity: many transactions from one sender waiting at once
+        let in_flight = context
+            .pending
+            .iter()
+            .filter(|pending| pending.from == tx.from && pending.hash() != hash)
+            .count();
+        if in_flight >= self.max_pending_per_sender {
+            score.add(4000, format!("sender has {} other pending transactions", in_flight));
+        }
+        
+        // Value outliers: draining the sender, or dwarfing the rest of the pool
+        if amount > 0 && u128::from(amount) * 10000 >= u128::from(sender.balance) * u128::from(self.outlier_balance_bps) {
+            score.add(3000, format!("amount {} is most of the sender's balance {}", amount, sender.balance));
+        }
+        
+        let mut amounts: Vec<u64> = context
+            .pending
+            .iter()
+            .filter(|pending| pending.hash() != hash)
+            .filter_map(|pending| pending.amount)
+            .filter(|amount| *amount > 0)
+            .collect();
+        if !amounts.is_empty() {
+            amounts.sort_unstable();
+            let median = amounts[amounts.len() / 2];
+            
+            if u128::from(amount) > u128::from(median) * u128::from(self.outlier_median_multiple) {
+                score.add(2000, format!("amount {} is far above the pending median {}", amount, median));
+            }
+        }
+        
+        // New accounts: a first transaction paying an account the chain has never seen
+        if let Some(to) = &tx.to {
+            let recipient = context.state.account(to);
+            
+            if sender.nonce == 0 && amount > 0 && recipient.balance == 0 && recipient.nonce == 0 {
+                score.add(2000, format!("new sender {} paying unseen account {}", tx.from, to));
+            }
+        }
+        
+        Ok(score)
+    }
+    
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+}
+
+/// Scorer asking a model served by the AI service for the anomaly probability of a transaction
+pub struct AiServiceScorer {
+    /// Client for the AI service
+    client: AiClient,
+    /// Model predicting the anomaly probability
+    model_id: String,
+}
+
+impl AiServiceScorer {
+    /// Create a scorer using a model of the AI service
+    pub fn new(client: AiClient, model_id: &str) -> Self {
+        AiServiceScorer {
+            client,
+            model_id: model_id.to_string(),
+        }
+    }
+    
+    /// Build the model input for a transaction
+    pub fn features(tx: &Transaction, context: &ScoringContext) -> Value {
+        let sender = context.state.account(&tx.from);
+        let recipient = tx.to.as_ref().map(|to| context.state.account(to)).unwrap_or_default();
+        let in_flight = context.pending.iter().filter(|pending| pending.from == tx.from).count();
+        
+        json!([[
+            tx.amount.unwrap_or(0),
+            tx.fee,
+            tx.nonce,
+            sender.balance,
+            sender.nonce,
+            recipient.balance,
+            in_flight,
+        ]])
+    }
+    
+    /// Score a transaction, for callers already running on a runtime
+    pub async fn score_async(&self, tx: &Transaction, context: &ScoringContext<'_>) -> Result<TransactionScore, AiClientError> {
+        let response = self.client.predict(&self.model_id, &Self::features(tx, context)).await?;
+        
+        // Models may return one value per input or a vector per input
+        let probability = match response.predictions.first() {
+            Some(Value::Array(values)) => values.first().and_then(Value::as_f64),
+            Some(value) => value.as_f64(),
+            None => None,
+        }
+        .ok_or("AI service returned no anomaly prediction")?;
+        
+        let mut score = TransactionScore::default();
+        let score_bps = (probability.clamp(0.0, 1.0) * f64::from(MAX_SCORE_BPS)).round() as u16;
+        if score_bps > 0 {
+            score.add(score_bps, format!("model {} predicts anomaly probability {:.2}", self.model_id, probability));
+        }
+        
+        Ok(score)
+    }
+}
+
+impl TransactionScorer for AiServiceScorer {
+    /// Score a transaction, failing rather than blocking inside a current-thread runtime
+    fn score(&self, tx: &Transaction, context: &ScoringContext) -> Result<TransactionScore, Box<dyn std::error::Error>> {
+        utils::block_on(None, self.score_async(tx, context))
+            .and_then(|score| score)
+            .map_err(|e| e as Box<dyn std::error::Error>)
+    }
+    
+    fn name(&self) -> &'static str {
+        "ai-service"
+    }
+}
+
+/// What the node does with a transaction after scoring it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ScreeningAction {
+    /// Admit the transaction without comment
+    Admit,
+    /// Admit the transaction and log a warning
+    Log,
+    /// Admit the transaction but include it after unflagged ones
+    Deprioritize,
+    /// Refuse the transaction
+    Reject,
+}
+
+/// Score thresholds at which each action is taken, in basis points
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScreeningPolicy {
+    /// Score from which transactions are logged
+    pub log_bps: Option<u16>,
+    /// Score from which transactions are deprioritized
+    pub deprioritize_bps: Option<u16>,
+    /// Score from which transactions are rejected
+    pub reject_bps: Option<u16>,
+}
+
+impl Default for ScreeningPolicy {
+    fn default() -> Self {
+        ScreeningPolicy {
+            log_bps: Some(5000),
+            deprioritize_bps: None,
+            reject_bps: None,
+        }
+    }
+}
+
+impl ScreeningPolicy {
+    /// Get the strictest action whose threshold a score reaches
+    pub fn action(&self, score_bps: u16) -> ScreeningAction {
+        let reached = |threshold: Option<u16>| threshold.is_some_and(|threshold| score_bps >= threshold);
+        
+        if reached(self.reject_bps) {
+            ScreeningAction::Reject
+        } else if reached(self.deprioritize_bps) {
+            ScreeningAction::Deprioritize
+        } else if reached(self.log_bps) {
+            ScreeningAction::Log
+        } else {
+            ScreeningAction::Admit
+        }
+    }
+}
+
+/// Outcome of screening a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreeningResult {
+    /// Hash of the screened transaction
+    pub transaction_hash: String,
+    /// Scorer that produced the score
+    pub scorer: String,
+    /// Anomaly score, in basis points
+    pub score_bps: u16,
+    /// Patterns that contributed to the score
+    pub reasons: Vec<String>,
+    /// Action taken
+    pub action: ScreeningAction,
+}
+
+/// Scorer and policy applied to transactions entering the pool and blocks
+pub struct TransactionScreener {
+    /// Scorer rating transactions
+    scorer: Box<dyn TransactionScorer>,
+    /// Thresholds turning scores into actions
+    policy: ScreeningPolicy,
+}
+
+impl Default for TransactionScreener {
+    fn default() -> Self {
+        Self::new(Box::new(HeuristicScorer::default()), ScreeningPolicy::default())
+    }
+}
+
+impl TransactionScreener {
+    /// Create a screener from a scorer and a policy
+    pub fn new(scorer: Box<dyn TransactionScorer>, policy: ScreeningPolicy) -> Self {
+        TransactionScreener { scorer, policy }
+    }
+    
+    /// Score a transaction and decide what to do with it, admitting it if the scorer fails
+    pub fn screen(&self, tx: &Transaction, context: &ScoringContext) -> ScreeningResult {
+        let score = match self.scorer.score(tx, context) {
+            Ok(score) => score,
+            Err(e) => TransactionScore {
+                score_bps: 0,
+                reasons: vec![format!("scoring failed: {}", e)],
+            },
+        };
+        
+        ScreeningResult {
+            transaction_hash: tx.hash(),
+            scorer: self.scorer.name().to_string(),
+            score_bps: score.score_bps,
+            action: self.policy.action(score.score_bps),
+            reasons: score.reasons,
+        }
+    }
+}
+
+/// Most recent screening results by transaction hash
+#[derive(Debug, Clone)]
+pub struct ScoreLog {
+    /// Number of results kept
+    capacity: usize,
+    /// Hashes in the order they were first recorded
+    order: VecDeque<String>,
+    /// Results by transaction hash
+    results: HashMap<String, ScreeningResult>,
+}
+
+impl ScoreLog {
+    /// Create a log keeping a number of results
+    pub fn new(capacity: usize) -> Self {
+        ScoreLog {
+            capacity,
+            order: VecDeque::new(),
+            results: HashMap::new(),
+        }
+    }
+    
+    /// Record a result, replacing any earlier result for the transaction
+    pub fn record(&mut self, result: ScreeningResult) {
+        if !self.results.contains_key(&result.transaction_hash) {
+            self.order.push_back(result.transaction_hash.clone());
+        }
+        
+        self.results.insert(result.transaction_hash.clone(), result);
+        
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.results.remove(&oldest);
+            }
+        }
+    }
+    
+    /// Get the latest result for a transaction
+    pub fn get(&self, hash: &str) -> Option<&ScreeningResult> {
+        self.results.get(hash)
+    }
+}
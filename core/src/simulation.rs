@@ -0,0 +1,748 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use log::{debug, warn};
+
+use crate::block::Block;
+use crate::chain::Blockchain;
+use crate::consensus::Consensus;
+use crate::crypto::KeyPair;
+use crate::spec::{ChainSpec, GenesisAccount};
+use crate::staking::ValidatorRegistration;
+use crate::transaction::{Transaction, TransactionType};
+
+/// Most blocks sent in reply to one catch-up request
+const MAX_BLOCKS_PER_REPLY: u64 = 64;
+
+/// Balance and self stake of every simulated validator
+const VALIDATOR_STAKE: u64 = 10000;
+
+/// How a simulated node behaves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeBehavior {
+    /// Follows the protocol
+    Honest,
+    /// Never produces, sends or receives anything
+    Crashed,
+    /// Signs two conflicting blocks for each of its slots, sending each to half of its peers
+    Equivocating,
+}
+
+/// Latency and loss of the simulated network
+#[derive(Debug, Clone)]
+pub struct NetworkConditions {
+    /// Shortest delivery time, in milliseconds
+    pub min_latency_ms: u64,
+    /// Longest delivery time, in milliseconds
+    pub max_latency_ms: u64,
+    /// Share of messages lost, in basis points
+    pub drop_bps: u16,
+}
+
+impl Default for NetworkConditions {
+    fn default() -> Self {
+        NetworkConditions {
+            min_latency_ms: 10,
+            max_latency_ms: 100,
+            drop_bps: 0,
+        }
+    }
+}
+
+/// Parameters of a simulation run
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    /// Seed of every random choice, so runs can be replayed
+    pub seed: u64,
+    /// Virtual time between block production attempts, in milliseconds
+    pub slot_ms: u64,
+    /// Blocks that must be built on top of a block before it counts as finalized
+    pub finality_depth: u64,
+    /// Latency and loss of the network
+    pub network: NetworkConditions,
+    /// Chain the nodes run, funded with the validators' stake at genesis
+    pub spec: ChainSpec,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            seed: 0,
+            slot_ms: 1000,
+            finality_depth: 2,
+            network: NetworkConditions::default(),
+            spec: ChainSpec {
+                chain_id: "hypernova-sim".to_string(),
+                epoch_length: 10,
+                min_self_stake: 1000,
+                slot_duration: 3,
+                ..ChainSpec::default()
+            },
+        }
+    }
+}
+
+/// Message exchanged between simulated nodes
+#[derive(Debug, Clone)]
+enum SimMessage {
+    /// Newly produced or relayed block
+    Block(Box<Block>),
+    /// Request for blocks from a height onwards
+    GetBlocks(u64),
+    /// Reply to a block request
+    Blocks(Vec<Block>),
+}
+
+/// Something that happens at a point of virtual time
+#[derive(Debug, Clone)]
+enum SimEvent {
+    /// Every live node tries to produce a block
+    Slot,
+    /// A message reaches a node
+    Deliver {
+        from: usize,
+        to: usize,
+        message: SimMessage,
+    },
+}
+
+/// Nodes that cannot reach each other during a window of virtual time
+#[derive(Debug, Clone)]
+struct Partition {
+    /// Group of every partitioned node, nodes outside all groups are unaffected
+    groups: BTreeMap<usize, usize>,
+    /// Start of the partition, in milliseconds
+    from_ms: u64,
+    /// End of the partition, in milliseconds
+    until_ms: u64,
+}
+
+/// Message counters of a simulation run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetworkStats {
+    /// Messages sent
+    pub sent: u64,
+    /// Messages delivered
+    pub delivered: u64,
+    /// Messages lost to random drops
+    pub dropped: u64,
+    /// Messages lost to partitions
+    pub partitioned: u64,
+}
+
+/// Node of a simulation
+struct SimNode {
+    /// Validator address of the node
+    address: String,
+    /// Key the node signs with
+    keypair: KeyPair,
+    /// Directory holding the node's chains
+    dir: PathBuf,
+    /// Number of chains the node has opened, naming the directory of the next one
+    chains: u64,
+    /// The node's own copy of the chain
+    blockchain: Blockchain,
+    /// Consensus engine producing and validating blocks
+    consensus: Box<dyn Consensus>,
+    /// How the node behaves
+    behavior: NodeBehavior,
+    /// Blocks received off the node's chain, by hash
+    known: BTreeMap<String, Block>,
+    /// Hashes of blocks that failed validation
+    rejected: BTreeSet<String>,
+}
+
+/// Deterministic simulation of validator nodes running a consensus engine over a virtual network
+///
+/// Virtual time only advances between events and also timestamps blocks, so a run with the
+/// same seed makes the same choices and produces the same blocks. Nodes follow the longest
+/// chain they know of, so engines letting several validators produce at once converge.
+pub struct Simulation {
+    /// Parameters of the run
+    config: SimulationConfig,
+    /// Simulated nodes
+    nodes: Vec<SimNode>,
+    /// Pending events by time and insertion order
+    events: BTreeMap<(u64, u64), SimEvent>,
+    /// Insertion counter ordering events scheduled for the same time
+    next_event: u64,
+    /// Current virtual time, in milliseconds
+    now_ms: u64,
+    /// Virtual time read by the nodes' chains, in milliseconds
+    clock: Arc<AtomicU64>,
+    /// Scheduled partitions
+    partitions: Vec<Partition>,
+    /// Random source of latencies and drops
+    rng: StdRng,
+    /// Message counters
+    stats: NetworkStats,
+}
+
+impl Simulation {
+    /// Create a simulation with one node per validator, storing chains under a directory
+    ///
+    /// Every validator is funded and registered in block 1, and elected for the first epoch after it.
+    /// The engine factory gets each validator's address and key.
+    pub fn new<F>(
+        data_dir: &str,
+        mut config: SimulationConfig,
+        validators: &[(&str, NodeBehavior)],
+        engine: F,
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        F: Fn(&str, KeyPair) -> Box<dyn Consensus>,
+    {
+        if validators.is_empty() {
+            return Err("A simulation needs at least one validator".into());
+        }
+        
+        if config.network.min_latency_ms > config.network.max_latency_ms {
+            return Err("Minimum latency cannot exceed maximum latency".into());
+        }
+        
+        for (address, _) in validators {
+            config.spec.genesis_accounts.push(GenesisAccount {
+                address: address.to_string(),
+                balance: VALIDATOR_STAKE,
+                public_key: Some(Self::keypair(config.seed, address)?.public_key_hex()),
+            });
+        }
+        
+        let clock = Arc::new(AtomicU64::new(0));
+        let mut nodes = Vec::new();
+        for (address, behavior) in validators {
+            let dir = Path::new(data_dir).join(address);
+            let _ = std::fs::remove_dir_all(&dir);
+            
+            let mut consensus = engine(address, Self::keypair(config.seed, address)?);
+            consensus.init()?;
+            
+            nodes.push(SimNode {
+                address: address.to_string(),
+                keypair: Self::keypair(config.seed, address)?,
+                blockchain: Self::open_chain(&dir.join("0"), &config.spec, &clock)?,
+                dir,
+                chains: 1,
+                consensus,
+                behavior: *behavior,
+                known: BTreeMap::new(),
+                rejected: BTreeSet::new(),
+            });
+        }
+        
+        let mut simulation = Simulation {
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+            nodes,
+            events: BTreeMap::new(),
+            next_event: 0,
+            now_ms: 0,
+            clock,
+            partitions: Vec::new(),
+            stats: NetworkStats::default(),
+        };
+        
+        simulation.bootstrap()?;
+        simulation.schedule(simulation.config.slot_ms, SimEvent::Slot);
+        
+        Ok(simulation)
+    }
+    
+    /// Derive a validator's key from the simulation seed
+    fn keypair(seed: u64, address: &str) -> Result<KeyPair, Box<dyn std::error::Error>> {
+        let digest = Sha256::new()
+            .chain_update(seed.to_be_bytes())
+            .chain_update(address.as_bytes())
+            .finalize();
+        
+        KeyPair::from_seed(&digest.into())
+    }
+    
+    /// Open a chain timestamping blocks with the virtual time
+    fn open_chain(dir: &Path, spec: &ChainSpec, clock: &Arc<AtomicU64>) -> Result<Blockchain, Box<dyn std::error::Error>> {
+        let clock = clock.clone();
+        let blockchain = Blockchain::with_spec(dir.to_str().ok_or("Invalid data directory")?, spec.clone())?
+            .with_clock(Arc::new(move || clock.load(Ordering::SeqCst) / 1000));
+        
+        Ok(blockchain)
+    }
+    
+    /// Register every validator and fill the first epoch with the same blocks on every node
+    fn bootstrap(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let registrations = self.nodes
+            .iter()
+            .map(|node| {
+                let registration = ValidatorRegistration {
+                    public_key: node.keypair.public_key_hex(),
+                    quantum_public_key: None,
+                    commission_bps: 0,
+                };
+                let mut tx = Transaction::new(
+                    TransactionType::ValidatorRegistration,
+                    node.address.clone(),
+                    None,
+                    Some(VALIDATOR_STAKE),
+                    Some(registration.encode()),
+                    0,
+                    0,
+                );
+                // Wall-clock timestamps would make runs with the same seed differ
+                tx.timestamp = self.now_ms / 1000;
+                tx.sign_with(&node.keypair);
+                tx
+            })
+            .collect();
+        
+        let mut transactions = registrations;
+        loop {
+            let block = self.nodes[0].blockchain.build_block(std::mem::take(&mut transactions))?;
+            let height = block.header.height;
+            
+            for node in &self.nodes {
+                node.blockchain.add_block(block.clone())?;
+            }
+            
+            if self.config.spec.is_epoch_boundary(height + 1) {
+                return Ok(());
+            }
+        }
+    }
+    
+    /// Cut the network into groups of nodes that cannot reach each other for a window of virtual time
+    pub fn partition(&mut self, groups: &[&[&str]], from_ms: u64, until_ms: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let mut members = BTreeMap::new();
+        
+        for (group, addresses) in groups.iter().enumerate() {
+            for address in addresses.iter() {
+                let index = self.index_of(address).ok_or_else(|| format!("Unknown node: {}", address))?;
+                if members.insert(index, group).is_some() {
+                    return Err(format!("Node {} is in more than one group", address).into());
+                }
+            }
+        }
+        
+        self.partitions.push(Partition {
+            groups: members,
+            from_ms,
+            until_ms,
+        });
+        
+        Ok(())
+    }
+    
+    /// Run until a point of virtual time
+    pub fn run_until(&mut self, until_ms: u64) {
+        while let Some(entry) = self.events.first_entry() {
+            let (time, _) = *entry.key();
+            if time > until_ms {
+                break;
+            }
+            
+            let event = entry.remove();
+            self.set_now(time);
+            
+            match event {
+                SimEvent::Slot => {
+                    for index in 0..self.nodes.len() {
+                        self.produce(index);
+                    }
+                    for index in 0..self.nodes.len() {
+                        self.poll_peer(index);
+                    }
+                    self.schedule(self.now_ms + self.config.slot_ms, SimEvent::Slot);
+                }
+                SimEvent::Deliver { from, to, message } => {
+                    self.stats.delivered += 1;
+                    self.receive(from, to, message);
+                }
+            }
+        }
+        
+        self.set_now(until_ms);
+    }
+    
+    /// Run for a span of virtual time
+    pub fn run_for(&mut self, duration_ms: u64) {
+        self.run_until(self.now_ms + duration_ms);
+    }
+    
+    /// Get the current virtual time, in milliseconds
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+    
+    /// Get the message counters
+    pub fn stats(&self) -> &NetworkStats {
+        &self.stats
+    }
+    
+    /// Get a node's copy of the chain
+    pub fn blockchain(&self, address: &str) -> Option<&Blockchain> {
+        self.index_of(address).map(|index| &self.nodes[index].blockchain)
+    }
+    
+    /// Get the chain height of every node
+    pub fn heights(&self) -> BTreeMap<String, u64> {
+        self.nodes
+            .iter()
+            .map(|node| (node.address.clone(), node.blockchain.get_height()))
+            .collect()
+    }
+    
+    /// Check no two nodes finalized different blocks at the same height
+    pub fn check_safety(&self) -> Result<(), Box<dyn std::error::Error>> {
+        for (i, a) in self.nodes.iter().enumerate() {
+            for b in &self.nodes[i + 1..] {
+                let finalized = self.finalized_height(a).min(self.finalized_height(b));
+                
+                for height in 0..=finalized {
+                    let hash_a = a.blockchain.get_block_by_height(height).map(|block| block.hash());
+                    let hash_b = b.blockchain.get_block_by_height(height).map(|block| block.hash());
+                    
+                    if hash_a != hash_b {
+                        return Err(format!(
+                            "Safety violation at height {}: {} finalized {:?}, {} finalized {:?}",
+                            height, a.address, hash_a, b.address, hash_b
+                        ).into());
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Check every node that is not crashed finalized at least a height
+    pub fn check_liveness(&self, min_height: u64) -> Result<(), Box<dyn std::error::Error>> {
+        for node in &self.nodes {
+            if node.behavior == NodeBehavior::Crashed {
+                continue;
+            }
+            
+            let finalized = self.finalized_height(node);
+            if finalized < min_height {
+                return Err(format!(
+                    "Liveness violation: {} finalized height {} at {} ms, expected at least {}",
+                    node.address, finalized, self.now_ms, min_height
+                ).into());
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the highest block of a node with enough blocks built on top of it
+    fn finalized_height(&self, node: &SimNode) -> u64 {
+        node.blockchain.get_height().saturating_sub(self.config.finality_depth)
+    }
+    
+    /// Move virtual time forward
+    fn set_now(&mut self, now_ms: u64) {
+        self.now_ms = now_ms;
+        self.clock.store(now_ms, Ordering::SeqCst);
+    }
+    
+    /// Get the index of a node by address
+    fn index_of(&self, address: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.address == address)
+    }
+    
+    /// Queue an event
+    fn schedule(&mut self, time: u64, event: SimEvent) {
+        self.events.insert((time, self.next_event), event);
+        self.next_event += 1;
+    }
+    
+    /// Send a message through the simulated network
+    fn send(&mut self, from: usize, to: usize, message: SimMessage) {
+        self.stats.sent += 1;
+        
+        let partitioned = self.partitions.iter().any(|partition| {
+            (partition.from_ms..partition.until_ms).contains(&self.now_ms)
+                && matches!(
+                    (partition.groups.get(&from), partition.groups.get(&to)),
+                    (Some(a), Some(b)) if a != b
+                )
+        });
+        if partitioned {
+            self.stats.partitioned += 1;
+            return;
+        }
+        
+        if self.rng.gen::<u64>() % 10000 < u64::from(self.config.network.drop_bps) {
+            self.stats.dropped += 1;
+            return;
+        }
+        
+        let network = &self.config.network;
+        let spread = network.max_latency_ms - network.min_latency_ms;
+        let latency = network.min_latency_ms + self.rng.gen::<u64>() % (spread + 1);
+        
+        self.schedule(self.now_ms + latency, SimEvent::Deliver { from, to, message });
+    }
+    
+    /// Send a message to every other node
+    fn broadcast(&mut self, from: usize, message: SimMessage) {
+        for to in 0..self.nodes.len() {
+            if to != from {
+                self.send(from, to, message.clone());
+            }
+        }
+    }
+    
+    /// Let a node try to produce the next block
+    fn produce(&mut self, index: usize) {
+        let node = &self.nodes[index];
+        if node.behavior == NodeBehavior::Crashed {
+            return;
+        }
+        
+        // Engines refuse to produce outside the node's slots
+        let mut block = match node.consensus.create_block(&node.blockchain) {
+            Ok(block) => block,
+            Err(e) => {
+                debug!("{} did not produce at {} ms: {}", node.address, self.now_ms, e);
+                return;
+            }
+        };
+        
+        // Engines holding no validator key leave signing to the node
+        if block.header.producer.is_none() {
+            block.sign_as(&node.address, &node.keypair);
+        }
+        
+        if let Err(e) = node.blockchain.add_block(block.clone()) {
+            warn!("{} produced an invalid block: {}", node.address, e);
+            return;
+        }
+        
+        if node.behavior == NodeBehavior::Equivocating {
+            // A second block for the same slot, signed with the same key
+            let mut conflicting = block.clone();
+            conflicting.header.timestamp += 1;
+            conflicting.sign_as(&node.address, &node.keypair);
+            
+            for to in 0..self.nodes.len() {
+                if to != index {
+                    let version = if to % 2 == 0 { &block } else { &conflicting };
+                    self.send(index, to, SimMessage::Block(Box::new(version.clone())));
+                }
+            }
+        } else {
+            self.broadcast(index, SimMessage::Block(Box::new(block)));
+        }
+    }
+    
+    /// Ask a random peer for its blocks past the node's finalized height, so missed blocks and branches are fetched
+    fn poll_peer(&mut self, index: usize) {
+        if self.nodes.len() < 2 || self.nodes[index].behavior == NodeBehavior::Crashed {
+            return;
+        }
+        
+        let offset = 1 + (self.rng.gen::<u64>() % (self.nodes.len() as u64 - 1)) as usize;
+        let peer = (index + offset) % self.nodes.len();
+        let finalized = self.finalized_height(&self.nodes[index]);
+        
+        self.send(index, peer, SimMessage::GetBlocks(finalized + 1));
+    }
+    
+    /// Handle a message arriving at a node
+    fn receive(&mut self, from: usize, to: usize, message: SimMessage) {
+        if self.nodes[to].behavior == NodeBehavior::Crashed {
+            return;
+        }
+        
+        match message {
+            SimMessage::Block(block) => self.receive_block(from, to, *block, true),
+            SimMessage::GetBlocks(from_height) => {
+                let blockchain = &self.nodes[to].blockchain;
+                let until = blockchain.get_height().min(from_height + MAX_BLOCKS_PER_REPLY - 1);
+                let blocks: Vec<Block> = (from_height..=until)
+                    .filter_map(|height| blockchain.get_block_by_height(height))
+                    .collect();
+                
+                if !blocks.is_empty() {
+                    self.send(to, from, SimMessage::Blocks(blocks));
+                }
+            }
+            SimMessage::Blocks(blocks) => {
+                // Blocks that still don't connect were sent from a fork, asking again would loop
+                for block in blocks {
+                    self.receive_block(from, to, block, false);
+                }
+            }
+        }
+    }
+    
+    /// Take in a block received by a node, switching to the best branch and relaying the blocks it adopts
+    fn receive_block(&mut self, from: usize, to: usize, block: Block, request_missing: bool) {
+        let finalized = self.finalized_height(&self.nodes[to]);
+        let node = &mut self.nodes[to];
+        
+        // Conflicting blocks incriminate their producer even if they can't be imported
+        node.blockchain.observe_block(&block);
+        
+        let hash = block.hash();
+        if block.header.height <= finalized
+            || node.rejected.contains(&hash)
+            || node.known.contains_key(&hash)
+            || node.blockchain.get_block_by_hash(&hash).is_some()
+        {
+            return;
+        }
+        
+        let parent = &block.header.prev_hash;
+        let connects = node.known.contains_key(parent) || node.blockchain.get_block_by_hash(parent).is_some();
+        node.known.insert(hash, block);
+        
+        if !connects {
+            if request_missing {
+                self.send(to, from, SimMessage::GetBlocks(finalized + 1));
+            }
+            return;
+        }
+        
+        for block in self.choose_fork(to) {
+            self.broadcast(to, SimMessage::Block(Box::new(block)));
+        }
+    }
+    
+    /// Switch a node to the best branch it knows of, returning the blocks it adopted
+    ///
+    /// The longest chain wins, and among chains of the same length the one with the
+    /// lowest tip hash, so nodes knowing the same blocks agree. Branches forking off
+    /// below the finalized height are never adopted.
+    fn choose_fork(&mut self, index: usize) -> Vec<Block> {
+        let finalized = self.finalized_height(&self.nodes[index]);
+        let mut adopted = Vec::new();
+        
+        // Each round either moves the tip forward or forgets a block, so this ends
+        loop {
+            let node = &mut self.nodes[index];
+            let current = match node.blockchain.get_latest_block() {
+                Some(tip) => rank(&tip),
+                None => break,
+            };
+            
+            let mut candidates: Vec<&Block> = node.known.values().filter(|block| rank(block) > current).collect();
+            candidates.sort_by_key(|block| Reverse(rank(block)));
+            let (fork_height, branch) = match candidates.into_iter().find_map(|candidate| branch_of(node, candidate)) {
+                Some(branch) => branch,
+                None => break,
+            };
+            
+            if fork_height < finalized {
+                for block in &branch {
+                    node.known.remove(&block.hash());
+                }
+                continue;
+            }
+            
+            match self.adopt(index, fork_height, branch, current) {
+                Ok(blocks) => adopted.extend(blocks),
+                Err(e) => warn!("{} could not switch branches: {}", self.nodes[index].address, e),
+            }
+        }
+        
+        // Blocks at or below the finalized height can no longer be adopted
+        let finalized = self.finalized_height(&self.nodes[index]);
+        self.nodes[index].known.retain(|_, block| block.header.height > finalized);
+        
+        adopted
+    }
+    
+    /// Import a branch forking off a node's chain at a height, keeping it if it beats the current tip
+    ///
+    /// Branches extending the tip are imported in place. Others are imported into a new
+    /// copy of the chain, replacing the current one only if they end up ahead of it.
+    /// Blocks failing validation are forgotten, along with the rest of the branch.
+    fn adopt(&mut self, index: usize, fork_height: u64, branch: Vec<Block>, current: (u64, Reverse<String>)) -> Result<Vec<Block>, Box<dyn std::error::Error>> {
+        let node = &mut self.nodes[index];
+        for block in &branch {
+            node.known.remove(&block.hash());
+        }
+        
+        if fork_height == current.0 {
+            return Ok(import(&*node.consensus, &mut node.rejected, &node.blockchain, branch));
+        }
+        
+        let dir = node.dir.join(node.chains.to_string());
+        node.chains += 1;
+        let blockchain = Self::open_chain(&dir, &self.config.spec, &self.clock)?;
+        for height in 1..=fork_height {
+            let block = node.blockchain.get_block_by_height(height).ok_or("Chain is missing a block")?;
+            blockchain.add_block(block)?;
+        }
+        
+        let imported = import(&*node.consensus, &mut node.rejected, &blockchain, branch);
+        let tip = blockchain.get_latest_block().ok_or("Chain has no blocks")?;
+        if imported.is_empty() || rank(&tip) <= current {
+            drop(blockchain);
+            let _ = std::fs::remove_dir_all(&dir);
+            return Ok(Vec::new());
+        }
+        
+        // Keep the evidence and the abandoned blocks, in case their branch overtakes again
+        for evidence in node.blockchain.get_pending_evidence() {
+            let _ = blockchain.add_evidence(evidence);
+        }
+        for height in fork_height + 1..=current.0 {
+            if let Some(block) = node.blockchain.get_block_by_height(height) {
+                node.known.insert(block.hash(), block);
+            }
+        }
+        
+        debug!("{} switched to a branch forking at height {}", node.address, fork_height);
+        let previous = std::mem::replace(&mut node.blockchain, blockchain);
+        drop(previous);
+        let _ = std::fs::remove_dir_all(node.dir.join((node.chains - 2).to_string()));
+        
+        Ok(imported)
+    }
+}
+
+/// Get the key blocks are ranked by when choosing a tip, higher being better
+fn rank(block: &Block) -> (u64, Reverse<String>) {
+    (block.header.height, Reverse(block.hash()))
+}
+
+/// Get the blocks leading from a node's chain to a known block, with the height they fork off at
+///
+/// Returns nothing if some block of the branch is missing.
+fn branch_of(node: &SimNode, tip: &Block) -> Option<(u64, Vec<Block>)> {
+    let mut branch = vec![tip.clone()];
+    loop {
+        let parent = &branch.last()?.header.prev_hash;
+        if let Some(block) = node.blockchain.get_block_by_hash(parent) {
+            branch.reverse();
+            return Some((block.header.height, branch));
+        }
+        branch.push(node.known.get(parent)?.clone());
+    }
+}
+
+/// Validate and add the blocks of a branch to a chain in order, returning those added
+///
+/// Stops at the first invalid block, remembering it as rejected.
+fn import(consensus: &dyn Consensus, rejected: &mut BTreeSet<String>, blockchain: &Blockchain, branch: Vec<Block>) -> Vec<Block> {
+    let mut imported = Vec::new();
+    for block in branch {
+        let valid = consensus
+            .validate_block(&block, blockchain)
+            .and_then(|_| blockchain.add_block(block.clone()));
+        
+        if let Err(e) = valid {
+            debug!("Rejected block {}: {}", block.header.height, e);
+            rejected.insert(block.hash());
+            break;
+        }
+        imported.push(block);
+    }
+    
+    imported
+}
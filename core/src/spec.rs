@@ -0,0 +1,248 @@
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::fs;
+use std::path::Path;
+
+use crate::crypto::{self, KeyPair};
+use crate::model_registry::{ModelRegistry, ModelSubmission};
+use crate::rewards::MonetaryPolicy;
+
+/// Account funded in the genesis state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisAccount {
+    /// Account address
+    pub address: String,
+    /// Initial balance
+    pub balance: u64,
+    /// Ed25519 public key signing the account's transactions (hex), unless the address is one
+    #[serde(default)]
+    pub public_key: Option<String>,
+}
+
+/// Accounts funded in the genesis of development chains
+pub const DEV_ACCOUNTS: [&str; 6] = ["alice", "bob", "charlie", "dave", "eve", "ferdie"];
+
+/// Balance of each development account
+pub const DEV_ACCOUNT_BALANCE: u64 = 1_000_000_000_000;
+
+/// Key of a development account, derived from its name so anyone can sign as it
+pub fn dev_keypair(name: &str) -> KeyPair {
+    let seed: [u8; 32] = Sha256::digest(name.as_bytes()).into();
+    KeyPair::from_seed(&seed).expect("any 32 bytes are an Ed25519 secret key")
+}
+
+/// Checks the hybrid consensus engine applies to every block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConsensusComponents {
+    /// Blocks must be produced and signed by the validator scheduled for their slot
+    pub scheduled_producer: bool,
+    /// Blocks must carry a valid AI proof
+    pub ai_proof: bool,
+    /// Blocks must carry a quantum-resistant signature of the producer
+    pub quantum_signature: bool,
+}
+
+impl Default for ConsensusComponents {
+    fn default() -> Self {
+        ConsensusComponents {
+            scheduled_producer: true,
+            ai_proof: true,
+            // Quantum-resistant signing is not available yet
+            quantum_signature: false,
+        }
+    }
+}
+
+/// Chain specification for HyperNova Chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainSpec {
+    /// Chain identifier
+    pub chain_id: String,
+    /// Timestamp of the genesis block, so every node derives the same genesis hash
+    #[serde(default)]
+    pub genesis_timestamp: u64,
+    /// Number of blocks in an epoch
+    pub epoch_length: u64,
+    /// Number of blocks undelegated tokens stay locked before release
+    pub unbonding_period: u64,
+    /// Maximum number of validators elected per epoch
+    pub max_validators: usize,
+    /// Minimum total stake a validator needs to be elected
+    pub min_validator_stake: u64,
+    /// Minimum stake a validator must bond to itself
+    pub min_self_stake: u64,
+    /// Share of stake slashed for double-signing, in basis points
+    pub double_sign_slash_bps: u64,
+    /// Share of stake slashed for prolonged downtime, in basis points
+    pub downtime_slash_bps: u64,
+    /// Share of scheduled slots a validator must produce per epoch, in basis points
+    pub liveness_threshold_bps: u64,
+    /// Number of blocks a jailed validator must wait before unjailing
+    pub jail_period: u64,
+    /// Minimum stake bonded with an AI model submission
+    #[serde(default = "default_min_model_stake")]
+    pub min_model_stake: u64,
+    /// Number of blocks validators can vote on a governance proposal
+    #[serde(default = "default_governance_voting_period")]
+    pub governance_voting_period: u64,
+    /// Share of validator stake that must approve a proposal, in basis points
+    #[serde(default = "default_governance_threshold_bps")]
+    pub governance_threshold_bps: u64,
+    /// Number of validators drawn by the randomness beacon to verify each block's AI proof
+    #[serde(default = "default_poai_committee_size")]
+    pub poai_committee_size: usize,
+    /// Seconds the scheduled producer has before the next validator in the set may produce instead, zero to never hand over
+    #[serde(default = "default_slot_duration")]
+    pub slot_duration: u64,
+    /// Checks applied by the hybrid consensus engine
+    #[serde(default)]
+    pub consensus: ConsensusComponents,
+    /// Block rewards, issuance caps and fee distribution
+    #[serde(default)]
+    pub monetary_policy: MonetaryPolicy,
+    /// Accounts funded at genesis
+    pub genesis_accounts: Vec<GenesisAccount>,
+    /// Models approved for Proof of AI at genesis
+    #[serde(default)]
+    pub genesis_models: Vec<ModelSubmission>,
+    /// Nodes dialed to join the network, as host:port
+    #[serde(default)]
+    pub bootnodes: Vec<String>,
+}
+
+impl Default for ChainSpec {
+    fn default() -> Self {
+        ChainSpec {
+            chain_id: "hypernova-mainnet".to_string(),
+            genesis_timestamp: 0,
+            epoch_length: 100,
+            unbonding_period: 1000,
+            max_validators: 21,
+            min_validator_stake: 1000,
+            min_self_stake: 10000,
+            double_sign_slash_bps: 500,
+            downtime_slash_bps: 10,
+            liveness_threshold_bps: 5000,
+            jail_period: 100,
+            min_model_stake: 1000,
+            governance_voting_period: 1000,
+            governance_threshold_bps: 6667,
+            poai_committee_size: 5,
+            slot_duration: 10,
+            consensus: ConsensusComponents::default(),
+            monetary_policy: MonetaryPolicy::default(),
+            genesis_accounts: Vec::new(),
+            genesis_models: Vec::new(),
+            bootnodes: Vec::new(),
+        }
+    }
+}
+
+/// Default of `min_model_stake` for specifications written before it existed
+fn default_min_model_stake() -> u64 {
+    ChainSpec::default().min_model_stake
+}
+
+/// Default of `governance_voting_period` for specifications written before it existed
+fn default_governance_voting_period() -> u64 {
+    ChainSpec::default().governance_voting_period
+}
+
+/// Default of `governance_threshold_bps` for specifications written before it existed
+fn default_governance_threshold_bps() -> u64 {
+    ChainSpec::default().governance_threshold_bps
+}
+
+/// Default of `poai_committee_size` for specifications written before it existed
+fn default_poai_committee_size() -> usize {
+    ChainSpec::default().poai_committee_size
+}
+
+/// Default of `slot_duration` for specifications written before it existed
+fn default_slot_duration() -> u64 {
+    ChainSpec::default().slot_duration
+}
+
+impl ChainSpec {
+    /// Single-node development chain with pre-funded accounts and no validators
+    pub fn dev() -> Self {
+        ChainSpec {
+            chain_id: "hypernova-dev".to_string(),
+            liveness_threshold_bps: 0,
+            consensus: ConsensusComponents {
+                scheduled_producer: false,
+                ai_proof: false,
+                quantum_signature: false,
+            },
+            genesis_accounts: DEV_ACCOUNTS
+                .iter()
+                .map(|address| GenesisAccount {
+                    address: address.to_string(),
+                    balance: DEV_ACCOUNT_BALANCE,
+                    public_key: Some(dev_keypair(address).public_key_hex()),
+                })
+                .collect(),
+            ..ChainSpec::default()
+        }
+    }
+    
+    /// Load a chain specification from a JSON file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let json = fs::read_to_string(path)?;
+        let spec: ChainSpec = serde_json::from_str(&json)?;
+        spec.validate()?;
+
+        Ok(spec)
+    }
+    
+    /// Check the specification describes a chain that can run
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.epoch_length == 0 {
+            return Err("Epoch length must be greater than zero".into());
+        }
+
+        if self.double_sign_slash_bps > 10000 || self.downtime_slash_bps > 10000 {
+            return Err("Slash fractions cannot exceed 10000 basis points".into());
+        }
+        
+        if self.liveness_threshold_bps > 10000 {
+            return Err("Liveness threshold cannot exceed 10000 basis points".into());
+        }
+        
+        if self.governance_threshold_bps > 10000 {
+            return Err("Governance threshold cannot exceed 10000 basis points".into());
+        }
+        
+        self.monetary_policy.validate()?;
+        
+        for account in &self.genesis_accounts {
+            if account.public_key.as_deref().is_some_and(|public_key| !crypto::is_public_key(public_key)) {
+                return Err(format!("Genesis account {} has an invalid public key", account.address).into());
+            }
+        }
+        
+        ModelRegistry::genesis(&self.genesis_models)?;
+        
+        Ok(())
+    }
+
+    /// Get the epoch a block height belongs to
+    pub fn epoch_of(&self, height: u64) -> u64 {
+        height / self.epoch_length
+    }
+
+    /// Check whether a block height is the first block of an epoch
+    pub fn is_epoch_boundary(&self, height: u64) -> bool {
+        height.is_multiple_of(self.epoch_length)
+    }
+
+    /// Get how many producers were passed over for a block made at a timestamp, from its parent's timestamp
+    pub fn slot_round(&self, parent_timestamp: u64, timestamp: u64) -> u64 {
+        if self.slot_duration == 0 {
+            return 0;
+        }
+        
+        timestamp.saturating_sub(parent_timestamp) / self.slot_duration
+    }
+}
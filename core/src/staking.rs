@@ -0,0 +1,473 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+use crate::epoch::ValidatorSetEntry;
+
+/// Lifecycle status of a registered validator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidatorStatus {
+    /// Eligible for election
+    Active,
+    /// Temporarily excluded from election
+    Jailed,
+    /// Self-stake fully unbonded
+    Inactive,
+    /// Permanently excluded after double-signing
+    Tombstoned,
+}
+
+/// Misbehaviour a validator can be slashed for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SlashReason {
+    /// Signed two different blocks at the same height
+    DoubleSign,
+    /// Missed too many block production slots
+    Downtime,
+}
+
+/// Payload of a validator registration transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorRegistration {
+    /// Ed25519 public key used to sign blocks (hex)
+    pub public_key: String,
+    /// Quantum-resistant public key (hex)
+    pub quantum_public_key: Option<String>,
+    /// Commission taken from delegators' rewards, in basis points
+    pub commission_bps: u16,
+}
+
+impl ValidatorRegistration {
+    /// Encode the registration as transaction data
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+    
+    /// Decode a registration from transaction data
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let registration: ValidatorRegistration = serde_json::from_slice(data)?;
+        
+        if hex::decode(&registration.public_key).map_or(true, |key| key.len() != 32) {
+            return Err("Validator public key must be a 32-byte hex string".into());
+        }
+        
+        if let Some(key) = &registration.quantum_public_key {
+            hex::decode(key).map_err(|_| "Quantum public key must be a hex string")?;
+        }
+        
+        if registration.commission_bps > 10000 {
+            return Err("Commission cannot exceed 10000 basis points".into());
+        }
+        
+        Ok(registration)
+    }
+}
+
+/// Registered validator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorRecord {
+    /// Validator's address
+    pub address: String,
+    /// Ed25519 public key used to sign blocks (hex)
+    pub public_key: String,
+    /// Quantum-resistant public key (hex)
+    pub quantum_public_key: Option<String>,
+    /// Commission taken from delegators' rewards, in basis points
+    pub commission_bps: u16,
+    /// Current status
+    pub status: ValidatorStatus,
+    /// Height the validator registered at
+    pub registered_height: u64,
+    /// Total stake slashed from this validator and its delegators
+    pub slashed: u64,
+    /// Height from which a jailed validator may unjail
+    #[serde(default)]
+    pub jailed_until: u64,
+}
+
+/// Tokens bonded by a delegator to a validator candidate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Delegation {
+    /// Delegator's address
+    pub delegator: String,
+    /// Candidate the tokens are bonded to
+    pub candidate: String,
+    /// Bonded amount
+    pub amount: u64,
+}
+
+/// Undelegated tokens waiting out the unbonding period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnbondingEntry {
+    /// Delegator's address
+    pub delegator: String,
+    /// Candidate the tokens were bonded to
+    pub candidate: String,
+    /// Amount being unbonded
+    pub amount: u64,
+    /// Height at which the tokens are returned to the delegator
+    pub release_height: u64,
+}
+
+/// All staking positions held by a single delegator
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DelegatorPositions {
+    /// Active delegations
+    pub delegations: Vec<Delegation>,
+    /// Delegations being unbonded
+    pub unbonding: Vec<UnbondingEntry>,
+}
+
+/// Delegation state of the chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StakingState {
+    /// Bonded amounts by delegator, then by candidate
+    delegations: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Undelegated tokens not yet released
+    unbonding: Vec<UnbondingEntry>,
+    /// Candidate stakes captured for the last validator election
+    epoch_stakes: BTreeMap<String, u64>,
+    /// Registered validators by address
+    validators: BTreeMap<String, ValidatorRecord>,
+    /// Total stake removed by slashing
+    total_slashed: u64,
+}
+
+impl StakingState {
+    /// Register a validator and bond its self-stake
+    pub fn register_validator(
+        &mut self,
+        address: &str,
+        registration: ValidatorRegistration,
+        self_stake: u64,
+        min_self_stake: u64,
+        height: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(record) = self.validators.get(address) {
+            if record.status != ValidatorStatus::Inactive {
+                return Err(format!("Validator {} is already registered", address).into());
+            }
+        }
+        
+        if self.self_stake(address).saturating_add(self_stake) < min_self_stake {
+            return Err(format!(
+                "Self-stake of {} is below the minimum of {}",
+                self_stake, min_self_stake
+            ).into());
+        }
+        
+        self.validators.insert(address.to_string(), ValidatorRecord {
+            address: address.to_string(),
+            public_key: registration.public_key,
+            quantum_public_key: registration.quantum_public_key,
+            commission_bps: registration.commission_bps,
+            status: ValidatorStatus::Active,
+            registered_height: height,
+            slashed: 0,
+            jailed_until: 0,
+        });
+        
+        if self_stake > 0 {
+            self.delegate(address, address, self_stake)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Get a registered validator
+    pub fn validator(&self, address: &str) -> Option<&ValidatorRecord> {
+        self.validators.get(address)
+    }
+    
+    /// Iterate over all registered validators
+    pub fn validators(&self) -> impl Iterator<Item = &ValidatorRecord> {
+        self.validators.values()
+    }
+    
+    /// Get the stake a validator has bonded to itself
+    pub fn self_stake(&self, address: &str) -> u64 {
+        self.delegations
+            .get(address)
+            .and_then(|positions| positions.get(address))
+            .copied()
+            .unwrap_or(0)
+    }
+    
+    /// Check a validator's self-stake after it unbonded part of it
+    pub fn check_self_stake(&mut self, address: &str, min_self_stake: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let remaining = self.self_stake(address);
+        
+        let record = match self.validators.get_mut(address) {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+        
+        if remaining == 0 {
+            // Unbonding everything is how a validator leaves the set
+            if record.status == ValidatorStatus::Active || record.status == ValidatorStatus::Jailed {
+                record.status = ValidatorStatus::Inactive;
+            }
+        } else if remaining < min_self_stake {
+            return Err(format!(
+                "Remaining self-stake of {} is below the minimum of {}",
+                remaining, min_self_stake
+            ).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Slash a validator and everyone delegating to it, returning the amount removed
+    pub fn slash(
+        &mut self,
+        address: &str,
+        reason: SlashReason,
+        fraction_bps: u64,
+        jailed_until: u64,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        if !self.validators.contains_key(address) {
+            return Err(format!("Validator not found: {}", address).into());
+        }
+        
+        let cut = |amount: u64| (amount as u128 * fraction_bps as u128 / 10000) as u64;
+        let mut slashed = 0;
+        
+        // Bonded stake
+        for positions in self.delegations.values_mut() {
+            if let Some(amount) = positions.get_mut(address) {
+                let penalty = cut(*amount);
+                *amount -= penalty;
+                slashed += penalty;
+            }
+        }
+        
+        // Stake that was still unbonding when the validator misbehaved
+        for entry in self.unbonding.iter_mut().filter(|entry| entry.candidate == address) {
+            let penalty = cut(entry.amount);
+            entry.amount -= penalty;
+            slashed += penalty;
+        }
+        
+        let record = self.validators.get_mut(address).unwrap();
+        record.slashed += slashed;
+        record.status = match reason {
+            SlashReason::DoubleSign => ValidatorStatus::Tombstoned,
+            SlashReason::Downtime if record.status == ValidatorStatus::Tombstoned => ValidatorStatus::Tombstoned,
+            SlashReason::Downtime => {
+                record.jailed_until = jailed_until;
+                ValidatorStatus::Jailed
+            }
+        };
+        
+        self.total_slashed += slashed;
+        
+        Ok(slashed)
+    }
+    
+    /// Return a jailed validator to the active set once its jail period is over
+    pub fn unjail(
+        &mut self,
+        address: &str,
+        height: u64,
+        min_self_stake: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let self_stake = self.self_stake(address);
+        
+        let record = self.validators
+            .get_mut(address)
+            .ok_or_else(|| format!("Validator not found: {}", address))?;
+        
+        if record.status != ValidatorStatus::Jailed {
+            return Err(format!("Validator {} is not jailed", address).into());
+        }
+        
+        if height < record.jailed_until {
+            return Err(format!(
+                "Validator {} is jailed until height {}",
+                address, record.jailed_until
+            ).into());
+        }
+        
+        if self_stake < min_self_stake {
+            return Err(format!(
+                "Self-stake of {} is below the minimum of {}",
+                self_stake, min_self_stake
+            ).into());
+        }
+        
+        record.status = ValidatorStatus::Active;
+        
+        Ok(())
+    }
+    
+    /// Elect validators from the captured stakes, ordered by stake
+    pub fn elect(&self, max_validators: usize, min_stake: u64) -> Vec<ValidatorSetEntry> {
+        // Only active registered validators are candidates
+        let mut candidates: Vec<ValidatorSetEntry> = self.validators
+            .values()
+            .filter(|record| record.status == ValidatorStatus::Active)
+            .map(|record| ValidatorSetEntry {
+                address: record.address.clone(),
+                public_key: record.public_key.clone(),
+                stake: self.epoch_stakes.get(&record.address).copied().unwrap_or(0),
+            })
+            .filter(|entry| entry.stake >= min_stake)
+            .collect();
+        
+        // Sort by stake, breaking ties by address so every node elects the same set
+        candidates.sort_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.address.cmp(&b.address)));
+        candidates.truncate(max_validators);
+        
+        candidates
+    }
+    
+    /// Get the total stake removed by slashing
+    pub fn total_slashed(&self) -> u64 {
+        self.total_slashed
+    }
+    
+    /// Bond tokens from a delegator to a candidate
+    pub fn delegate(
+        &mut self,
+        delegator: &str,
+        candidate: &str,
+        amount: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if amount == 0 {
+            return Err("Delegation amount must be greater than zero".into());
+        }
+
+        match self.validators.get(candidate).map(|record| record.status) {
+            None => return Err(format!("{} is not a registered validator", candidate).into()),
+            Some(ValidatorStatus::Tombstoned) => {
+                return Err(format!("Validator {} has been tombstoned", candidate).into())
+            }
+            Some(_) => {}
+        }
+        
+        let bonded = self.delegations
+            .entry(delegator.to_string())
+            .or_default()
+            .entry(candidate.to_string())
+            .or_insert(0);
+
+        *bonded = bonded.checked_add(amount).ok_or("Delegation amount overflow")?;
+
+        Ok(())
+    }
+
+    /// Start unbonding tokens a delegator has bonded to a candidate
+    pub fn undelegate(
+        &mut self,
+        delegator: &str,
+        candidate: &str,
+        amount: u64,
+        release_height: u64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if amount == 0 {
+            return Err("Undelegation amount must be greater than zero".into());
+        }
+
+        let positions = self.delegations
+            .get_mut(delegator)
+            .ok_or_else(|| format!("No delegations found for {}", delegator))?;
+
+        let bonded = positions
+            .get_mut(candidate)
+            .ok_or_else(|| format!("{} has no delegation to {}", delegator, candidate))?;
+
+        if *bonded < amount {
+            return Err(format!(
+                "Cannot undelegate {}: only {} bonded to {}",
+                amount, bonded, candidate
+            ).into());
+        }
+
+        *bonded -= amount;
+
+        if *bonded == 0 {
+            positions.remove(candidate);
+        }
+        if positions.is_empty() {
+            self.delegations.remove(delegator);
+        }
+
+        self.unbonding.push(UnbondingEntry {
+            delegator: delegator.to_string(),
+            candidate: candidate.to_string(),
+            amount,
+            release_height,
+        });
+
+        Ok(())
+    }
+
+    /// Remove and return all unbonding entries released at or before a height
+    pub fn release_matured(&mut self, height: u64) -> Vec<UnbondingEntry> {
+        let (released, pending) = self.unbonding
+            .drain(..)
+            .partition(|entry| entry.release_height <= height);
+
+        self.unbonding = pending;
+
+        released
+    }
+
+    /// Get the total stake bonded to each candidate
+    pub fn candidate_stakes(&self) -> BTreeMap<String, u64> {
+        let mut stakes = BTreeMap::new();
+
+        for positions in self.delegations.values() {
+            for (candidate, amount) in positions {
+                *stakes.entry(candidate.clone()).or_insert(0) += amount;
+            }
+        }
+
+        stakes
+    }
+
+    /// Get the delegators bonded to a candidate and their amounts
+    pub fn delegators_of(&self, candidate: &str) -> Vec<(String, u64)> {
+        self.delegations
+            .iter()
+            .filter_map(|(delegator, positions)| positions.get(candidate).map(|amount| (delegator.clone(), *amount)))
+            .collect()
+    }
+    
+    /// Capture the current candidate stakes for the next validator election
+    pub fn snapshot_epoch(&mut self) {
+        self.epoch_stakes = self.candidate_stakes();
+    }
+
+    /// Get the candidate stakes captured for the last validator election
+    pub fn epoch_stakes(&self) -> &BTreeMap<String, u64> {
+        &self.epoch_stakes
+    }
+
+    /// Get all positions held by a delegator
+    pub fn positions(&self, delegator: &str) -> DelegatorPositions {
+        let delegations = self.delegations
+            .get(delegator)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|(candidate, &amount)| Delegation {
+                        delegator: delegator.to_string(),
+                        candidate: candidate.clone(),
+                        amount,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let unbonding = self.unbonding
+            .iter()
+            .filter(|entry| entry.delegator == delegator)
+            .cloned()
+            .collect();
+
+        DelegatorPositions {
+            delegations,
+            unbonding,
+        }
+    }
+}
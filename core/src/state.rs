@@ -0,0 +1,630 @@
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet};
+use log::info;
+
+use crate::beacon::{self, RandomBeacon};
+use crate::block::Block;
+use crate::crypto;
+use crate::epoch::{self, EpochState, ValidatorSetEntry};
+use crate::evidence::DoubleSignEvidence;
+use crate::governance::{GovernanceState, ProposalAction, ProposalStatus, Vote};
+use crate::ai_proof::AIProof;
+use crate::model_registry::{ModelRegistry, ModelStatus, ModelSubmission, GENESIS_OWNER};
+use crate::rewards::RewardTotals;
+use crate::spec::ChainSpec;
+use crate::staking::{SlashReason, StakingState, ValidatorRegistration, ValidatorStatus};
+use crate::transaction::{Transaction, TransactionType};
+use crate::uptime::UptimeTracker;
+
+/// Account state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Account {
+    /// Spendable balance
+    pub balance: u64,
+    /// Number of transactions applied from this account
+    pub nonce: u64,
+    /// Ed25519 public key signing the account's transactions (hex), unless the address is one
+    pub public_key: Option<String>,
+}
+
+/// World state of HyperNova Chain, derived by applying blocks in order
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChainState {
+    /// Height of the last applied block
+    pub height: u64,
+    /// Timestamp of the last applied block
+    pub timestamp: u64,
+    /// Accounts by address
+    accounts: BTreeMap<String, Account>,
+    /// Delegation state
+    staking: StakingState,
+    /// Infractions that have already been punished
+    processed_evidence: BTreeSet<String>,
+    /// Produced and missed slots of scheduled validators
+    uptime: UptimeTracker,
+    /// Validator sets of the current, next and past epochs
+    epochs: EpochState,
+    /// Models submitted for Proof of AI
+    models: ModelRegistry,
+    /// Governance proposals and votes
+    governance: GovernanceState,
+    /// Randomness beacon mixed by block producers
+    beacon: RandomBeacon,
+    /// Issuance and fee distribution totals
+    rewards: RewardTotals,
+}
+
+impl ChainState {
+    /// Create the genesis state described by a chain specification, failing on invalid genesis models
+    pub fn genesis(spec: &ChainSpec) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut state = ChainState {
+            timestamp: spec.genesis_timestamp,
+            beacon: RandomBeacon::genesis(&spec.chain_id),
+            models: ModelRegistry::genesis(&spec.genesis_models)?,
+            ..ChainState::default()
+        };
+
+        for account in &spec.genesis_accounts {
+            let entry = state.accounts.entry(account.address.clone()).or_default();
+            entry.balance = entry.balance
+                .checked_add(account.balance)
+                .ok_or_else(|| format!("Genesis balance overflow for {}", account.address))?;
+            if account.public_key.is_some() {
+                entry.public_key = account.public_key.clone();
+            }
+            state.rewards.genesis_supply = state.rewards.genesis_supply
+                .checked_add(account.balance)
+                .ok_or("Genesis supply overflow")?;
+        }
+
+        state.staking.snapshot_epoch();
+        let mut validators = state.staking.elect(spec.max_validators, spec.min_validator_stake);
+        beacon::shuffle(&mut validators, state.beacon.randomness());
+        state.epochs = EpochState::genesis(validators.clone());
+        
+        // With single-block epochs the genesis block also ends epoch 0
+        if spec.is_epoch_boundary(1) {
+            state.epochs.queue(validators);
+        }
+        
+        Ok(state)
+    }
+
+    /// Copy the state holding only some accounts, enough to score transactions between them
+    pub(crate) fn accounts_snapshot<'a, I: IntoIterator<Item = &'a String>>(&self, addresses: I) -> ChainState {
+        let accounts = addresses
+            .into_iter()
+            .filter_map(|address| self.accounts.get(address).map(|account| (address.clone(), account.clone())))
+            .collect();
+        
+        ChainState {
+            height: self.height,
+            accounts,
+            ..ChainState::default()
+        }
+    }
+    
+    /// Get an account, or an empty account if it doesn't exist
+    pub fn account(&self, address: &str) -> Account {
+        self.accounts.get(address).cloned().unwrap_or_default()
+    }
+
+    /// Get the balance of an account
+    pub fn balance(&self, address: &str) -> u64 {
+        self.accounts.get(address).map_or(0, |account| account.balance)
+    }
+
+    /// Get the key signing an account's transactions, the address itself if no other key was registered
+    pub fn account_key(&self, address: &str) -> Option<String> {
+        self.accounts
+            .get(address)
+            .and_then(|account| account.public_key.clone())
+            .or_else(|| crypto::is_public_key(address).then(|| address.to_string()))
+    }
+    
+    /// Check a transaction is signed with its sender's key
+    pub fn check_signature(&self, tx: &Transaction) -> Result<(), Box<dyn std::error::Error>> {
+        let public_key = self
+            .account_key(&tx.from)
+            .ok_or_else(|| format!("{} has no public key to sign transaction {}", tx.from, tx.hash()))?;
+        
+        if !tx.verify_signature(&public_key)? {
+            return Err(format!("Invalid signature on transaction {} from {}", tx.hash(), tx.from).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the delegation state
+    pub fn staking(&self) -> &StakingState {
+        &self.staking
+    }
+
+    /// Get the validator sets of the current, next and past epochs
+    pub fn epochs(&self) -> &EpochState {
+        &self.epochs
+    }
+    
+    /// Get the validators allowed to sign the block at a height
+    pub fn validator_set_at(&self, height: u64, spec: &ChainSpec) -> Option<Vec<ValidatorSetEntry>> {
+        self.epochs.set_for_epoch(spec.epoch_of(height))
+    }
+    
+    /// Get the validator scheduled to produce the block at a height in a round, if its set is known yet
+    pub fn scheduled_producer_at(&self, height: u64, round: u64, spec: &ChainSpec) -> Option<ValidatorSetEntry> {
+        let validators = self.validator_set_at(height, spec)?;
+        epoch::slot_leader(&validators, height, round).cloned()
+    }
+    
+    /// Get the validator set hash the header of the block at a height must carry
+    pub fn expected_validator_set_hash(&self, height: u64, spec: &ChainSpec) -> Option<String> {
+        if height > 0 && spec.is_epoch_boundary(height) {
+            self.epochs.next_set_hash()
+        } else {
+            None
+        }
+    }
+    
+    /// Get the models submitted for Proof of AI
+    pub fn models(&self) -> &ModelRegistry {
+        &self.models
+    }
+    
+    /// Get the governance proposals
+    pub fn governance(&self) -> &GovernanceState {
+        &self.governance
+    }
+    
+    /// Get the randomness beacon
+    pub fn beacon(&self) -> &RandomBeacon {
+        &self.beacon
+    }
+    
+    /// Get the issuance and fee distribution totals
+    pub fn rewards(&self) -> &RewardTotals {
+        &self.rewards
+    }
+    
+    /// Get the uptime statistics of scheduled validators
+    pub fn uptime(&self) -> &UptimeTracker {
+        &self.uptime
+    }
+    
+    /// Slash a validator for misbehaviour at a height, returning the amount removed
+    pub fn slash_validator(
+        &mut self,
+        address: &str,
+        reason: SlashReason,
+        height: u64,
+        spec: &ChainSpec,
+    ) -> Result<u64, Box<dyn std::error::Error>> {
+        let fraction_bps = match reason {
+            SlashReason::DoubleSign => spec.double_sign_slash_bps,
+            SlashReason::Downtime => spec.downtime_slash_bps,
+        };
+        
+        let slashed = self.staking.slash(address, reason, fraction_bps, height + spec.jail_period)?;
+        self.rewards.slashed = self.rewards.slashed.checked_add(slashed).ok_or("Slashed total overflow")?;
+        
+        info!("Slashed {} from validator {} for {:?}", slashed, address, reason);
+        
+        Ok(slashed)
+    }
+    
+    /// Check that evidence can be applied at a height without changing the state
+    pub fn check_evidence(
+        &self,
+        evidence: &DoubleSignEvidence,
+        height: u64,
+        spec: &ChainSpec,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        evidence.verify(&self.staking)?;
+        
+        if height.saturating_sub(evidence.height()) > spec.unbonding_period {
+            return Err(format!("Evidence from height {} is too old", evidence.height()).into());
+        }
+        
+        if self.processed_evidence.contains(&evidence.id()) {
+            return Err(format!("Evidence {} has already been processed", evidence.id()).into());
+        }
+        
+        if self.staking.validator(&evidence.validator).map(|record| record.status) == Some(ValidatorStatus::Tombstoned) {
+            return Err(format!("Validator {} is already tombstoned", evidence.validator).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Apply a block on top of this state
+    pub fn apply_block(&mut self, block: &Block, spec: &ChainSpec) -> Result<(), Box<dyn std::error::Error>> {
+        let height = block.header.height;
+
+        // The first block of an epoch commits to the validator set elected for it
+        let expected_hash = self.expected_validator_set_hash(height, spec);
+        if block.header.validator_set_hash != expected_hash {
+            return Err(format!(
+                "Invalid validator set hash at height {}: expected {:?}, got {:?}",
+                height, expected_hash, block.header.validator_set_hash
+            ).into());
+        }
+        
+        // Producers take over from each other as time passes, so time must not run backwards
+        if block.header.timestamp < self.timestamp {
+            return Err(format!(
+                "Block {} timestamp {} is before its parent's {}",
+                height, block.header.timestamp, self.timestamp
+            ).into());
+        }
+        
+        if height > 0 && spec.is_epoch_boundary(height) {
+            self.epochs.rotate(spec.epoch_of(height));
+        }
+        
+        self.apply_randomness(block)?;
+        
+        // Punish double-signing before any stake can move
+        for evidence in &block.evidence {
+            self.check_evidence(evidence, height, spec)?;
+            self.processed_evidence.insert(evidence.id());
+            self.slash_validator(&evidence.validator, SlashReason::DoubleSign, height, spec)?;
+        }
+        
+        // Record whether the scheduled validator produced this block, which only its signature proves,
+        // and a miss for every validator that let its turn pass before it
+        let round = spec.slot_round(self.timestamp, block.header.timestamp);
+        let producer = self.signed_producer(block).cloned();
+        let passed_over = round.min(self.epochs.current().len() as u64);
+        for skipped in 0..passed_over {
+            if let Some(scheduled) = self.epochs.scheduled_producer(height, skipped) {
+                let scheduled = scheduled.to_string();
+                if producer.as_deref() != Some(scheduled.as_str()) {
+                    self.uptime.record(&scheduled, None);
+                }
+            }
+        }
+        if let Some(scheduled) = self.epochs.scheduled_producer(height, round) {
+            let scheduled = scheduled.to_string();
+            self.uptime.record(&scheduled, producer.as_deref());
+        }
+        
+        // Return unbonded tokens whose unbonding period has elapsed
+        for entry in self.staking.release_matured(height) {
+            self.credit(&entry.delegator, entry.amount)?;
+        }
+
+        for tx in &block.transactions {
+            self.apply_transaction(tx, height, spec)?;
+        }
+
+        self.distribute_rewards(block, spec)?;
+        
+        self.governance.expire(height);
+        
+        // Models that were not approved in time return their stake and free their id and file
+        for model in self.models.expire(height) {
+            info!("Model {} expired without approval", model.model_id);
+            self.credit(&model.owner, model.stake)?;
+        }
+        
+        // Validator set changes only take effect once the epoch is over
+        if spec.is_epoch_boundary(height + 1) {
+            // Jail validators that missed too many slots in the epoch that is ending
+            self.enforce_liveness(spec.epoch_of(height), height, spec)?;
+            
+            // Capture the stakes and elect the validators for the next epoch
+            self.staking.snapshot_epoch();
+            // The producer schedule is the elected set in beacon order
+            let mut validators = self.staking.elect(spec.max_validators, spec.min_validator_stake);
+            beacon::shuffle(&mut validators, self.beacon.randomness());
+            self.epochs.queue(validators);
+        }
+
+        self.height = height;
+        self.timestamp = block.header.timestamp;
+
+        Ok(())
+    }
+
+    /// Mix the producer's RANDAO reveal into the beacon and check the output the header commits to
+    fn apply_randomness(&mut self, block: &Block) -> Result<(), Box<dyn std::error::Error>> {
+        let header = &block.header;
+        
+        if let Some(producer) = &header.producer {
+            // Only the producer itself may commit, otherwise anyone could lock it out with an unknown secret
+            if header.randao_commitment.is_some() {
+                let record = self.staking
+                    .validator(producer)
+                    .ok_or_else(|| format!("RANDAO commitment from unregistered producer {}", producer))?;
+                
+                if !block.verify_signature(&record.public_key)? {
+                    return Err(format!("RANDAO commitment is not signed by {}", producer).into());
+                }
+            }
+            
+            self.beacon.apply(producer, header.randao_reveal.as_deref(), header.randao_commitment.as_deref(), header.height)?;
+        } else if header.randao_reveal.is_some() || header.randao_commitment.is_some() {
+            return Err("RANDAO values require a block producer".into());
+        }
+        
+        if header.randomness.as_deref() != Some(self.beacon.randomness()) {
+            return Err(format!(
+                "Invalid randomness at height {}: expected {}, got {:?}",
+                header.height, self.beacon.randomness(), header.randomness
+            ).into());
+        }
+        
+        Ok(())
+    }
+    
+    /// Issue the block reward and share the block's fees
+    ///
+    /// Only blocks signed by an active validator earn the reward and the producer's
+    /// share of fees, so jailed validators are not paid. Shares without a recipient go
+    /// to the treasury.
+    fn distribute_rewards(&mut self, block: &Block, spec: &ChainSpec) -> Result<(), Box<dyn std::error::Error>> {
+        let policy = &spec.monetary_policy;
+        let fees = block.transactions
+            .iter()
+            .try_fold(0u64, |total, tx| total.checked_add(tx.fee))
+            .ok_or("Fee overflow")?;
+        let mut split = policy.fee_distribution.split(fees);
+        
+        let producer = self.signed_producer(block).filter(|producer| {
+            self.staking
+                .validator(producer)
+                .is_some_and(|record| record.status == ValidatorStatus::Active)
+        });
+        
+        // Model providers are paid for the proofs their models make, genesis models have no provider
+        let model_provider = block.ai_proof
+            .as_ref()
+            .and_then(|proof| AIProof::from_bytes(proof).ok())
+            .and_then(|proof| self.models.check_status(&proof.model_id, ModelStatus::Approved).ok())
+            .map(|model| model.owner.clone())
+            .filter(|owner| owner != GENESIS_OWNER);
+        
+        match &model_provider {
+            Some(owner) => self.credit(owner, split.model_provider)?,
+            None => split.treasury += std::mem::take(&mut split.model_provider),
+        }
+        
+        match producer {
+            Some(producer) => {
+                let reward = policy.block_reward(block.header.height, &self.rewards);
+                let earned = reward.checked_add(split.producer).ok_or("Producer earnings overflow")?;
+                self.rewards.issued = self.rewards.issued.checked_add(reward).ok_or("Issuance overflow")?;
+                self.pay_validator(producer, earned)?;
+            }
+            None => split.treasury += std::mem::take(&mut split.producer),
+        }
+        
+        self.credit(&policy.treasury, split.treasury)?;
+        self.rewards.record_fees(fees, &split)
+    }
+    
+    /// Get the producer of a block if it is a registered validator that signed the block
+    fn signed_producer<'a>(&self, block: &'a Block) -> Option<&'a String> {
+        block.header.producer.as_ref().filter(|producer| {
+            self.staking
+                .validator(producer)
+                .is_some_and(|record| block.verify_signature(&record.public_key).unwrap_or(false))
+        })
+    }
+    
+    /// Pay a validator's earnings, keeping its commission and sharing the rest with its delegators by stake
+    fn pay_validator(&mut self, validator: &str, amount: u64) -> Result<(), Box<dyn std::error::Error>> {
+        let commission_bps = self.staking.validator(validator).map_or(0, |record| record.commission_bps);
+        let commission = (u128::from(amount) * u128::from(commission_bps) / 10000) as u64;
+        let shared = amount - commission;
+        
+        let delegators = self.staking.delegators_of(validator);
+        let total_stake: u128 = delegators.iter().map(|(_, stake)| u128::from(*stake)).sum();
+        
+        let mut paid = 0;
+        for (delegator, stake) in delegators {
+            if let Some(share) = (u128::from(shared) * u128::from(stake)).checked_div(total_stake) {
+                self.credit(&delegator, share as u64)?;
+                paid += share as u64;
+            }
+        }
+        
+        // The validator keeps its commission and any rounding remainder
+        self.credit(validator, amount - paid)
+    }
+    
+    /// Close an epoch's uptime statistics and jail validators below the liveness threshold
+    fn enforce_liveness(&mut self, epoch: u64, height: u64, spec: &ChainSpec) -> Result<(), Box<dyn std::error::Error>> {
+        for (address, stats) in self.uptime.finish_epoch(epoch) {
+            if stats.uptime_bps() >= spec.liveness_threshold_bps {
+                continue;
+            }
+            
+            let active = self.staking
+                .validator(&address)
+                .is_some_and(|record| record.status == ValidatorStatus::Active);
+            
+            if active {
+                info!(
+                    "Jailing validator {} for downtime: produced {} of {} slots in epoch {}",
+                    address, stats.produced, stats.produced + stats.missed, epoch
+                );
+                self.slash_validator(&address, SlashReason::Downtime, height, spec)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Apply a single transaction
+    fn apply_transaction(
+        &mut self,
+        tx: &Transaction,
+        height: u64,
+        spec: &ChainSpec,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let amount = tx.amount.unwrap_or(0);
+
+        // Each nonce is used once and in order, so a transaction cannot be replayed
+        let expected_nonce = self.account(&tx.from).nonce;
+        if tx.nonce != expected_nonce {
+            return Err(format!(
+                "Invalid nonce for {}: expected {}, got {}",
+                tx.from, expected_nonce, tx.nonce
+            ).into());
+        }
+        
+        // Every transaction moves value, if only its fee, so only the sender's key may authorize it
+        self.check_signature(tx)?;
+        
+        match tx.tx_type {
+            TransactionType::Transfer => {
+                let to = tx.to.as_ref().ok_or("Transfer requires a recipient")?;
+                self.debit(&tx.from, amount.checked_add(tx.fee).ok_or("Amount overflow")?)?;
+                self.credit(to, amount)?;
+            }
+            TransactionType::Delegation => {
+                let candidate = tx.to.as_ref().ok_or("Delegation requires a candidate")?;
+                self.debit(&tx.from, amount.checked_add(tx.fee).ok_or("Amount overflow")?)?;
+                self.staking.delegate(&tx.from, candidate, amount)?;
+            }
+            TransactionType::Undelegation => {
+                let candidate = tx.to.as_ref().ok_or("Undelegation requires a candidate")?;
+                self.debit(&tx.from, tx.fee)?;
+                self.staking.undelegate(&tx.from, candidate, amount, height + spec.unbonding_period)?;
+                
+                if tx.from == *candidate {
+                    self.staking.check_self_stake(candidate, spec.min_self_stake)?;
+                }
+            }
+            TransactionType::ValidatorRegistration => {
+                let data = tx.data.as_ref().ok_or("Validator registration requires data")?;
+                let registration = ValidatorRegistration::decode(data)?;
+                self.debit(&tx.from, amount.checked_add(tx.fee).ok_or("Amount overflow")?)?;
+                self.staking.register_validator(&tx.from, registration, amount, spec.min_self_stake, height)?;
+            }
+            TransactionType::Unjail => {
+                self.debit(&tx.from, tx.fee)?;
+                self.staking.unjail(&tx.from, height, spec.min_self_stake)?;
+            }
+            TransactionType::AIModelSubmission => {
+                let data = tx.data.as_ref().ok_or("Model submission requires data")?;
+                let submission = ModelSubmission::decode(data)?;
+                self.debit(&tx.from, amount.checked_add(tx.fee).ok_or("Amount overflow")?)?;
+                self.models.submit(&tx.from, submission, amount, spec.min_model_stake, height, spec.governance_voting_period)?;
+            }
+            TransactionType::GovernanceProposal => {
+                let data = tx.data.as_ref().ok_or("Governance proposal requires data")?;
+                let action = ProposalAction::decode(data)?;
+                self.check_proposal(&action)?;
+                self.debit(&tx.from, tx.fee)?;
+                
+                // A pending model stays registered while a proposal to approve it is open
+                if let ProposalAction::ApproveModel { model_id } = &action {
+                    self.models.extend_pending(model_id, height + spec.governance_voting_period)?;
+                }
+                
+                let id = self.governance.submit(&tx.from, action, height, spec.governance_voting_period);
+                info!("Governance proposal {} submitted by {}", id, tx.from);
+            }
+            TransactionType::GovernanceVote => {
+                let data = tx.data.as_ref().ok_or("Governance vote requires data")?;
+                let vote = Vote::decode(data)?;
+                
+                if !self.epochs.current().iter().any(|validator| validator.address == tx.from) {
+                    return Err(format!("{} is not in the current validator set", tx.from).into());
+                }
+                
+                self.debit(&tx.from, tx.fee)?;
+                self.apply_vote(&tx.from, &vote, height, spec)?;
+            }
+            _ => {
+                self.debit(&tx.from, tx.fee)?;
+            }
+        }
+
+        self.accounts.entry(tx.from.clone()).or_default().nonce += 1;
+
+        Ok(())
+    }
+
+    /// Check a proposal's action can currently be applied
+    fn check_proposal(&self, action: &ProposalAction) -> Result<(), Box<dyn std::error::Error>> {
+        match action {
+            ProposalAction::ApproveModel { model_id } => self.models.check_status(model_id, ModelStatus::Pending)?,
+            ProposalAction::RetireModel { model_id } => self.models.check_status(model_id, ModelStatus::Approved)?,
+        };
+        
+        Ok(())
+    }
+    
+    /// Record a validator vote, executing the proposal once enough stake approves it
+    fn apply_vote(&mut self, voter: &str, vote: &Vote, height: u64, spec: &ChainSpec) -> Result<(), Box<dyn std::error::Error>> {
+        let proposal = self.governance.vote(voter, vote, height)?;
+        
+        if !proposal.has_passed(self.epochs.current(), spec.governance_threshold_bps) {
+            return Ok(());
+        }
+        
+        let action = proposal.action.clone();
+        
+        // Another proposal may have changed the model since this one was submitted
+        let status = match self.execute_proposal(&action, height) {
+            Ok(()) => ProposalStatus::Passed,
+            Err(e) => {
+                info!("Governance proposal {} can no longer be applied: {}", vote.proposal_id, e);
+                ProposalStatus::Rejected
+            }
+        };
+        
+        info!("Governance proposal {} closed as {:?}", vote.proposal_id, status);
+        self.governance.close(vote.proposal_id, status);
+        
+        Ok(())
+    }
+    
+    /// Apply the action of a passed proposal
+    fn execute_proposal(&mut self, action: &ProposalAction, height: u64) -> Result<(), Box<dyn std::error::Error>> {
+        match action {
+            ProposalAction::ApproveModel { model_id } => self.models.approve(model_id, height),
+            ProposalAction::RetireModel { model_id } => {
+                let (owner, stake) = self.models.retire(model_id, height)?;
+                self.credit(&owner, stake)
+            }
+        }
+    }
+    
+    /// Remove tokens from an account
+    fn debit(&mut self, address: &str, amount: u64) -> Result<(), Box<dyn std::error::Error>> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let account = self.accounts.entry(address.to_string()).or_default();
+
+        if account.balance < amount {
+            return Err(format!(
+                "Insufficient balance for {}: has {}, needs {}",
+                address, account.balance, amount
+            ).into());
+        }
+
+        account.balance -= amount;
+
+        Ok(())
+    }
+
+    /// Add tokens to an account
+    fn credit(&mut self, address: &str, amount: u64) -> Result<(), Box<dyn std::error::Error>> {
+        if amount == 0 {
+            return Ok(());
+        }
+        
+        let account = self.accounts.entry(address.to_string()).or_default();
+
+        account.balance = account.balance
+            .checked_add(amount)
+            .ok_or_else(|| format!("Balance overflow for {}", address))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,596 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use log::{debug, info, warn};
+use serde::{Serialize, Deserialize};
+use tokio::task::JoinSet;
+use crate::block::{Block, BlockHeader};
+use crate::chain::Blockchain;
+use crate::crypto::Signature;
+use crate::handshake::ChainStatus;
+use crate::network::{P2PNetwork, MAX_MESSAGE_SIZE};
+use crate::reputation::PeerEvent;
+
+/// Most headers returned for one request
+pub const MAX_HEADERS_PER_REQUEST: u64 = 512;
+
+/// Most blocks returned for one request
+pub const MAX_BODIES_PER_REQUEST: usize = 64;
+
+/// Block header with the producer's signature, which covers the header alone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedHeader {
+    /// Header of the block
+    pub header: BlockHeader,
+    /// Signature of the block's producer
+    pub signature: Option<Signature>,
+}
+
+impl SignedHeader {
+    /// Take the header and signature of a block
+    pub fn from_block(block: Block) -> Self {
+        SignedHeader {
+            header: block.header,
+            signature: block.validator_signature,
+        }
+    }
+    
+    /// Verify the signature against the producer's public key
+    pub fn verify_signature(&self, public_key: &str) -> bool {
+        self.signature
+            .as_ref()
+            .is_some_and(|signature| signature.verify(public_key, self.header.hash().as_bytes()).unwrap_or(false))
+    }
+}
+
+/// Request for chain data sent to a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SyncRequest {
+    /// Ask for the peer's current chain status
+    Status,
+    /// Ask for consecutive headers
+    Headers {
+        /// Height of the first header
+        start: u64,
+        /// Number of headers wanted
+        count: u64,
+    },
+    /// Ask for blocks by hash
+    Bodies {
+        /// Hashes of the blocks wanted, in chain order
+        hashes: Vec<String>,
+    },
+}
+
+impl SyncRequest {
+    /// Answer the request from a source of blocks
+    ///
+    /// Headers and blocks are returned in order up to the first one missing, and
+    /// are capped so the response fits in a message.
+    pub fn answer(self, source: &dyn BlockSource) -> SyncResponse {
+        match self {
+            SyncRequest::Status => SyncResponse::Status(source.status()),
+            SyncRequest::Headers { start, count } => {
+                SyncResponse::Headers(source.headers(start, count.min(MAX_HEADERS_PER_REQUEST)))
+            }
+            SyncRequest::Bodies { hashes } => {
+                let hashes = &hashes[..hashes.len().min(MAX_BODIES_PER_REQUEST)];
+                let mut size = 0;
+                let blocks = source
+                    .bodies(hashes)
+                    .into_iter()
+                    .take_while(|block| {
+                        let fits = size == 0 || size <= MAX_MESSAGE_SIZE / 2;
+                        size += serde_json::to_vec(block).map_or(0, |encoded| encoded.len());
+                        fits
+                    })
+                    .collect();
+                
+                SyncResponse::Bodies(blocks)
+            }
+        }
+    }
+}
+
+/// Chain data returned by a peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SyncResponse {
+    /// Chain status of the peer
+    Status(ChainStatus),
+    /// Consecutive signed headers, possibly fewer than asked for
+    Headers(Vec<SignedHeader>),
+    /// Blocks in the order asked for, possibly fewer than asked for
+    Bodies(Vec<Block>),
+}
+
+/// Message of the sync protocol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SyncMessage {
+    /// Request expecting a response with the same id
+    Request {
+        /// Identifier chosen by the requester
+        id: u64,
+        /// Data asked for
+        request: SyncRequest,
+    },
+    /// Response to a request
+    Response {
+        /// Identifier of the request
+        id: u64,
+        /// Data returned
+        response: SyncResponse,
+    },
+}
+
+impl SyncMessage {
+    /// Encode the message for the wire
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap()
+    }
+    
+    /// Decode a message from the wire
+    pub fn decode(data: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_slice(data)?)
+    }
+}
+
+/// Chain data served to syncing peers
+pub trait BlockSource: Send + Sync {
+    /// Get the chain status
+    fn status(&self) -> ChainStatus;
+    
+    /// Get consecutive signed headers from a height, stopping at the first one missing
+    fn headers(&self, start: u64, count: u64) -> Vec<SignedHeader>;
+    
+    /// Get blocks by hash, stopping at the first one missing
+    fn bodies(&self, hashes: &[String]) -> Vec<Block>;
+}
+
+impl BlockSource for Blockchain {
+    fn status(&self) -> ChainStatus {
+        ChainStatus::from_blockchain(self)
+    }
+    
+    fn headers(&self, start: u64, count: u64) -> Vec<SignedHeader> {
+        (start..start.saturating_add(count))
+            .map_while(|height| self.get_block_by_height(height))
+            .map(SignedHeader::from_block)
+            .collect()
+    }
+    
+    fn bodies(&self, hashes: &[String]) -> Vec<Block> {
+        hashes.iter().map_while(|hash| self.get_block_by_hash(hash)).collect()
+    }
+}
+
+/// Configuration of block synchronization
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    /// Most headers downloaded before their blocks are fetched
+    pub max_headers_per_round: u64,
+    /// Number of blocks asked from a peer at once
+    pub bodies_per_request: usize,
+    /// Time between checks for peers ahead of this node
+    pub interval: Duration,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        SyncConfig {
+            max_headers_per_round: 2048,
+            bodies_per_request: 32,
+            interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Progress of block synchronization
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncProgress {
+    /// Whether the node is downloading blocks
+    pub syncing: bool,
+    /// Height of the chain when synchronization started
+    pub starting_height: u64,
+    /// Height of the chain
+    pub current_height: u64,
+    /// Highest height announced by the peers still connected, or the chain's height if none is ahead
+    pub highest_height: u64,
+}
+
+/// Reason a peer failed a sync request
+enum Failure {
+    /// Peer did not answer, or had nothing to return
+    Stalled(String),
+    /// Peer returned data that is not what it claimed
+    Invalid(String),
+    /// Peer does not have the blocks asked for, which it never announced by hash
+    Missing(String),
+}
+
+/// Key a header must be signed with
+enum Signer {
+    /// Header needs no signature, as the chain does not schedule producers and it names none
+    Nobody,
+    /// Header must be signed with the producer's public key
+    Producer(String),
+    /// Producers at the header's height are not known until earlier blocks are imported
+    Unknown,
+}
+
+/// Range of downloaded headers whose blocks are being fetched, as indexes
+type BodyRange = (usize, usize);
+
+/// Catches the chain up with peers, downloading and checking headers before fetching blocks
+pub struct SyncManager {
+    chain: Arc<Blockchain>,
+    network: Arc<P2PNetwork>,
+    config: SyncConfig,
+    progress: Arc<Mutex<SyncProgress>>,
+}
+
+impl SyncManager {
+    /// Create a sync manager for a chain
+    pub fn new(chain: Arc<Blockchain>, network: Arc<P2PNetwork>) -> Self {
+        SyncManager {
+            chain,
+            network,
+            config: SyncConfig::default(),
+            progress: Arc::new(Mutex::new(SyncProgress::default())),
+        }
+    }
+    
+    /// Set the batch sizes and interval
+    pub fn with_config(mut self, config: SyncConfig) -> Self {
+        self.config = config;
+        self
+    }
+    
+    /// Get the synchronization progress
+    pub fn progress(&self) -> SyncProgress {
+        self.progress.lock().unwrap().clone()
+    }
+    
+    /// Get a handle to the progress, which stays current while the manager runs
+    pub fn progress_handle(&self) -> Arc<Mutex<SyncProgress>> {
+        self.progress.clone()
+    }
+    
+    /// Synchronize with peers for as long as the node runs
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(self.config.interval);
+        
+        loop {
+            interval.tick().await;
+            self.sync().await;
+        }
+    }
+    
+    /// Download blocks until no peer is ahead of this node and return how many were imported
+    ///
+    /// Peers that stall are skipped until the next call. Peers that return headers
+    /// or blocks that do not check out are disconnected.
+    pub async fn sync(&self) -> u64 {
+        let mut excluded = HashSet::new();
+        let mut imported = 0;
+        
+        {
+            let mut progress = self.progress.lock().unwrap();
+            progress.starting_height = self.chain.get_height();
+            progress.current_height = progress.starting_height;
+        }
+        
+        loop {
+            // Peers that disconnected or were dropped no longer count towards the highest height
+            let peers = self.peers_ahead(&excluded).await;
+            let best = peers.first().cloned();
+            {
+                let mut progress = self.progress.lock().unwrap();
+                progress.highest_height = best.as_ref().map_or(self.chain.get_height(), |(_, height)| *height);
+                progress.syncing |= best.is_some();
+            }
+            
+            let (best_peer, highest) = match best {
+                Some(best) => best,
+                None => break,
+            };
+            
+            let headers = match self.download_headers(&best_peer, highest).await {
+                Ok(headers) => headers,
+                Err(failure) => {
+                    self.penalize(&best_peer, failure, &mut excluded);
+                    continue;
+                }
+            };
+            
+            match self.download_blocks(&headers, &best_peer, &peers, &mut excluded).await {
+                Ok(count) => imported += count,
+                Err((count, failure)) => {
+                    imported += count;
+                    self.penalize(&best_peer, failure, &mut excluded);
+                }
+            }
+        }
+        
+        let mut progress = self.progress.lock().unwrap();
+        if progress.syncing {
+            info!("Synchronized {} blocks, now at height {}", imported, progress.current_height);
+        }
+        progress.syncing = false;
+        
+        imported
+    }
+    
+    /// Ask connected peers for their status and get those ahead of this node, highest first
+    async fn peers_ahead(&self, excluded: &HashSet<String>) -> Vec<(String, u64)> {
+        let mut tasks = JoinSet::new();
+        for peer in self.network.get_peers().into_iter().filter(|peer| !excluded.contains(peer)) {
+            let network = self.network.clone();
+            tasks.spawn(async move {
+                let response = network.request(&peer, SyncRequest::Status).await;
+                (peer, response)
+            });
+        }
+        
+        let height = self.chain.get_height();
+        let mut peers = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            if let Ok((peer, Ok(SyncResponse::Status(status)))) = joined {
+                if status.best_height > height {
+                    peers.push((peer, status.best_height));
+                }
+            }
+        }
+        
+        peers.sort_by_key(|(_, height)| std::cmp::Reverse(*height));
+        peers
+    }
+    
+    /// Download the headers following the chain tip from a peer and check they link up and are signed
+    ///
+    /// Headers are downloaded up to the first one whose producers are not known yet,
+    /// which the next round continues from once the blocks before it are imported.
+    async fn download_headers(&self, peer: &str, highest: u64) -> Result<Vec<SignedHeader>, Failure> {
+        let tip = self.chain.get_latest_block().ok_or_else(|| Failure::Stalled("Chain has no blocks".to_string()))?;
+        let (mut height, mut hash, mut timestamp) = (tip.header.height, tip.hash(), tip.header.timestamp);
+        let end = highest.min(height + self.config.max_headers_per_round);
+        let mut headers = Vec::new();
+        
+        while height < end {
+            let count = (end - height).min(MAX_HEADERS_PER_REQUEST);
+            let batch = match self.network.request(peer, SyncRequest::Headers { start: height + 1, count }).await {
+                Ok(SyncResponse::Headers(batch)) => batch,
+                Ok(_) => return Err(Failure::Invalid("Unexpected response to a header request".to_string())),
+                Err(e) => return Err(Failure::Stalled(e.to_string())),
+            };
+            
+            if batch.is_empty() {
+                return Err(Failure::Invalid(format!("No headers after height {} despite announcing {}", height, highest)));
+            }
+            
+            if batch.len() as u64 > count {
+                return Err(Failure::Invalid("More headers than requested".to_string()));
+            }
+            
+            for signed in batch {
+                let header = &signed.header;
+                if header.height != height + 1 || header.prev_hash != hash {
+                    return Err(Failure::Invalid(format!("Header {} does not extend the chain", header.height)));
+                }
+                
+                match self.signer(header, timestamp)? {
+                    Signer::Nobody => {
+                        if signed.signature.is_some() {
+                            return Err(Failure::Invalid(format!("Header {} is signed without naming its producer", header.height)));
+                        }
+                    }
+                    Signer::Producer(public_key) => {
+                        if !signed.verify_signature(&public_key) {
+                            return Err(Failure::Invalid(format!("Header {} is not signed by its producer", header.height)));
+                        }
+                    }
+                    Signer::Unknown => return self.checked_headers(peer, headers),
+                }
+                
+                height = header.height;
+                hash = header.hash();
+                timestamp = header.timestamp;
+                headers.push(signed);
+            }
+        }
+        
+        self.checked_headers(peer, headers)
+    }
+    
+    /// Get the key the header must be signed with, or fail if it names the wrong producer
+    ///
+    /// Chains that schedule producers require the validator scheduled at the header's
+    /// height, in the round its timestamp falls in after its parent's. Other chains only
+    /// require a named producer to be a registered validator.
+    fn signer(&self, header: &BlockHeader, parent_timestamp: u64) -> Result<Signer, Failure> {
+        let spec = self.chain.get_spec();
+        if spec.consensus.scheduled_producer {
+            let round = spec.slot_round(parent_timestamp, header.timestamp);
+            let scheduled = match self.chain.get_scheduled_producer(header.height, round) {
+                Some(scheduled) => scheduled,
+                None => return Ok(Signer::Unknown),
+            };
+            
+            if header.producer.as_ref() != Some(&scheduled.address) {
+                return Err(Failure::Invalid(format!("Header {} is not from the scheduled producer", header.height)));
+            }
+            return Ok(Signer::Producer(scheduled.public_key));
+        }
+        
+        Ok(match &header.producer {
+            Some(producer) => self.chain.get_validator(producer).map_or(Signer::Unknown, |record| Signer::Producer(record.public_key)),
+            None => Signer::Nobody,
+        })
+    }
+    
+    /// Accept headers that passed their checks, unless there are none
+    fn checked_headers(&self, peer: &str, headers: Vec<SignedHeader>) -> Result<Vec<SignedHeader>, Failure> {
+        if headers.is_empty() {
+            return Err(Failure::Stalled("No header after the chain tip can be checked yet".to_string()));
+        }
+        
+        debug!("Downloaded {} headers from peer {}", headers.len(), peer);
+        self.network.report_peer(peer, PeerEvent::UsefulResponse);
+        Ok(headers)
+    }
+    
+    /// Fetch the blocks of downloaded headers from peers in parallel and import them in order
+    ///
+    /// Ranges that a peer fails to return are handed to another peer. Peers that do
+    /// not have a range may be on another branch, so they are only asked for nothing
+    /// more. A block that matches its header but does not apply means the headers
+    /// were bad, as does the supplier of the headers not having their blocks when no
+    /// other peer serves them. Either is returned as a failure of the supplier along
+    /// with the number of blocks imported before it.
+    async fn download_blocks(
+        &self,
+        headers: &[SignedHeader],
+        supplier: &str,
+        peers: &[(String, u64)],
+        excluded: &mut HashSet<String>,
+    ) -> Result<u64, (u64, Failure)> {
+        let hashes: Vec<String> = headers.iter().map(|signed| signed.header.hash()).collect();
+        let batch = self.config.bodies_per_request.clamp(1, MAX_BODIES_PER_REQUEST);
+        let mut queue: VecDeque<BodyRange> = (0..hashes.len())
+            .step_by(batch)
+            .map(|start| (start, (start + batch).min(hashes.len())))
+            .collect();
+        let mut blocks: Vec<Option<Block>> = vec![None; hashes.len()];
+        let mut idle: Vec<(String, u64)> = peers.iter().filter(|(peer, _)| !excluded.contains(peer)).cloned().collect();
+        let mut tasks = JoinSet::new();
+        let mut next = 0;
+        let mut imported = 0;
+        let mut refused_by_supplier = false;
+        
+        loop {
+            // Hand out ranges to idle peers that announced their blocks
+            while let Some(&(start, end)) = queue.front() {
+                let needed = headers[end - 1].header.height;
+                let position = match idle.iter().position(|(_, best)| *best >= needed) {
+                    Some(position) => position,
+                    None => break,
+                };
+                
+                let (peer, best) = idle.remove(position);
+                queue.pop_front();
+                let network = self.network.clone();
+                let wanted = headers[start..end].to_vec();
+                tasks.spawn(async move {
+                    let result = fetch_bodies(&network, &peer, wanted).await;
+                    (peer, best, (start, end), result)
+                });
+            }
+            
+            let (peer, best, (start, end), result) = match tasks.join_next().await {
+                Some(Ok(finished)) => finished,
+                Some(Err(e)) => {
+                    warn!("Block download task failed: {}", e);
+                    continue;
+                }
+                None => break,
+            };
+            
+            let fetched = match result {
+                Ok(fetched) => fetched,
+                Err(Failure::Missing(reason)) => {
+                    debug!("Peer {} does not have blocks {} to {}: {}", peer, headers[start].header.height, headers[end - 1].header.height, reason);
+                    queue.push_front((start, end));
+                    refused_by_supplier |= peer == supplier;
+                    continue;
+                }
+                Err(failure) => {
+                    queue.push_front((start, end));
+                    self.penalize(&peer, failure, excluded);
+                    continue;
+                }
+            };
+            
+            // Peers may return part of a range, the rest is asked for again
+            self.network.report_peer(&peer, PeerEvent::UsefulResponse);
+            let received = fetched.len();
+            for (offset, block) in fetched.into_iter().enumerate() {
+                blocks[start + offset] = Some(block);
+            }
+            if start + received < end {
+                queue.push_front((start + received, end));
+            }
+            idle.push((peer, best));
+            
+            while let Some(block) = blocks.get_mut(next).and_then(Option::take) {
+                next += 1;
+                let height = block.header.height;
+                
+                // Gossip may have delivered the block in the meantime
+                if self.chain.get_block_by_hash(&block.hash()).is_some() {
+                    continue;
+                }
+                
+                if let Err(e) = self.chain.add_block(block) {
+                    return Err((imported, Failure::Invalid(format!("Block {} does not apply: {}", height, e))));
+                }
+                
+                imported += 1;
+                self.progress.lock().unwrap().current_height = height;
+            }
+        }
+        
+        if next < hashes.len() {
+            let (first, last) = (headers[next].header.height, headers[hashes.len() - 1].header.height);
+            if refused_by_supplier {
+                return Err((imported, Failure::Invalid(format!("No peer has blocks {} to {} of the headers it sent", first, last))));
+            }
+            debug!("No peers left to fetch blocks {} to {} from", first, last);
+        }
+        
+        Ok(imported)
+    }
+    
+    /// Stop asking a peer for data this round and lower its score unless it only lacked the data, which bans it if it lied
+    fn penalize(&self, peer: &str, failure: Failure, excluded: &mut HashSet<String>) {
+        excluded.insert(peer.to_string());
+        
+        match failure {
+            Failure::Stalled(reason) => {
+                debug!("Peer {} stalled during sync: {}", peer, reason);
+                self.network.report_peer(peer, PeerEvent::Unresponsive);
+            }
+            Failure::Invalid(reason) => {
+                warn!("Peer {} sent invalid sync data: {}", peer, reason);
+                self.network.report_peer(peer, PeerEvent::InvalidSyncData);
+            }
+            Failure::Missing(reason) => {
+                debug!("Peer {} is missing blocks during sync: {}", peer, reason);
+            }
+        }
+    }
+}
+
+/// Ask a peer for blocks and check each one matches its header and carries the signature checked with it
+async fn fetch_bodies(network: &P2PNetwork, peer: &str, headers: Vec<SignedHeader>) -> Result<Vec<Block>, Failure> {
+    let hashes: Vec<String> = headers.iter().map(|signed| signed.header.hash()).collect();
+    let blocks = match network.request(peer, SyncRequest::Bodies { hashes: hashes.clone() }).await {
+        Ok(SyncResponse::Bodies(blocks)) => blocks,
+        Ok(_) => return Err(Failure::Invalid("Unexpected response to a block request".to_string())),
+        Err(e) => return Err(Failure::Stalled(e.to_string())),
+    };
+    
+    if blocks.is_empty() {
+        return Err(Failure::Missing("Peer returned no blocks".to_string()));
+    }
+    
+    if blocks.len() > hashes.len() {
+        return Err(Failure::Invalid("More blocks than requested".to_string()));
+    }
+    
+    for ((block, hash), signed) in blocks.iter().zip(&hashes).zip(&headers) {
+        if block.hash() != *hash || !block.has_valid_roots() || block.validator_signature != signed.signature {
+            return Err(Failure::Invalid(format!("Block {} does not match its header", block.header.height)));
+        }
+    }
+    
+    Ok(blocks)
+}
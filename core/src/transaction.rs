@@ -2,7 +2,7 @@ use serde::{Serialize, Deserialize};
 use sha2::{Sha256, Digest};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::crypto::Signature;
+use crate::crypto::{KeyPair, Signature};
 
 /// Transaction types supported by HyperNova Chain
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +21,12 @@ pub enum TransactionType {
     GovernanceProposal,
     /// Governance vote
     GovernanceVote,
+    /// Bond tokens to a validator candidate
+    Delegation,
+    /// Start unbonding tokens from a validator candidate
+    Undelegation,
+    /// Return a jailed validator to the active set
+    Unjail,
 }
 
 /// Transaction structure for HyperNova Chain
@@ -96,15 +102,22 @@ impl Transaction {
         self.signature = Some(signature);
     }
     
+    /// Sign the transaction with the sender's key
+    pub fn sign_with(&mut self, keypair: &KeyPair) {
+        let signature = keypair.sign(self.hash().as_bytes());
+        self.sign(signature);
+    }
+    
     /// Add quantum-resistant signature
     pub fn add_quantum_signature(&mut self, signature: Vec<u8>) {
         self.quantum_signature = Some(signature);
     }
     
-    /// Verify the transaction signature
-    pub fn verify_signature(&self) -> bool {
-        // In a real implementation, this would verify the signature
-        // against the sender's public key
-        self.signature.is_some()
+    /// Verify the sender's signature against its public key
+    pub fn verify_signature(&self, public_key: &str) -> Result<bool, Box<dyn std::error::Error>> {
+        match &self.signature {
+            Some(signature) => signature.verify(public_key, self.hash().as_bytes()),
+            None => Ok(false),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,359 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadHalf, WriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use crate::network::NetworkError;
+
+/// Bytes buffered in each direction of an in-memory connection
+const MEMORY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Chunks in flight on an in-memory connection while latency holds them back
+const MEMORY_CHUNKS_IN_FLIGHT: usize = 64;
+
+/// First port handed out by an in-memory network
+const FIRST_MEMORY_PORT: u16 = 30000;
+
+/// Byte stream to a peer
+pub trait Stream: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Stream for T {}
+
+/// Connection to a peer, whatever transport carries it
+pub type PeerStream = Box<dyn Stream>;
+
+/// Future returned by transports
+pub type TransportFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, NetworkError>> + Send + 'a>>;
+
+/// Way of opening connections to peers and accepting theirs
+pub trait Transport: Send + Sync {
+    /// Listen for connections on a port, any free port if 0
+    ///
+    /// Called from within the runtime the network runs on.
+    fn listen(&self, port: u16) -> Result<Box<dyn Listener>, NetworkError>;
+    
+    /// Open a connection to an address, returning it with the address it reached
+    fn dial<'a>(&'a self, address: &'a str) -> TransportFuture<'a, (PeerStream, SocketAddr)>;
+}
+
+/// Source of connections opened by peers
+pub trait Listener: Send {
+    /// Get the address peers reach the listener at
+    fn local_addr(&self) -> SocketAddr;
+    
+    /// Wait for the next connection, returning it with the address it came from
+    fn accept(&mut self) -> TransportFuture<'_, (PeerStream, SocketAddr)>;
+}
+
+/// Transport over TCP
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpTransport;
+
+impl Transport for TcpTransport {
+    /// Listen on all interfaces, binding right away so a port in use is reported
+    fn listen(&self, port: u16) -> Result<Box<dyn Listener>, NetworkError> {
+        let listener = std::net::TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+        
+        Ok(Box::new(TcpTransportListener {
+            listener: TcpListener::from_std(listener)?,
+            local_addr,
+        }))
+    }
+    
+    fn dial<'a>(&'a self, address: &'a str) -> TransportFuture<'a, (PeerStream, SocketAddr)> {
+        Box::pin(async move {
+            let stream = TcpStream::connect(address).await?;
+            let remote = stream.peer_addr()?;
+            
+            Ok((Box::new(stream) as PeerStream, remote))
+        })
+    }
+}
+
+/// Listener of the TCP transport
+struct TcpTransportListener {
+    listener: TcpListener,
+    local_addr: SocketAddr,
+}
+
+impl Listener for TcpTransportListener {
+    fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+    
+    fn accept(&mut self) -> TransportFuture<'_, (PeerStream, SocketAddr)> {
+        Box::pin(async move {
+            let (stream, remote) = self.listener.accept().await?;
+            
+            Ok((Box::new(stream) as PeerStream, remote))
+        })
+    }
+}
+
+/// Network connecting nodes inside one process, for tests
+///
+/// Each node gets a transport with an address of its own, and connections between
+/// them are carried over in-memory pipes. Latency can be added to all connections
+/// or to those between two nodes, and connections between nodes can be cut.
+///
+/// Data is delayed on the Tokio clock, so tests can run nodes on a current-thread
+/// runtime with a paused clock, through the async API of the network.
+#[derive(Clone, Default)]
+pub struct MemoryNetwork {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+/// Listeners, connections and settings of an in-memory network
+#[derive(Default)]
+struct MemoryState {
+    /// Number of transports created, used to give each its own address
+    hosts: u32,
+    /// Last port handed out
+    last_port: u16,
+    /// Senders of new connections by listening address
+    listeners: HashMap<SocketAddr, mpsc::UnboundedSender<(PeerStream, SocketAddr)>>,
+    /// Open connections
+    links: Vec<MemoryLink>,
+    /// Time each chunk of data takes to arrive
+    latency: Duration,
+    /// Latency between pairs of hosts, in place of the network's
+    link_latency: HashMap<(IpAddr, IpAddr), Duration>,
+}
+
+impl MemoryState {
+    /// Get the time data takes from one host to another
+    fn latency(&self, from: IpAddr, to: IpAddr) -> Duration {
+        self.link_latency.get(&link(from, to)).copied().unwrap_or(self.latency)
+    }
+}
+
+/// Connection between two hosts of an in-memory network
+struct MemoryLink {
+    /// Addresses of the hosts at both ends
+    hosts: (IpAddr, IpAddr),
+    /// Tasks carrying data in each direction, ending which closes the connection
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MemoryNetwork {
+    /// Create an empty network
+    pub fn new() -> Self {
+        MemoryNetwork::default()
+    }
+    
+    /// Create the transport of a new node, with an address of its own
+    pub fn transport(&self) -> MemoryTransport {
+        let mut state = self.state.lock().unwrap();
+        state.hosts += 1;
+        let host = Ipv4Addr::from(u32::from(Ipv4Addr::new(10, 0, 0, 0)) + state.hosts);
+        
+        MemoryTransport {
+            network: self.clone(),
+            host: IpAddr::V4(host),
+        }
+    }
+    
+    /// Delay data on all connections, including open ones, by a latency
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+    
+    /// Delay data between two hosts, including on open connections, by a latency in place of the network's
+    pub fn set_link_latency(&self, a: IpAddr, b: IpAddr, latency: Duration) {
+        self.state.lock().unwrap().link_latency.insert(link(a, b), latency);
+    }
+    
+    /// Cut the connections between two hosts, returning how many were cut
+    pub fn disconnect(&self, a: IpAddr, b: IpAddr) -> usize {
+        self.cut(|hosts| hosts == (a, b) || hosts == (b, a))
+    }
+    
+    /// Cut all connections of a host, returning how many were cut
+    pub fn isolate(&self, host: IpAddr) -> usize {
+        self.cut(|(a, b)| a == host || b == host)
+    }
+    
+    /// Cut the open connections between hosts matching a filter
+    fn cut<F: Fn((IpAddr, IpAddr)) -> bool>(&self, filter: F) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.links.retain(|link| !link.tasks.iter().all(JoinHandle::is_finished));
+        
+        let mut cut = 0;
+        state.links.retain(|link| {
+            if !filter(link.hosts) {
+                return true;
+            }
+            
+            for task in &link.tasks {
+                task.abort();
+            }
+            cut += 1;
+            false
+        });
+        
+        cut
+    }
+    
+    /// Get the next free port of a host
+    fn next_port(state: &mut MemoryState, host: IpAddr) -> u16 {
+        loop {
+            state.last_port = match state.last_port {
+                0 | u16::MAX => FIRST_MEMORY_PORT,
+                port => port + 1,
+            };
+            
+            if !state.listeners.contains_key(&SocketAddr::new(host, state.last_port)) {
+                return state.last_port;
+            }
+        }
+    }
+}
+
+/// Transport of one node on an in-memory network
+#[derive(Clone)]
+pub struct MemoryTransport {
+    network: MemoryNetwork,
+    host: IpAddr,
+}
+
+impl MemoryTransport {
+    /// Get the address of the node on the network
+    pub fn host(&self) -> IpAddr {
+        self.host
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn listen(&self, port: u16) -> Result<Box<dyn Listener>, NetworkError> {
+        let mut state = self.network.state.lock().unwrap();
+        
+        let port = match port {
+            0 => MemoryNetwork::next_port(&mut state, self.host),
+            port => port,
+        };
+        let local_addr = SocketAddr::new(self.host, port);
+        if state.listeners.contains_key(&local_addr) {
+            return Err(format!("Address in use: {}", local_addr).into());
+        }
+        
+        let (sender, connections) = mpsc::unbounded_channel();
+        state.listeners.insert(local_addr, sender);
+        
+        Ok(Box::new(MemoryListener {
+            network: self.network.clone(),
+            local_addr,
+            connections,
+        }))
+    }
+    
+    /// Connect to a listener on the network, carrying data through tasks on the current runtime
+    fn dial<'a>(&'a self, address: &'a str) -> TransportFuture<'a, (PeerStream, SocketAddr)> {
+        Box::pin(async move {
+            let remote: SocketAddr = address.parse()?;
+            let mut state = self.network.state.lock().unwrap();
+            
+            let listener = state
+                .listeners
+                .get(&remote)
+                .cloned()
+                .ok_or_else(|| format!("Connection refused: {}", address))?;
+            let local = SocketAddr::new(self.host, MemoryNetwork::next_port(&mut state, self.host));
+            
+            // Each end gets a pipe whose far side is joined to the other's by a task per direction
+            let (dialer, dialer_far) = tokio::io::duplex(MEMORY_BUFFER_SIZE);
+            let (accepted, accepted_far) = tokio::io::duplex(MEMORY_BUFFER_SIZE);
+            let (dialer_reader, dialer_writer) = tokio::io::split(dialer_far);
+            let (accepted_reader, accepted_writer) = tokio::io::split(accepted_far);
+            let tasks = vec![
+                tokio::spawn(carry(dialer_reader, accepted_writer, (self.host, remote.ip()), self.network.state.clone())),
+                tokio::spawn(carry(accepted_reader, dialer_writer, (remote.ip(), self.host), self.network.state.clone())),
+            ];
+            
+            if listener.send((Box::new(accepted) as PeerStream, local)).is_err() {
+                for task in &tasks {
+                    task.abort();
+                }
+                return Err(format!("Connection refused: {}", address).into());
+            }
+            state.links.retain(|link| !link.tasks.iter().all(JoinHandle::is_finished));
+            state.links.push(MemoryLink { hosts: (self.host, remote.ip()), tasks });
+            
+            Ok((Box::new(dialer) as PeerStream, remote))
+        })
+    }
+}
+
+/// Listener of an in-memory transport, which stops taking connections when dropped
+struct MemoryListener {
+    network: MemoryNetwork,
+    local_addr: SocketAddr,
+    connections: mpsc::UnboundedReceiver<(PeerStream, SocketAddr)>,
+}
+
+impl Listener for MemoryListener {
+    fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+    
+    fn accept(&mut self) -> TransportFuture<'_, (PeerStream, SocketAddr)> {
+        Box::pin(async move { self.connections.recv().await.ok_or_else(|| "Listener closed".into()) })
+    }
+}
+
+impl Drop for MemoryListener {
+    fn drop(&mut self) {
+        self.network.state.lock().unwrap().listeners.remove(&self.local_addr);
+    }
+}
+
+/// Identify the link between two hosts, whichever end it is seen from
+fn link(a: IpAddr, b: IpAddr) -> (IpAddr, IpAddr) {
+    (a.min(b), a.max(b))
+}
+
+/// Carry data one way across an in-memory connection between two hosts, delayed by their latency
+///
+/// Closing the sending end closes the receiving end once the data in flight has arrived.
+async fn carry(
+    mut from: ReadHalf<DuplexStream>,
+    mut to: WriteHalf<DuplexStream>,
+    hosts: (IpAddr, IpAddr),
+    state: Arc<Mutex<MemoryState>>,
+) {
+    let (sender, mut chunks) = mpsc::channel::<(Instant, Vec<u8>)>(MEMORY_CHUNKS_IN_FLIGHT);
+    
+    let deliver = async move {
+        while let Some((due, chunk)) = chunks.recv().await {
+            tokio::time::sleep_until(due).await;
+            if to.write_all(&chunk).await.is_err() {
+                break;
+            }
+        }
+        let _ = to.shutdown().await;
+    };
+    
+    let read = async move {
+        let mut buffer = vec![0u8; MEMORY_BUFFER_SIZE];
+        loop {
+            let n = match from.read(&mut buffer).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            
+            let due = Instant::now() + state.lock().unwrap().latency(hosts.0, hosts.1);
+            if sender.send((due, buffer[..n].to_vec())).await.is_err() {
+                break;
+            }
+        }
+    };
+    
+    tokio::join!(read, deliver);
+}
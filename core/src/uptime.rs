@@ -0,0 +1,112 @@
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// Number of finished epochs whose statistics are kept in chain state
+const UPTIME_HISTORY_EPOCHS: usize = 100;
+
+/// Block production record of a validator over an epoch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotStats {
+    /// Scheduled slots the validator produced a block in
+    pub produced: u64,
+    /// Scheduled slots the validator missed
+    pub missed: u64,
+}
+
+impl SlotStats {
+    /// Share of scheduled slots produced, in basis points
+    pub fn uptime_bps(&self) -> u64 {
+        let total = self.produced + self.missed;
+        
+        if total == 0 {
+            return 10000;
+        }
+        
+        self.produced * 10000 / total
+    }
+}
+
+/// Uptime of a validator in a single epoch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochUptime {
+    /// Epoch the validator set was elected in
+    pub epoch: u64,
+    /// Slots produced and missed
+    pub stats: SlotStats,
+    /// Share of scheduled slots produced, in basis points
+    pub uptime_bps: u64,
+}
+
+/// Uptime report of a validator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorUptime {
+    /// Validator's address
+    pub address: String,
+    /// Slots produced and missed in the epoch in progress
+    pub current: SlotStats,
+    /// Uptime in finished epochs, oldest first
+    pub history: Vec<EpochUptime>,
+}
+
+/// Produced and missed slots of every scheduled validator
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UptimeTracker {
+    /// Statistics of the epoch in progress
+    current: BTreeMap<String, SlotStats>,
+    /// Statistics of finished epochs, by epoch
+    history: BTreeMap<u64, BTreeMap<String, SlotStats>>,
+}
+
+impl UptimeTracker {
+    /// Record the outcome of a slot scheduled for a validator
+    pub fn record(&mut self, scheduled: &str, producer: Option<&str>) {
+        let stats = self.current.entry(scheduled.to_string()).or_default();
+        
+        if producer == Some(scheduled) {
+            stats.produced += 1;
+        } else {
+            stats.missed += 1;
+        }
+    }
+    
+    /// Close the epoch in progress, returning its statistics
+    pub fn finish_epoch(&mut self, epoch: u64) -> BTreeMap<String, SlotStats> {
+        let finished = std::mem::take(&mut self.current);
+        self.history.insert(epoch, finished.clone());
+        
+        while self.history.len() > UPTIME_HISTORY_EPOCHS {
+            let oldest = *self.history.keys().next().unwrap();
+            self.history.remove(&oldest);
+        }
+        
+        finished
+    }
+    
+    /// Get the statistics of the epoch in progress
+    pub fn current(&self) -> &BTreeMap<String, SlotStats> {
+        &self.current
+    }
+    
+    /// Get the uptime report of a validator
+    pub fn report(&self, address: &str) -> ValidatorUptime {
+        ValidatorUptime {
+            address: address.to_string(),
+            current: self.current.get(address).copied().unwrap_or_default(),
+            history: self.history_of(address),
+        }
+    }
+    
+    /// Get a validator's uptime in each finished epoch still kept, oldest first
+    pub fn history_of(&self, address: &str) -> Vec<EpochUptime> {
+        self.history
+            .iter()
+            .filter_map(|(&epoch, validators)| {
+                validators.get(address).map(|stats| EpochUptime {
+                    epoch,
+                    stats: *stats,
+                    uptime_bps: stats.uptime_bps(),
+                })
+            })
+            .collect()
+    }
+}
@@ -1,4 +1,12 @@
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use log::{error, warn};
+use serde::{de::DeserializeOwned, Serialize};
 use sha2::{Sha256, Digest};
+use tokio::runtime::{Handle, RuntimeFlavor};
 
 /// Calculate SHA-256 hash of data
 pub fn sha256(data: &[u8]) -> String {
@@ -38,6 +46,11 @@ pub fn random_nonce() -> u64 {
     rand::thread_rng().gen()
 }
 
+/// Get the current Unix time in seconds
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
 /// Format a timestamp as a human-readable date/time
 pub fn format_timestamp(timestamp: u64) -> String {
     use std::time::{Duration, UNIX_EPOCH};
@@ -46,4 +59,128 @@ pub fn format_timestamp(timestamp: u64) -> String {
     let datetime = chrono::DateTime::<chrono::Utc>::from(datetime);
     
     datetime.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// Write a file through a temporary file renamed over it, so a crash never leaves it half written
+pub fn write_atomically(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    
+    let mut file = std::fs::File::create(&temporary)?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    std::fs::rename(&temporary, path)
+}
+
+/// JSON file holding a list of records, such as saved peers or bans
+///
+/// Records are snapshotted under whatever lock guards them and written once it is
+/// released. A snapshot older than one already written is dropped, so racing writers
+/// never take the file back.
+#[derive(Debug)]
+pub struct JsonFile {
+    /// Location of the file
+    path: PathBuf,
+    /// What the records are, for log messages
+    description: &'static str,
+    /// Number of the latest snapshot taken
+    taken: u64,
+    /// Number of the latest snapshot written
+    written: Arc<Mutex<u64>>,
+}
+
+impl JsonFile {
+    /// Create a handle to a file of records
+    pub fn new(path: &Path, description: &'static str) -> Self {
+        JsonFile {
+            path: path.to_path_buf(),
+            description,
+            taken: 0,
+            written: Arc::new(Mutex::new(0)),
+        }
+    }
+    
+    /// Load the records, logging a file that cannot be read and starting afresh
+    pub fn load<T: DeserializeOwned>(&self) -> Vec<T> {
+        if !self.path.exists() {
+            return Vec::new();
+        }
+        
+        let result = std::fs::read(&self.path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()));
+        result.unwrap_or_else(|e| {
+            warn!("Ignoring unreadable {} in {}: {}", self.description, self.path.display(), e);
+            Vec::new()
+        })
+    }
+    
+    /// Take a snapshot of the records to write later
+    pub fn snapshot<T>(&mut self, records: Vec<T>) -> Snapshot<T> {
+        self.taken += 1;
+        
+        Snapshot {
+            path: self.path.clone(),
+            description: self.description,
+            number: self.taken,
+            written: self.written.clone(),
+            records,
+        }
+    }
+}
+
+/// Records taken from a `JsonFile` to be written once the lock guarding them is released
+#[must_use = "a snapshot is only written when saved"]
+pub struct Snapshot<T> {
+    /// Location of the file
+    path: PathBuf,
+    /// What the records are, for log messages
+    description: &'static str,
+    /// Order in which the snapshot was taken
+    number: u64,
+    /// Number of the latest snapshot written to the file
+    written: Arc<Mutex<u64>>,
+    /// Records to write
+    records: Vec<T>,
+}
+
+impl<T: Serialize> Snapshot<T> {
+    /// Write the records unless a newer snapshot was written, logging rather than failing on errors
+    pub fn save(self) {
+        let mut written = self.written.lock().unwrap();
+        if *written > self.number {
+            return;
+        }
+        
+        let result = serde_json::to_vec_pretty(&self.records)
+            .map_err(|e| e.to_string())
+            .and_then(|data| write_atomically(&self.path, &data).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => *written = self.number,
+            Err(e) => error!("Failed to save {} to {}: {}", self.description, self.path.display(), e),
+        }
+    }
+}
+
+/// Run a future to completion from synchronous code
+///
+/// The future runs on `runtime` if given, otherwise on the current runtime or a temporary
+/// one. Inside a multi-threaded runtime the worker hands its other tasks over while it
+/// blocks. A current-thread runtime would stall, so callers there get an error and must
+/// use the async API instead.
+pub fn block_on<F: Future>(runtime: Option<&Handle>, future: F) -> Result<F::Output, Box<dyn std::error::Error + Send + Sync>> {
+    match Handle::try_current() {
+        Ok(current) if current.runtime_flavor() == RuntimeFlavor::CurrentThread => {
+            Err("Cannot block inside a current-thread runtime, use the async API".into())
+        }
+        Ok(current) => Ok(tokio::task::block_in_place(|| runtime.unwrap_or(&current).block_on(future))),
+        Err(_) => match runtime {
+            Some(runtime) => Ok(runtime.block_on(future)),
+            None => {
+                let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+                Ok(runtime.block_on(future))
+            }
+        },
+    }
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use hypernova_core::{
+        addressbook::{AddressBook, PeerEntry, MAX_RECENT_FAILURES},
+        discovery::NodeRecord,
+        network::{NetworkConfig, P2PNetwork},
+        transport::MemoryNetwork,
+    };
+    use crate::common::wait_for;
+    
+    fn record(public_key: &str, address: &str) -> NodeRecord {
+        NodeRecord { public_key: public_key.to_string(), address: address.to_string() }
+    }
+    
+    #[test]
+    fn test_known_peers_are_ranked_persisted_and_aged_out() {
+        let path = std::env::temp_dir().join("hypernova_address_book.json");
+        let _ = std::fs::remove_file(&path);
+        let mut book = AddressBook::open(&path);
+        
+        book.record_success(&record("a", "10.0.0.1:30303"), 1);
+        book.record_success(&record("a", "10.0.0.1:30303"), 1);
+        book.record_success(&record("b", "10.0.0.2:30303"), 3);
+        book.record_success(&record("c", "10.0.0.3:30303"), 1);
+        assert!(book.record_failure("10.0.0.2:30303"));
+        assert!(!book.record_failure("10.0.0.9:30303"));
+        
+        // Peers connected to most often come first, those failing since last seen last
+        let order: Vec<String> = book.best().into_iter().map(|entry| entry.public_key).collect();
+        assert_eq!(order, vec!["a", "c", "b"]);
+        let b = book.get("b").unwrap();
+        assert_eq!((b.services, b.successes, b.failures, b.recent_failures), (3, 1, 1, 1));
+        
+        // Peers that keep failing are forgotten
+        for _ in 0..MAX_RECENT_FAILURES {
+            book.record_failure("10.0.0.3:30303");
+        }
+        assert!(book.get("c").is_none());
+        
+        // Entries outlive restarts, except for peers not seen for too long
+        book.save();
+        let mut entries: Vec<PeerEntry> = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(entries.len(), 2);
+        entries[0].last_seen = 0;
+        std::fs::write(&path, serde_json::to_vec(&entries).unwrap()).unwrap();
+        let reopened = AddressBook::open(&path);
+        assert_eq!(reopened.len(), 1);
+        assert!(reopened.get(&entries[1].public_key).is_some());
+    }
+    
+    #[test]
+    fn test_corrupt_address_book_is_replaced() {
+        let path = std::env::temp_dir().join("hypernova_corrupt_address_book.json");
+        std::fs::write(&path, b"[{\"public_key\": ").unwrap();
+        
+        // A file cut short by a crash does not keep the node from starting
+        let mut book = AddressBook::open(&path);
+        assert!(book.is_empty());
+        
+        // Saving writes the whole file at once and leaves nothing else behind
+        book.record_success(&record("a", "10.0.0.1:30303"), 1);
+        book.save();
+        assert_eq!(AddressBook::open(&path).len(), 1);
+        let mut temporary = path.clone().into_os_string();
+        temporary.push(".tmp");
+        assert!(!std::path::Path::new(&temporary).exists());
+    }
+    
+    #[test]
+    fn test_saves_taken_under_a_lock_keep_the_newest_entries() {
+        let path = std::env::temp_dir().join("hypernova_racing_address_book.json");
+        let _ = std::fs::remove_file(&path);
+        let mut book = AddressBook::open(&path);
+        
+        // Nothing is written until the entries change
+        assert!(book.take_unsaved().is_none());
+        book.record_success(&record("a", "10.0.0.1:30303"), 1);
+        let stale = book.take_unsaved().unwrap();
+        assert!(book.take_unsaved().is_none());
+        
+        // A writer that took its snapshot first but saves last does not undo a newer save
+        book.record_success(&record("b", "10.0.0.2:30303"), 1);
+        book.save();
+        stale.save();
+        assert_eq!(AddressBook::open(&path).len(), 2);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_restarted_node_rejoins_through_known_peers() {
+        let path = std::env::temp_dir().join("hypernova_rejoin_peers.json");
+        let peer_path = std::env::temp_dir().join("hypernova_rejoin_peer_peers.json");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&peer_path);
+        let network = MemoryNetwork::new();
+        
+        let peer = P2PNetwork::new(0)
+            .unwrap()
+            .with_transport(network.transport())
+            .with_address_book(AddressBook::open(&peer_path));
+        peer.start().unwrap();
+        let address = peer.local_addr().unwrap().to_string();
+        
+        let node = P2PNetwork::new(0)
+            .unwrap()
+            .with_transport(network.transport())
+            .with_address_book(AddressBook::open(&path));
+        node.start().unwrap();
+        node.connect_async(&address).await.unwrap();
+        assert!(wait_for(|| peer.get_peers().contains(&node.public_key())).await);
+        drop(node);
+        
+        // Nodes that connected in were not dialed, so they are not known to be reachable even though they listen
+        assert!(AddressBook::open(&peer_path).is_empty());
+        
+        // A node started with the saved address book dials the peer without any bootnode
+        let restarted = P2PNetwork::new(0)
+            .unwrap()
+            .with_transport(network.transport())
+            .with_config(NetworkConfig { discovery_interval: Duration::from_millis(100), ..NetworkConfig::default() })
+            .with_address_book(AddressBook::open(&path));
+        restarted.start().unwrap();
+        
+        assert!(wait_for(|| restarted.get_peers().contains(&peer.public_key())).await);
+        assert_eq!(AddressBook::open(&path).get(&peer.public_key()).unwrap().successes, 2);
+    }
+}
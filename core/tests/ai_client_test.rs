@@ -0,0 +1,149 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use serde_json::{json, Value};
+    use hypernova_core::{
+        ai_client::{AiClient, AiClientConfig, BlockData},
+        scoring::{AiServiceScorer, ScoringContext, TransactionScorer},
+        spec::ChainSpec,
+        state::ChainState,
+        transaction::{Transaction, TransactionType},
+    };
+    use crate::common::{self, Requests};
+    
+    /// Serve canned (status, delay, body) responses in order, repeating the last one
+    fn mock_service(responses: Vec<(u16, Duration, Value)>) -> (String, Requests) {
+        common::mock_ai_service(move |index, _, _| responses[index.min(responses.len() - 1)].clone())
+    }
+    
+    fn client(endpoint: &str) -> AiClient {
+        AiClient::with_config(endpoint, AiClientConfig {
+            timeout: Duration::from_millis(200),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(10),
+        })
+    }
+    
+    fn block_data() -> BlockData {
+        BlockData {
+            height: 7,
+            prev_hash: "ab".repeat(32),
+            timestamp: 1700000000,
+            transactions: Vec::new(),
+        }
+    }
+    
+    fn proof() -> Value {
+        json!({
+            "nonce": 42,
+            "hash": "00ff",
+            "confidence": 0.91,
+            "timestamp": 1700000001,
+            "model_id": "consensus-v1",
+            "model_hash": "ab".repeat(32),
+            "is_valid": true,
+        })
+    }
+    
+    #[tokio::test]
+    async fn test_generate_and_verify_proof() {
+        let (endpoint, requests) = mock_service(vec![
+            (200, Duration::ZERO, proof()),
+            (200, Duration::ZERO, json!({ "is_valid": true })),
+        ]);
+        let client = client(&endpoint);
+        
+        let generated = client.generate_proof(&block_data(), Some("consensus-v1")).await.unwrap();
+        assert_eq!(generated.nonce, 42);
+        assert_eq!(generated.model_id, "consensus-v1");
+        assert!((generated.confidence - 0.91).abs() < f64::EPSILON);
+        
+        assert!(client.verify_proof(&block_data(), &generated).await.unwrap());
+        
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, "/consensus/generate_proof");
+        assert_eq!(requests[0].1["block_data"]["height"], 7);
+        assert_eq!(requests[0].1["model_id"], "consensus-v1");
+        assert_eq!(requests[1].0, "/consensus/verify_proof");
+        assert_eq!(requests[1].1["proof"]["nonce"], 42);
+    }
+    
+    #[tokio::test]
+    async fn test_server_errors_and_timeouts_are_retried() {
+        let (endpoint, requests) = mock_service(vec![
+            (500, Duration::ZERO, json!({ "detail": "model busy" })),
+            (200, Duration::from_secs(2), proof()),
+            (200, Duration::ZERO, proof()),
+        ]);
+        
+        let generated = client(&endpoint).generate_proof(&block_data(), None).await.unwrap();
+        assert_eq!(generated.nonce, 42);
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let (endpoint, requests) = mock_service(vec![(503, Duration::ZERO, json!({}))]);
+        
+        assert!(client(&endpoint).generate_proof(&block_data(), None).await.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_rejected_requests_are_not_retried() {
+        let (endpoint, requests) = mock_service(vec![(404, Duration::ZERO, json!({ "detail": "Model not found" }))]);
+        
+        let error = client(&endpoint).generate_proof(&block_data(), Some("missing")).await.unwrap_err();
+        assert!(error.to_string().contains("Model not found"));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_ai_service_scorer() {
+        let (endpoint, requests) = mock_service(vec![(200, Duration::ZERO, json!({
+            "model_id": "anomaly-v1",
+            "predictions": [[0.8]],
+            "inference_time": 0.01,
+            "timestamp": 1700000000,
+        }))]);
+        let scorer = AiServiceScorer::new(client(&endpoint), "anomaly-v1");
+        
+        let state = ChainState::genesis(&ChainSpec {
+            genesis_accounts: vec![common::genesis_account("alice", 5000)],
+            ..ChainSpec::default()
+        }).unwrap();
+        let tx = Transaction::new(TransactionType::Transfer, "alice".to_string(), Some("bob".to_string()), Some(250), None, 1, 0);
+        
+        let score = scorer.score(&tx, &ScoringContext { state: &state, pending: &[] }).unwrap();
+        assert_eq!(score.score_bps, 8000);
+        
+        // Features: amount, fee, nonce, sender balance, sender nonce, recipient balance, pending from sender
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, "/models/predict");
+        assert_eq!(requests[0].1["model_id"], "anomaly-v1");
+        assert_eq!(requests[0].1["input_data"], json!([[250, 1, 0, 5000, 0, 0, 0]]));
+    }
+    
+    #[tokio::test]
+    async fn test_ai_service_scorer_on_current_thread_runtime() {
+        let (endpoint, requests) = mock_service(vec![(200, Duration::ZERO, json!({
+            "model_id": "anomaly-v1",
+            "predictions": [0.25],
+            "inference_time": 0.01,
+            "timestamp": 1700000000,
+        }))]);
+        let scorer = AiServiceScorer::new(client(&endpoint), "anomaly-v1");
+        let state = ChainState::genesis(&ChainSpec::default()).unwrap();
+        let tx = Transaction::new(TransactionType::Transfer, "alice".to_string(), Some("bob".to_string()), Some(250), None, 1, 0);
+        let context = ScoringContext { state: &state, pending: &[] };
+        
+        // Blocking would stall the only worker thread, so the sync scorer fails without a request
+        assert!(scorer.score(&tx, &context).is_err());
+        assert!(requests.lock().unwrap().is_empty());
+        
+        let score = scorer.score_async(&tx, &context).await.unwrap();
+        assert_eq!(score.score_bps, 2500);
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sha2::{Sha256, Digest};
+    use hypernova_core::{
+        ai_client::BlockData,
+        ai_model::{extract_features, Activation, DenseLayer, QuantizedModel, FEATURE_COUNT, FEATURE_TOLERANCE, ONE},
+        block::Block,
+        transaction::{Transaction, TransactionType},
+    };
+    
+    fn block_data() -> BlockData {
+        BlockData {
+            height: 1_000_000,
+            prev_hash: format!("8{}", "0".repeat(63)),
+            timestamp: 1_500_000_000,
+            transactions: vec![
+                json!({ "amount": 250000 }),
+                json!({ "amount": 250000 }),
+                json!({}),
+                json!({}),
+            ],
+        }
+    }
+    
+    /// Two-layer model: relu(height - 1/4), relu(-prev_hash) -> hard_sigmoid(sum)
+    fn test_model() -> QuantizedModel {
+        let mut first = vec![0; FEATURE_COUNT];
+        first[0] = ONE;
+        let mut second = vec![0; FEATURE_COUNT];
+        second[4] = -ONE;
+        
+        QuantizedModel::new(vec![
+            DenseLayer {
+                weights: vec![first, second],
+                biases: vec![-ONE / 4, 0],
+                activation: Activation::Relu,
+            },
+            DenseLayer {
+                weights: vec![vec![ONE, ONE]],
+                biases: vec![0],
+                activation: Activation::HardSigmoid,
+            },
+        ]).unwrap()
+    }
+    
+    #[test]
+    fn test_features_follow_prepare_input_layout() {
+        let features = extract_features(&block_data()).unwrap();
+        
+        // height / 1e6, timestamp / 1e9, tx count / 1000, volume / 1e6, prev hash prefix / 16^16
+        assert_eq!(features[0], ONE);
+        assert_eq!(features[1], ONE + ONE / 2);
+        assert_eq!(features[2], 4 * ONE / 1000);
+        assert_eq!(features[3], ONE / 2);
+        assert_eq!(features[4], ONE / 2);
+        assert!(features[5..].iter().all(|feature| *feature == 0));
+        
+        let mut invalid = block_data();
+        invalid.prev_hash = "zz".repeat(32);
+        assert!(extract_features(&invalid).is_err());
+        
+        // Amounts that are not unsigned integers are rejected rather than counted as zero
+        for amount in [json!(-1), json!(2.5), json!("250000")] {
+            let mut invalid = block_data();
+            invalid.transactions[2] = json!({ "amount": amount });
+            assert!(extract_features(&invalid).is_err(), "{}", amount);
+        }
+    }
+    
+    #[test]
+    fn test_amountless_transactions_count_as_zero() {
+        let unjail = Transaction::new(TransactionType::Unjail, "validator1".to_string(), None, None, None, 1, 0);
+        let transfer = Transaction::new(TransactionType::Transfer, "alice".to_string(), Some("bob".to_string()), Some(500000), None, 1, 0);
+        let block = Block::new("ab".repeat(32), 1, vec![unjail, transfer], 0);
+        
+        // Serialized blocks carry "amount": null for amountless transactions
+        let block_data = BlockData::from_block(&block);
+        assert_eq!(block_data.transactions[0]["amount"], json!(null));
+        
+        let features = extract_features(&block_data).unwrap();
+        assert_eq!(features[2], 2 * ONE / 1000);
+        assert_eq!(features[3], ONE / 2);
+    }
+    
+    #[test]
+    fn test_features_are_within_tolerance_of_the_service() {
+        let cases = [
+            (1u64, 1u64, 0usize, 0u64, "0000000000000001"),
+            (123_457, 1_700_000_000, 3, 999_999, "0123456789abcdef"),
+            (999_999_999, 4_102_444_800, 999, 123_456_789_012, "fedcba9876543210"),
+            (7, 33, 17, 1, "ffffffffffffffff"),
+        ];
+        
+        for (height, timestamp, tx_count, volume, prefix) in cases {
+            let mut transactions = vec![json!({}); tx_count];
+            transactions.push(json!({ "amount": volume }));
+            let block_data = BlockData {
+                height,
+                prev_hash: format!("{}{}", prefix, "0".repeat(48)),
+                timestamp,
+                transactions,
+            };
+            let features = extract_features(&block_data).unwrap();
+            
+            // `_prepare_input` divides in double precision and stores float32
+            let service = [
+                height as f64 / 1e6,
+                timestamp as f64 / 1e9,
+                (tx_count + 1) as f64 / 1000.0,
+                volume as f64 / 1e6,
+                u64::from_str_radix(prefix, 16).unwrap() as f64 / 16f64.powi(16),
+            ];
+            
+            for (index, expected) in service.iter().enumerate() {
+                let expected = f64::from(*expected as f32);
+                let actual = features[index] as f64 / ONE as f64;
+                let tolerance = expected.abs() * FEATURE_TOLERANCE + 1.0 / ONE as f64;
+                assert!((actual - expected).abs() <= tolerance, "feature {} of {:?}: {} vs {}", index, block_data, actual, expected);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_forward_pass_is_bit_exact() {
+        let model = test_model();
+        let features = extract_features(&block_data()).unwrap();
+        
+        // hard_sigmoid(3/4) = 3/4 / 5 + 1/2, rounded down at every step
+        assert_eq!(model.forward(&features), vec![2791728742]);
+        assert_eq!(model.evaluate(&block_data(), 0).unwrap(), 6499);
+        assert_eq!(model.evaluate(&block_data(), 50).unwrap(), 9749);
+        assert_eq!(model.evaluate(&block_data(), 150).unwrap(), 9749);
+        
+        // Confidence is capped at certainty
+        assert_eq!(model.evaluate(&block_data(), 99).unwrap(), 10000);
+    }
+    
+    #[test]
+    fn test_model_encoding_and_hash() {
+        let model = test_model();
+        let bytes = model.to_bytes();
+        
+        assert_eq!(QuantizedModel::from_bytes(&bytes).unwrap(), model);
+        assert_eq!(model.hash(), <[u8; 32]>::from(Sha256::digest(&bytes)));
+        
+        let path = std::env::temp_dir().join("hypernova_ai_model.bin");
+        std::fs::write(&path, &bytes).unwrap();
+        assert_eq!(QuantizedModel::from_file(&path).unwrap(), model);
+        
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(QuantizedModel::from_bytes(&trailing).is_err());
+        
+        // Layers must consume the outputs of the previous layer
+        let mut layers = model.layers;
+        layers[1].weights[0].push(ONE);
+        assert!(QuantizedModel::new(layers.clone()).is_err());
+        assert!(QuantizedModel::from_bytes(&QuantizedModel { layers }.to_bytes()).is_err());
+    }
+}
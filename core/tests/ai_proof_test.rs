@@ -0,0 +1,182 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use hypernova_core::{
+        ai_client::BlockData,
+        ai_model::{Activation, DenseLayer, QuantizedModel, FEATURE_COUNT, ONE},
+        ai_proof::AIProof,
+        block::Block,
+        chain::Blockchain,
+        consensus::{Consensus, ProofOfAI},
+        crypto::KeyPair,
+        model_registry::ModelSubmission,
+        spec::ChainSpec,
+    };
+    use crate::common;
+    
+    /// Genesis entry approving a model for consensus
+    fn genesis_model(model_id: &str, model: &QuantizedModel) -> ModelSubmission {
+        ModelSubmission {
+            model_id: model_id.to_string(),
+            model_hash: hex::encode(model.hash()),
+            architecture: "dense(10x1,linear)".to_string(),
+        }
+    }
+    
+    /// Create a chain with validator1 registered under a key
+    fn test_chain(name: &str, keypair: &KeyPair) -> Blockchain {
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            genesis_accounts: vec![common::genesis_account("validator1", 10000)],
+            genesis_models: vec![
+                genesis_model("full", &constant_model(ONE)),
+                genesis_model("strong", &constant_model(ONE / 4 * 3)),
+                genesis_model("weak", &constant_model(ONE / 10 * 7)),
+            ],
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain(&format!("ai_proof_{}", name), spec);
+        
+        let tx = common::registration_tx("validator1", keypair.public_key_hex(), 5000);
+        blockchain.add_block(blockchain.build_block(vec![tx]).unwrap()).unwrap();
+        
+        blockchain
+    }
+    
+    /// Model whose confidence ignores the block
+    fn constant_model(confidence: i64) -> QuantizedModel {
+        QuantizedModel::new(vec![DenseLayer {
+            weights: vec![vec![0; FEATURE_COUNT]],
+            biases: vec![confidence],
+            activation: Activation::Linear,
+        }]).unwrap()
+    }
+    
+    /// Build a block with a proof from a model, claiming its real confidence unless overridden
+    fn block_with_proof(
+        blockchain: &Blockchain,
+        keypair: &KeyPair,
+        model_id: &str,
+        model: &QuantizedModel,
+        claimed_bps: Option<u16>,
+    ) -> Block {
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        block.header.producer = Some("validator1".to_string());
+        
+        let confidence_bps = claimed_bps.unwrap_or_else(|| model.evaluate(&BlockData::from_block(&block), 0).unwrap());
+        let mut proof = AIProof::new(&block.header, model_id.to_string(), model.hash(), 0, confidence_bps);
+        proof.sign(keypair);
+        block.add_ai_proof(proof.to_bytes());
+        block
+    }
+    
+    #[test]
+    fn test_proof_encoding_is_canonical() {
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = test_chain("encoding", &keypair);
+        let block = block_with_proof(&blockchain, &keypair, "full", &constant_model(ONE), Some(9000));
+        
+        let bytes = block.ai_proof.unwrap();
+        let proof = AIProof::from_bytes(&bytes).unwrap();
+        assert_eq!(proof.model_id, "full");
+        assert_eq!(proof.confidence_bps, 9000);
+        assert_eq!(proof.to_bytes(), bytes);
+        
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(AIProof::from_bytes(&trailing).is_err());
+        assert!(AIProof::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        
+        let mut overconfident = proof;
+        overconfident.confidence_bps = 10001;
+        assert!(AIProof::from_bytes(&overconfident.to_bytes()).is_err());
+    }
+    
+    #[test]
+    fn test_proof_must_commit_to_header_and_meet_threshold() {
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = test_chain("threshold", &keypair);
+        let strong = constant_model(ONE / 4 * 3);
+        let weak = constant_model(ONE / 10 * 7);
+        let proof_of_ai = ProofOfAI::new("http://127.0.0.1:9", 0.75)
+            .with_model(strong.clone())
+            .with_model(weak.clone());
+        
+        let block = block_with_proof(&blockchain, &keypair, "strong", &strong, None);
+        proof_of_ai.validate_block(&block, &blockchain).unwrap();
+        
+        // Changing the header after the proof was made breaks the commitment
+        let mut tampered = block.clone();
+        tampered.header.timestamp += 1;
+        assert!(proof_of_ai.validate_block(&tampered, &blockchain).is_err());
+        
+        // Confidence below the threshold is rejected
+        let weak_block = block_with_proof(&blockchain, &keypair, "weak", &weak, None);
+        assert!(proof_of_ai.validate_block(&weak_block, &blockchain).is_err());
+        
+        // The claimed confidence must match the local evaluation of the model
+        let overclaimed = block_with_proof(&blockchain, &keypair, "weak", &weak, Some(9000));
+        assert!(proof_of_ai.validate_block(&overclaimed, &blockchain).is_err());
+        
+        // Models must be loaded locally and approved on chain under the proof's id
+        let unknown_model = block_with_proof(&blockchain, &keypair, "full", &constant_model(ONE), None);
+        assert!(proof_of_ai.validate_block(&unknown_model, &blockchain).is_err());
+        
+        let mislabelled = block_with_proof(&blockchain, &keypair, "weak", &strong, None);
+        assert!(proof_of_ai.validate_block(&mislabelled, &blockchain).is_err());
+        
+        let unregistered = block_with_proof(&blockchain, &keypair, "model1", &strong, None);
+        assert!(proof_of_ai.validate_block(&unregistered, &blockchain).is_err());
+        
+        // The proof must be signed by the producer's registered key
+        let other = KeyPair::generate().unwrap();
+        let forged = block_with_proof(&blockchain, &other, "strong", &strong, None);
+        assert!(proof_of_ai.validate_block(&forged, &blockchain).is_err());
+        
+        let mut unknown = block;
+        unknown.header.producer = Some("validator2".to_string());
+        assert!(proof_of_ai.validate_block(&unknown, &blockchain).is_err());
+    }
+    
+    #[test]
+    fn test_producers_outside_the_verification_committee_can_prove_blocks() {
+        let keys = [KeyPair::generate().unwrap(), KeyPair::generate().unwrap()];
+        let addresses = ["validator1", "validator2"];
+        let model = constant_model(ONE);
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            poai_committee_size: 1,
+            genesis_accounts: addresses
+                .iter()
+                .map(|address| common::genesis_account(address, 10000))
+                .collect(),
+            genesis_models: vec![genesis_model("full", &model)],
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain("ai_proof_committee", spec);
+        let registrations = addresses
+            .iter()
+            .zip(&keys)
+            .map(|(address, keypair)| common::registration_tx(address, keypair.public_key_hex(), 5000))
+            .collect();
+        blockchain.add_block(blockchain.build_block(registrations).unwrap()).unwrap();
+        let proof_of_ai = ProofOfAI::new("http://127.0.0.1:9", 0.75).with_model(model.clone());
+        
+        // Only one validator is drawn to re-verify the block, but either may produce and prove it
+        let committee = blockchain.get_verification_committee(2).unwrap();
+        assert_eq!(committee.len(), 1);
+        for (address, keypair) in addresses.iter().zip(&keys) {
+            let mut block = blockchain.build_block(Vec::new()).unwrap();
+            block.header.producer = Some(address.to_string());
+            let confidence_bps = model.evaluate(&BlockData::from_block(&block), 0).unwrap();
+            let mut proof = AIProof::new(&block.header, "full".to_string(), model.hash(), 0, confidence_bps);
+            proof.sign(keypair);
+            block.add_ai_proof(proof.to_bytes());
+            
+            proof_of_ai.validate_block(&block, &blockchain).unwrap();
+        }
+    }
+}
@@ -0,0 +1,128 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use hypernova_core::{
+        beacon::{self, RandomBeacon},
+        block::Block,
+        chain::Blockchain,
+        crypto::KeyPair,
+        epoch::ValidatorSetEntry,
+        spec::ChainSpec,
+    };
+    use crate::common;
+    
+    fn test_chain(name: &str, validators: &[(&str, &KeyPair)]) -> Blockchain {
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            poai_committee_size: 2,
+            genesis_accounts: validators
+                .iter()
+                .map(|(address, _)| common::genesis_account(address, 10000))
+                .collect(),
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain(&format!("beacon_{}", name), spec);
+        
+        let registrations = validators
+            .iter()
+            .map(|(address, keypair)| common::registration_tx(address, keypair.public_key_hex(), 5000))
+            .collect();
+        blockchain.add_block(blockchain.build_block(registrations).unwrap()).unwrap();
+        
+        blockchain
+    }
+    
+    fn contributed_block(blockchain: &Blockchain, producer: &str, keypair: &KeyPair) -> Block {
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        blockchain.contribute_randomness(&mut block, producer, keypair).unwrap();
+        block.sign_as(producer, keypair);
+        block
+    }
+    
+    #[test]
+    fn test_commit_reveal_mixes_randomness() {
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = test_chain("commit_reveal", &[("validator1", &keypair)]);
+        let initial = blockchain.get_randomness_at(1).unwrap();
+        
+        // The first block only commits, leaving the output unchanged
+        let block = contributed_block(&blockchain, "validator1", &keypair);
+        assert!(block.header.randao_reveal.is_none());
+        blockchain.add_block(block).unwrap();
+        assert_eq!(blockchain.get_randomness_at(2).unwrap(), initial);
+        
+        // Producing again without revealing is rejected
+        let mut hiding = blockchain.build_block(Vec::new()).unwrap();
+        hiding.sign_as("validator1", &keypair);
+        assert!(blockchain.add_block(hiding).is_err());
+        
+        // A wrong reveal is rejected
+        let mut wrong = contributed_block(&blockchain, "validator1", &keypair);
+        wrong.header.randao_reveal = Some(beacon::derive_secret(&keypair, 3));
+        wrong.sign_as("validator1", &keypair);
+        assert!(blockchain.add_block(wrong).is_err());
+        
+        // The reveal is mixed into the output
+        let block = contributed_block(&blockchain, "validator1", &keypair);
+        let reveal = block.header.randao_reveal.clone().unwrap();
+        blockchain.add_block(block).unwrap();
+        assert_eq!(blockchain.get_randomness_at(3).unwrap(), beacon::mix(&initial, &reveal).unwrap());
+    }
+    
+    #[test]
+    fn test_commitment_must_be_signed_by_producer() {
+        let keypair = KeyPair::generate().unwrap();
+        let other = KeyPair::generate().unwrap();
+        let blockchain = test_chain("forged_commitment", &[("validator1", &keypair)]);
+        
+        // A commitment claiming validator1 produced the block but signed by another key
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        block.header.randao_commitment = Some(beacon::commitment(&beacon::derive_secret(&other, 2)).unwrap());
+        block.sign_as("validator1", &other);
+        assert!(blockchain.add_block(block).is_err());
+        
+        let mut beacon_state = RandomBeacon::genesis("test");
+        assert!(beacon_state.apply("validator1", Some(&beacon::derive_secret(&keypair, 1)), None, 1).is_err());
+    }
+    
+    #[test]
+    fn test_shuffle_and_committee_selection() {
+        let validators: Vec<ValidatorSetEntry> = (0..10)
+            .map(|i| ValidatorSetEntry {
+                address: format!("validator{}", i),
+                public_key: String::new(),
+                stake: 1000,
+            })
+            .collect();
+        let seed = beacon::genesis_randomness("test");
+        
+        // The same seed always selects the same members, drawn from the set
+        let committee = beacon::select_committee(&validators, &seed, 4);
+        assert_eq!(committee, beacon::select_committee(&validators, &seed, 4));
+        assert_eq!(committee.len(), 4);
+        assert!(committee.iter().all(|member| validators.contains(member)));
+        
+        let other_seed = beacon::genesis_randomness("other");
+        let mut shuffled = validators.clone();
+        beacon::shuffle(&mut shuffled, &other_seed);
+        assert_ne!(shuffled, validators);
+        shuffled.sort_by(|a, b| a.address.cmp(&b.address));
+        let mut sorted = validators.clone();
+        sorted.sort_by(|a, b| a.address.cmp(&b.address));
+        assert_eq!(shuffled, sorted);
+        
+        // Committees on chain come from the block's validator set
+        let key1 = KeyPair::generate().unwrap();
+        let key2 = KeyPair::generate().unwrap();
+        let key3 = KeyPair::generate().unwrap();
+        let blockchain = test_chain("committee", &[("validator1", &key1), ("validator2", &key2), ("validator3", &key3)]);
+        blockchain.add_block(blockchain.build_block(Vec::new()).unwrap()).unwrap();
+        
+        let committee = blockchain.get_verification_committee(3).unwrap();
+        let set = blockchain.get_validator_set_at(3).unwrap();
+        assert_eq!(committee.len(), 2);
+        assert!(committee.iter().all(|member| set.contains(member)));
+    }
+}
@@ -0,0 +1,201 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use serde_json::{json, Value};
+use hypernova_core::{
+    ai_model::{Activation, DenseLayer, QuantizedModel, FEATURE_COUNT, ONE},
+    chain::Blockchain,
+    model_registry::ModelSubmission,
+    spec::{self, ChainSpec, GenesisAccount},
+    staking::ValidatorRegistration,
+    transaction::{Transaction, TransactionType},
+};
+
+/// Open a chain in a fresh data directory named after the test
+pub fn test_chain(name: &str, spec: ChainSpec) -> Blockchain {
+    let dir = std::env::temp_dir().join(format!("hypernova_{}", name));
+    let _ = std::fs::remove_dir_all(&dir);
+    
+    Blockchain::with_spec(dir.to_str().unwrap(), spec).unwrap()
+}
+
+/// Genesis account whose transactions are signed with the development key of its address
+pub fn genesis_account(address: &str, balance: u64) -> GenesisAccount {
+    GenesisAccount {
+        address: address.to_string(),
+        balance,
+        public_key: Some(spec::dev_keypair(address).public_key_hex()),
+    }
+}
+
+/// Sign a transaction with the development key of its sender
+pub fn signed(mut tx: Transaction) -> Transaction {
+    tx.sign_with(&spec::dev_keypair(&tx.from));
+    tx
+}
+
+/// Signed transfer between two accounts
+pub fn transfer(from: &str, to: &str, amount: u64, nonce: u64) -> Transaction {
+    signed(Transaction::new(
+        TransactionType::Transfer,
+        from.to_string(),
+        Some(to.to_string()),
+        Some(amount),
+        None,
+        0,
+        nonce,
+    ))
+}
+
+/// Signed registration of a validator without commission, as the account's first transaction
+pub fn registration_tx(address: &str, public_key: String, self_stake: u64) -> Transaction {
+    let registration = ValidatorRegistration {
+        public_key,
+        quantum_public_key: None,
+        commission_bps: 0,
+    };
+    
+    signed(Transaction::new(
+        TransactionType::ValidatorRegistration,
+        address.to_string(),
+        None,
+        Some(self_stake),
+        Some(registration.encode()),
+        0,
+        0,
+    ))
+}
+
+/// Number each sender's transactions in order, starting from its next account nonce
+pub fn with_nonces(blockchain: &Blockchain, mut transactions: Vec<Transaction>) -> Vec<Transaction> {
+    let mut nonces = HashMap::new();
+    for tx in &mut transactions {
+        let nonce = nonces.entry(tx.from.clone()).or_insert_with(|| blockchain.get_account(&tx.from).nonce);
+        tx.nonce = *nonce;
+        *nonce += 1;
+    }
+    transactions.into_iter().map(signed).collect()
+}
+
+/// Poll a condition for up to two seconds, returning whether it became true
+pub async fn wait_for<F: Fn() -> bool>(condition: F) -> bool {
+    for _ in 0..100 {
+        if condition() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    condition()
+}
+
+/// Requests received by a mock AI service, as (path, body)
+pub type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+/// Start a local stand-in for the AI service
+///
+/// `respond` is given the index, path and body of each request and returns the
+/// status, delay and body of the response.
+pub fn mock_ai_service<F>(respond: F) -> (String, Requests)
+where
+    F: Fn(usize, &str, &Value) -> (u16, Duration, Value) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let requests: Requests = Arc::new(Mutex::new(Vec::new()));
+    let received = requests.clone();
+    
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+            
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = line.to_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            
+            let (status, delay, response) = {
+                let mut requests = received.lock().unwrap();
+                let response = respond(requests.len(), &path, &body);
+                requests.push((path, body));
+                response
+            };
+            
+            let mut stream = stream;
+            thread::spawn(move || {
+                thread::sleep(delay);
+                let payload = response.to_string();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    payload.len(),
+                    payload
+                );
+            });
+        }
+    });
+    
+    (endpoint, requests)
+}
+
+/// Model that is always 90% confident
+pub fn consensus_model() -> QuantizedModel {
+    QuantizedModel::new(vec![DenseLayer {
+        weights: vec![vec![0; FEATURE_COUNT]],
+        biases: vec![ONE / 10 * 9],
+        activation: Activation::Linear,
+    }]).unwrap()
+}
+
+/// Genesis approval of the consensus model as "test_model"
+pub fn consensus_model_submission() -> ModelSubmission {
+    ModelSubmission {
+        model_id: "test_model".to_string(),
+        model_hash: hex::encode(consensus_model().hash()),
+        architecture: "dense(10x1,linear)".to_string(),
+    }
+}
+
+/// Start a local stand-in for the AI service proving with a model
+pub fn mock_proof_service(model: &QuantizedModel) -> String {
+    let model_hash = hex::encode(model.hash());
+    
+    let (endpoint, _) = mock_ai_service(move |_, path, _| {
+        let response = if path == "/consensus/generate_proof" {
+            json!({
+                "nonce": 1,
+                "hash": "00ff",
+                "confidence": 0.9,
+                "timestamp": 0,
+                "model_id": "test_model",
+                "model_hash": model_hash,
+                "is_valid": true,
+            })
+        } else {
+            json!({ "is_valid": true })
+        };
+        (200, Duration::ZERO, response)
+    });
+    
+    endpoint
+}
@@ -1,18 +1,50 @@
+mod common;
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
     use hypernova_core::{
-        block::Block,
         chain::Blockchain,
-        consensus::{Consensus, ProofOfAI, DelegatedProofOfStake},
+        consensus::{Consensus, ProofOfAI, DelegatedProofOfStake, HybridConsensus},
+        crypto::KeyPair,
+        spec::{ChainSpec, ConsensusComponents},
+        staking::ValidatorRegistration,
+        transaction::{Transaction, TransactionType},
     };
-
+    use crate::common;
+    
+    /// Create a chain with validator1 registered under a key and scheduled from height 2
+    fn validator_chain(name: &str, keypair: &KeyPair, consensus: ConsensusComponents) -> Blockchain {
+        let tx = common::registration_tx("validator1", keypair.public_key_hex(), 5000);
+        registered_chain(name, tx, consensus)
+    }
+    
+    /// Create a chain with validator1 registered by a transaction and scheduled from height 2
+    fn registered_chain(name: &str, registration: Transaction, consensus: ConsensusComponents) -> Blockchain {
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            consensus,
+            genesis_accounts: vec![common::genesis_account("validator1", 10000)],
+            genesis_models: vec![common::consensus_model_submission()],
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain(&format!("consensus_{}", name), spec);
+        blockchain.add_block(blockchain.build_block(vec![registration]).unwrap()).unwrap();
+        
+        blockchain
+    }
+    
     #[test]
     fn test_proof_of_ai_consensus() {
-        // Create a ProofOfAI instance
-        let proof_of_ai = ProofOfAI::new("test_model", 0.75);
+        // Create a mock blockchain with a registered validator
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = validator_chain("poai", &keypair, ConsensusComponents::default());
         
-        // Create a mock blockchain
-        let blockchain = Blockchain::new("test_data").unwrap();
+        // Create a ProofOfAI instance producing as that validator
+        let proof_of_ai = ProofOfAI::new(&common::mock_proof_service(&common::consensus_model()), 0.75)
+            .with_validator("validator1", keypair)
+            .with_model(common::consensus_model());
         
         // Create a block
         let block = proof_of_ai.create_block(&blockchain).unwrap();
@@ -44,31 +76,124 @@ mod tests {
     
     #[test]
     fn test_hybrid_consensus() {
-        // Create a ProofOfAI instance
-        let proof_of_ai = ProofOfAI::new("test_model", 0.75);
-        
-        // Create a DPoS instance
-        let dpos = DelegatedProofOfStake::new(21, 1000);
-        
-        // Create a mock blockchain
-        let blockchain = Blockchain::new("test_data").unwrap();
-        
-        // Create a block using ProofOfAI
-        let block = proof_of_ai.create_block(&blockchain).unwrap();
-        
-        // Validate the block using DPoS
-        let result = dpos.validate_block(&block, &blockchain);
-        
-        // The validation should fail because the block doesn't have a validator signature
-        assert!(result.is_err());
-        
-        // Create a block using DPoS
-        let block = dpos.create_block(&blockchain).unwrap();
-        
-        // Validate the block using ProofOfAI
-        let result = proof_of_ai.validate_block(&block, &blockchain);
-        
-        // The validation should fail because the block doesn't have an AI proof
-        assert!(result.is_err());
+        // Create a mock blockchain with a registered validator
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = validator_chain("hybrid", &keypair, ConsensusComponents::default());
+        
+        // Create a hybrid instance producing as the scheduled validator
+        let proof_of_ai = ProofOfAI::new(&common::mock_proof_service(&common::consensus_model()), 0.75).with_model(common::consensus_model());
+        let hybrid = HybridConsensus::new(proof_of_ai).with_validator("validator1", keypair);
+        
+        // The block carries both a validator signature and an AI proof
+        let block = hybrid.create_block(&blockchain).unwrap();
+        assert!(block.validator_signature.is_some());
+        assert!(block.ai_proof.is_some());
+        hybrid.validate_block(&block, &blockchain).unwrap();
+        
+        // Neither a DPoS signature nor an AI proof is enough on its own
+        let mut unsigned = block.clone();
+        unsigned.validator_signature = None;
+        assert!(hybrid.validate_block(&unsigned, &blockchain).is_err());
+        
+        let mut unproven = block.clone();
+        unproven.ai_proof = None;
+        assert!(hybrid.validate_block(&unproven, &blockchain).is_err());
+        
+        // Only the validator scheduled for the slot may produce the block
+        let other = KeyPair::generate().unwrap();
+        let mut unscheduled = block.clone();
+        unscheduled.sign_as("validator2", &other);
+        assert!(hybrid.validate_block(&unscheduled, &blockchain).is_err());
+        
+        blockchain.add_block(block).unwrap();
+    }
+    
+    #[test]
+    fn test_hybrid_consensus_components() {
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = validator_chain("hybrid_components", &keypair, ConsensusComponents {
+            scheduled_producer: true,
+            ai_proof: false,
+            quantum_signature: true,
+        });
+        
+        // A scheduled, signed block needs no AI proof but lacks a quantum signature
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        block.sign_as("validator1", &keypair);
+        
+        let hybrid = HybridConsensus::new(ProofOfAI::new("http://127.0.0.1:9", 0.75));
+        let error = hybrid.validate_block(&block, &blockchain).unwrap_err();
+        assert!(error.to_string().contains("quantum"));
+        
+        // Producing fails while quantum-resistant signing is unavailable
+        let hybrid = hybrid.with_validator("validator1", keypair);
+        assert!(hybrid.create_block(&blockchain).is_err());
+    }
+    
+    #[test]
+    fn test_quantum_signature_must_be_quantum_resistant() {
+        let keypair = KeyPair::generate().unwrap();
+        let registration = ValidatorRegistration {
+            public_key: keypair.public_key_hex(),
+            quantum_public_key: Some(keypair.public_key_hex()),
+            commission_bps: 0,
+        };
+        let tx = common::signed(Transaction::new(
+            TransactionType::ValidatorRegistration,
+            "validator1".to_string(),
+            None,
+            Some(5000),
+            Some(registration.encode()),
+            0,
+            0,
+        ));
+        let blockchain = registered_chain("hybrid_classical_quantum", tx, ConsensusComponents {
+            scheduled_producer: true,
+            ai_proof: false,
+            quantum_signature: true,
+        });
+        
+        // An Ed25519 signature passed off as the quantum one is rejected
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        block.sign_as("validator1", &keypair);
+        block.quantum_signature = Some(keypair.sign(block.hash().as_bytes()));
+        
+        let hybrid = HybridConsensus::new(ProofOfAI::new("http://127.0.0.1:9", 0.75));
+        let error = hybrid.validate_block(&block, &blockchain).unwrap_err();
+        assert!(error.to_string().contains("quantum"), "{}", error);
+    }
+    
+    #[test]
+    fn test_chain_checks_added_blocks_with_its_consensus_engine() {
+        let keypair = KeyPair::generate().unwrap();
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            consensus: ConsensusComponents {
+                scheduled_producer: true,
+                ai_proof: false,
+                quantum_signature: false,
+            },
+            genesis_accounts: vec![common::genesis_account("validator1", 10000)],
+            ..ChainSpec::default()
+        };
+        let hybrid = HybridConsensus::new(ProofOfAI::new("http://127.0.0.1:9", 0.75));
+        let blockchain = common::test_chain("consensus_engine", spec).with_consensus(Arc::new(hybrid));
+        
+        // Blocks without a producer bootstrap the chain until validator1 is elected
+        let registration = common::registration_tx("validator1", keypair.public_key_hex(), 5000);
+        blockchain.add_block(blockchain.build_block(vec![registration]).unwrap()).unwrap();
+        let unproduced = blockchain.build_block(Vec::new()).unwrap();
+        assert!(blockchain.add_block(unproduced).is_err());
+        
+        // Afterwards only the scheduled validator's blocks are added
+        let mut forged = blockchain.build_block(Vec::new()).unwrap();
+        forged.sign_as("validator2", &KeyPair::generate().unwrap());
+        assert!(blockchain.add_block(forged).is_err());
+        
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        block.sign_as("validator1", &keypair);
+        blockchain.add_block(block).unwrap();
+        assert_eq!(blockchain.get_height(), 2);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,80 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use hypernova_core::{
+        chain::Blockchain,
+        consensus::DevConsensus,
+        spec::{ChainSpec, DEV_ACCOUNT_BALANCE},
+    };
+    use crate::common::{self, transfer};
+    
+    fn dev_chain(name: &str) -> Blockchain {
+        common::test_chain(&format!("dev_{}", name), ChainSpec::dev())
+    }
+    
+    #[test]
+    fn test_instant_seal() {
+        let blockchain = dev_chain("instant");
+        let consensus = DevConsensus::instant();
+        
+        // Nothing is sealed while the pool is empty
+        assert!(consensus.seal(&blockchain).unwrap().is_none());
+        assert_eq!(blockchain.get_height(), 0);
+        
+        blockchain.add_transaction(transfer("alice", "bob", 100, 0)).unwrap();
+        let block = consensus.seal(&blockchain).unwrap().unwrap();
+        
+        assert_eq!(block.header.height, 1);
+        assert_eq!(blockchain.get_balance("alice"), DEV_ACCOUNT_BALANCE - 100);
+        assert_eq!(blockchain.get_balance("bob"), DEV_ACCOUNT_BALANCE + 100);
+        assert!(blockchain.get_pending_transactions().is_empty());
+        
+        // A transaction cannot be replayed once its nonce is used
+        let replay = transfer("alice", "bob", 100, 0);
+        assert!(blockchain.add_transaction(replay.clone()).is_err());
+        assert!(blockchain.add_block(blockchain.build_block(vec![replay]).unwrap()).is_err());
+        
+        // Accounts without a key cannot sign anything
+        assert!(blockchain.add_transaction(transfer("nobody", "bob", 100, 0)).is_err());
+        
+        // Transactions that cannot apply are dropped without blocking the rest
+        blockchain.add_transaction(transfer("eve", "bob", DEV_ACCOUNT_BALANCE + 1, 0)).unwrap();
+        blockchain.add_transaction(transfer("charlie", "dave", 50, 0)).unwrap();
+        consensus.seal(&blockchain).unwrap();
+        
+        assert_eq!(blockchain.get_balance("dave"), DEV_ACCOUNT_BALANCE + 50);
+        assert_eq!(blockchain.get_balance("eve"), DEV_ACCOUNT_BALANCE);
+        assert!(blockchain.get_pending_transactions().is_empty());
+    }
+    
+    #[test]
+    fn test_interval_seal() {
+        let blockchain = dev_chain("interval");
+        let consensus = DevConsensus::interval(Duration::from_millis(10));
+        
+        // Interval sealing produces empty blocks too
+        consensus.seal(&blockchain).unwrap().unwrap();
+        consensus.seal(&blockchain).unwrap().unwrap();
+        assert_eq!(blockchain.get_height(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_seals_when_transactions_arrive() {
+        let blockchain = Arc::new(dev_chain("run"));
+        let sealing_chain = blockchain.clone();
+        tokio::spawn(async move { DevConsensus::instant().run(sealing_chain).await });
+        
+        blockchain.add_transaction(transfer("alice", "eve", 1, 0)).unwrap();
+        
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while blockchain.get_height() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        
+        assert_eq!(blockchain.get_balance("eve"), DEV_ACCOUNT_BALANCE + 1);
+    }
+}
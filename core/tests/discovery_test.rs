@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use hypernova_core::{
+        addressbook::AddressBook,
+        crypto::KeyPair,
+        discovery::{self, DiscoveryMessage, NodeRecord, RoutingTable, BUCKET_SIZE},
+        handshake::{ChainStatus, HandshakeMessage, Hello, SERVICE_FULL_NODE},
+        network::{NetworkConfig, P2PNetwork},
+        noise::{self, NoiseKey, SecureSender},
+    };
+    use tokio::net::TcpStream;
+    
+    fn record(id: [u8; 32]) -> NodeRecord {
+        NodeRecord {
+            public_key: hex::encode(id),
+            address: "127.0.0.1:1".to_string(),
+        }
+    }
+    
+    /// Connect to a node without running a network, returning the stream and the sending half of the channel
+    async fn raw_peer(address: &str) -> (TcpStream, SecureSender) {
+        let keypair = KeyPair::generate().unwrap();
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        let channel = noise::handshake(&mut stream, true, &NoiseKey::generate().unwrap(), &keypair).await.unwrap();
+        let (mut sender, mut receiver) = channel.split();
+        
+        let hello = Hello::new(&keypair, ChainStatus::default(), SERVICE_FULL_NODE);
+        sender.send(&mut stream, &HandshakeMessage::Hello(hello).encode()).await.unwrap();
+        receiver.receive(&mut stream, 4096).await.unwrap().unwrap();
+        sender.send(&mut stream, &HandshakeMessage::Ready.encode()).await.unwrap();
+        receiver.receive(&mut stream, 4096).await.unwrap().unwrap();
+        
+        (stream, sender)
+    }
+    
+    /// Send a discovery message, which travels on channel 1
+    async fn send_discovery(stream: &mut TcpStream, sender: &mut SecureSender, message: DiscoveryMessage) {
+        let mut data = vec![1];
+        data.extend(message.encode());
+        sender.send(stream, &data).await.unwrap();
+    }
+    
+    #[test]
+    fn test_routing_table_buckets_and_lookups() {
+        let mut table = RoutingTable::new([0; 32]);
+        assert!(!table.insert(record([0; 32])));
+        
+        // Ids with the first bit set share the farthest bucket, which holds a limited number of nodes
+        let far = |last: u8| {
+            let mut id = [0; 32];
+            id[0] = 0x80;
+            id[31] = last;
+            id
+        };
+        for last in 0..BUCKET_SIZE as u8 {
+            assert!(table.insert(record(far(last))));
+        }
+        assert!(!table.insert(record(far(0xff))));
+        assert!(table.insert(record(far(0))));
+        let mut near = [0; 32];
+        near[31] = 1;
+        assert!(table.insert(record(near)));
+        assert_eq!(table.len(), BUCKET_SIZE + 1);
+        
+        // Lookups return the nodes closest to the target by XOR distance
+        assert_eq!(table.closest(&[0; 32], 1), vec![record(near)]);
+        assert_eq!(table.closest(&far(3), 2), vec![record(far(3)), record(far(2))]);
+        
+        // Each non-empty bucket gets a refresh target inside it, then not again until stale
+        let mut targets = table.refresh_targets(Duration::from_secs(60), &mut rand::thread_rng());
+        targets.sort();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0][..31], [0; 31]);
+        assert_eq!(targets[0][31], 1);
+        assert!(targets[1][0] & 0x80 != 0);
+        assert!(table.refresh_targets(Duration::from_secs(60), &mut rand::thread_rng()).is_empty());
+        
+        table.remove(&hex::encode(near));
+        assert!(table.get(&hex::encode(near)).is_none());
+        assert_eq!(discovery::distance(&far(1), &far(2)), {
+            let mut id = [0; 32];
+            id[31] = 3;
+            id
+        });
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_nodes_discover_each_other_through_a_bootnode() {
+        let config = |bootnodes: Vec<String>| NetworkConfig {
+            max_peers: 10,
+            target_outbound: 3,
+            bootnodes,
+            discovery_interval: Duration::from_millis(100),
+            ..NetworkConfig::default()
+        };
+        
+        let bootnode = P2PNetwork::new(0).unwrap().with_config(config(Vec::new()));
+        bootnode.start().unwrap();
+        let bootnode_address = format!("127.0.0.1:{}", bootnode.local_addr().unwrap().port());
+        
+        let nodes: Vec<P2PNetwork> = (1..=4u8)
+            .map(|seed| {
+                let node = P2PNetwork::new(0)
+                    .unwrap()
+                    .with_node_key(KeyPair::from_seed(&[seed; 32]).unwrap())
+                    .with_config(config(vec![bootnode_address.clone()]));
+                node.start().unwrap();
+                node
+            })
+            .collect();
+        
+        // Every node learns of every other one and dials until it has three outbound peers
+        let everyone_known = || {
+            nodes.iter().all(|node| node.known_nodes().len() == nodes.len())
+                && nodes.iter().all(|node| node.get_peers().len() >= 3)
+        };
+        for _ in 0..100 {
+            if everyone_known() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(everyone_known());
+        
+        for node in &nodes {
+            assert!(node.known_nodes().iter().all(|record| record.public_key != node.public_key()));
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lookup_replies_must_be_requested_and_small() {
+        let node = P2PNetwork::new(0).unwrap();
+        node.start().unwrap();
+        let address = format!("127.0.0.1:{}", node.local_addr().unwrap().port());
+        let mut inbound = node.take_inbound().unwrap();
+        let (mut stream, mut sender) = raw_peer(&address).await;
+        
+        // Nodes sent without a lookup asking for them are not learned
+        let nodes: Vec<NodeRecord> = (1..=3u8).map(|last| {
+            let mut id = [0x80; 32];
+            id[31] = last;
+            record(id)
+        }).collect();
+        send_discovery(&mut stream, &mut sender, DiscoveryMessage::Nodes { nodes: nodes.clone() }).await;
+        sender.send(&mut stream, &[0, 42]).await.unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(5), inbound.recv()).await.unwrap().unwrap();
+        assert_eq!(message.data, vec![42]);
+        assert!(node.known_nodes().is_empty());
+        
+        // Replies larger than a bucket break the protocol
+        let nodes = nodes.into_iter().cycle().take(BUCKET_SIZE + 1).collect();
+        send_discovery(&mut stream, &mut sender, DiscoveryMessage::Nodes { nodes }).await;
+        for _ in 0..100 {
+            if node.get_peers().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(node.get_peers().is_empty());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_known_nodes_must_answer_with_their_key() {
+        let impostor = P2PNetwork::new(0).unwrap();
+        impostor.start().unwrap();
+        let address = format!("127.0.0.1:{}", impostor.local_addr().unwrap().port());
+        
+        // The node remembers another node at the address the impostor now answers at
+        let mut book = AddressBook::new();
+        let expected = KeyPair::generate().unwrap().public_key_hex();
+        book.record_success(&NodeRecord { public_key: expected, address: address.clone() }, SERVICE_FULL_NODE);
+        let node = P2PNetwork::new(0)
+            .unwrap()
+            .with_config(NetworkConfig { discovery_interval: Duration::from_millis(50), ..NetworkConfig::default() })
+            .with_address_book(book);
+        node.start().unwrap();
+        
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(node.get_peers().is_empty());
+        assert!(impostor.get_peers().is_empty());
+        
+        // Dialing the address without expecting a key still works
+        assert_eq!(node.connect(&address).unwrap(), impostor.public_key());
+    }
+}
@@ -0,0 +1,183 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use serde_json::json;
+    use hypernova_core::{
+        chain::Blockchain,
+        epoch::{self, validator_set_hash, ValidatorSetEntry},
+        rpc::{RpcRequest, RpcServer},
+        spec::ChainSpec,
+        transaction::Transaction,
+    };
+    use crate::common;
+    
+    fn test_chain(name: &str) -> Blockchain {
+        let spec = ChainSpec {
+            epoch_length: 3,
+            min_self_stake: 1000,
+            // Test blocks are unsigned, so liveness is not enforced
+            liveness_threshold_bps: 0,
+            genesis_accounts: vec![
+                common::genesis_account("validator1", 5000),
+                common::genesis_account("validator2", 5000),
+            ],
+            ..ChainSpec::default()
+        };
+        
+        common::test_chain(&format!("epoch_{}", name), spec)
+    }
+    
+    fn registration_tx(address: &str, self_stake: u64) -> Transaction {
+        common::registration_tx(address, "11".repeat(32), self_stake)
+    }
+    
+    fn add_block(blockchain: &Blockchain, transactions: Vec<Transaction>) {
+        let block = blockchain.build_block(transactions).unwrap();
+        blockchain.add_block(block).unwrap();
+    }
+    
+    // Sets are in shuffled schedule order, so compare them sorted
+    fn addresses(blockchain: &Blockchain, height: u64) -> Vec<String> {
+        let mut addresses: Vec<String> = blockchain
+            .get_validator_set_at(height)
+            .unwrap()
+            .into_iter()
+            .map(|validator| validator.address)
+            .collect();
+        addresses.sort();
+        addresses
+    }
+    
+    #[test]
+    fn test_chain_spec_is_validated() {
+        let dir = std::env::temp_dir().join("hypernova_epoch_invalid_spec");
+        let spec = ChainSpec { epoch_length: 0, ..ChainSpec::default() };
+        
+        assert!(spec.validate().is_err());
+        assert!(Blockchain::with_spec(dir.to_str().unwrap(), spec).is_err());
+        assert!(ChainSpec::dev().validate().is_ok());
+    }
+    
+    #[test]
+    fn test_chain_spec_files_may_omit_later_fields() {
+        let mut json = serde_json::to_value(ChainSpec::dev()).unwrap();
+        for field in ["genesis_timestamp", "min_model_stake", "governance_voting_period", "governance_threshold_bps", "poai_committee_size", "slot_duration"] {
+            json.as_object_mut().unwrap().remove(field);
+        }
+        
+        let spec: ChainSpec = serde_json::from_value(json).unwrap();
+        let defaults = ChainSpec::default();
+        assert_eq!(spec.genesis_timestamp, 0);
+        assert_eq!(spec.min_model_stake, defaults.min_model_stake);
+        assert_eq!(spec.governance_voting_period, defaults.governance_voting_period);
+        assert_eq!(spec.governance_threshold_bps, defaults.governance_threshold_bps);
+        assert_eq!(spec.poai_committee_size, defaults.poai_committee_size);
+        assert_eq!(spec.slot_duration, defaults.slot_duration);
+    }
+    
+    #[test]
+    fn test_slots_pass_to_the_next_validator_after_each_round() {
+        let validators: Vec<ValidatorSetEntry> = ["a", "b", "c"]
+            .iter()
+            .map(|address| ValidatorSetEntry {
+                address: address.to_string(),
+                public_key: String::new(),
+                stake: 1000,
+            })
+            .collect();
+        let leader = |height, round| epoch::slot_leader(&validators, height, round).unwrap().address.as_str();
+        
+        assert_eq!(leader(4, 0), "b");
+        assert_eq!(leader(4, 1), "c");
+        assert_eq!(leader(4, 2), "a");
+        assert_eq!(leader(4, 3), "b");
+        assert_eq!(leader(u64::MAX, u64::MAX), "a");
+        
+        let spec = ChainSpec { slot_duration: 10, ..ChainSpec::default() };
+        assert_eq!(spec.slot_round(100, 109), 0);
+        assert_eq!(spec.slot_round(100, 125), 2);
+        assert_eq!(spec.slot_round(100, 90), 0);
+        assert_eq!(ChainSpec { slot_duration: 0, ..spec }.slot_round(100, 1000), 0);
+    }
+    
+    #[test]
+    fn test_mid_epoch_registration_waits_for_next_epoch() {
+        let blockchain = test_chain("queued");
+        
+        add_block(&blockchain, vec![registration_tx("validator1", 2000)]);
+        
+        // The registration is not part of epoch 0, and epoch 1 is not elected yet
+        assert!(addresses(&blockchain, 1).is_empty());
+        assert!(blockchain.get_validator_set_at(3).is_none());
+        
+        add_block(&blockchain, Vec::new());
+        assert_eq!(addresses(&blockchain, 3), vec!["validator1".to_string()]);
+        assert!(addresses(&blockchain, 2).is_empty());
+        
+        // A registration during epoch 1 only joins in epoch 2
+        add_block(&blockchain, vec![registration_tx("validator2", 3000)]);
+        add_block(&blockchain, Vec::new());
+        assert_eq!(addresses(&blockchain, 4), vec!["validator1".to_string()]);
+        assert!(blockchain.get_validator_set_at(6).is_none());
+        
+        add_block(&blockchain, Vec::new());
+        assert_eq!(addresses(&blockchain, 6), vec!["validator1".to_string(), "validator2".to_string()]);
+    }
+    
+    #[test]
+    fn test_epoch_boundary_header_commits_to_validator_set() {
+        let blockchain = test_chain("commitment");
+        
+        add_block(&blockchain, vec![registration_tx("validator1", 2000)]);
+        add_block(&blockchain, Vec::new());
+        
+        let block = blockchain.build_block(Vec::new()).unwrap();
+        let expected = validator_set_hash(&blockchain.get_validator_set_at(3).unwrap());
+        assert_eq!(block.header.validator_set_hash, Some(expected));
+        
+        // A missing or wrong commitment is rejected
+        let mut missing = block.clone();
+        missing.header.validator_set_hash = None;
+        assert!(blockchain.add_block(missing).is_err());
+        
+        let mut wrong = block.clone();
+        wrong.header.validator_set_hash = Some(validator_set_hash(&[]));
+        assert!(blockchain.add_block(wrong).is_err());
+        
+        blockchain.add_block(block).unwrap();
+        
+        // Blocks inside an epoch carry no commitment
+        let mut inside = blockchain.build_block(Vec::new()).unwrap();
+        assert!(inside.header.validator_set_hash.is_none());
+        inside.header.validator_set_hash = Some(validator_set_hash(&[]));
+        assert!(blockchain.add_block(inside).is_err());
+    }
+    
+    #[test]
+    fn test_historical_validator_set_is_served_over_rpc() {
+        let blockchain = Arc::new(test_chain("history"));
+        
+        add_block(&blockchain, vec![registration_tx("validator1", 2000)]);
+        for _ in 0..5 {
+            add_block(&blockchain, Vec::new());
+        }
+        
+        let rpc = RpcServer::new(blockchain.clone(), 0);
+        let query = |params| {
+            rpc.handle(RpcRequest {
+                jsonrpc: "2.0".to_string(),
+                method: "hnc_getValidatorSet".to_string(),
+                params,
+                id: json!(1),
+            })
+        };
+        
+        assert_eq!(query(json!([1])).result.unwrap(), json!([]));
+        assert_eq!(query(json!([4])).result.unwrap()[0]["address"], "validator1");
+        assert_eq!(query(json!([])).result.unwrap()[0]["stake"], 2000);
+        assert_eq!(query(json!([100])).result.unwrap(), json!(null));
+        assert!(query(json!(["latest"])).error.is_some());
+    }
+}
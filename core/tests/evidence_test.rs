@@ -0,0 +1,130 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use hypernova_core::{
+        block::Block,
+        chain::Blockchain,
+        consensus::DelegatedProofOfStake,
+        crypto::KeyPair,
+        spec::ChainSpec,
+        staking::ValidatorStatus,
+    };
+    use crate::common;
+    
+    fn test_chain(name: &str, keypair: &KeyPair) -> Blockchain {
+        let spec = ChainSpec {
+            min_self_stake: 1000,
+            genesis_accounts: vec![common::genesis_account("validator1", 10000)],
+            ..ChainSpec::default()
+        };
+        
+        let blockchain = common::test_chain(&format!("evidence_{}", name), spec);
+        
+        let tx = common::registration_tx("validator1", keypair.public_key_hex(), 10000);
+        blockchain.add_block(blockchain.build_block(vec![tx]).unwrap()).unwrap();
+        
+        blockchain
+    }
+    
+    fn signed_block(blockchain: &Blockchain, keypair: &KeyPair, shard_id: u32) -> Block {
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        block.header.shard_id = shard_id;
+        block.sign_as("validator1", keypair);
+        block
+    }
+    
+    #[test]
+    fn test_double_sign_is_detected_and_slashed_once() {
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = test_chain("slash_once", &keypair);
+        
+        // Two different blocks signed at height 2
+        let block_a = signed_block(&blockchain, &keypair, 0);
+        let block_b = signed_block(&blockchain, &keypair, 1);
+        
+        assert!(blockchain.observe_block(&block_a).is_none());
+        let evidence = blockchain.observe_block(&block_b).unwrap();
+        assert_eq!(evidence.height(), 2);
+        assert_eq!(blockchain.get_pending_evidence().len(), 1);
+        
+        // Include the evidence in the next block
+        let mut block = block_a;
+        block.set_evidence(blockchain.get_pending_evidence());
+        block.sign_as("validator1", &keypair);
+        blockchain.add_block(block).unwrap();
+        
+        let record = blockchain.get_validator("validator1").unwrap();
+        assert_eq!(record.status, ValidatorStatus::Tombstoned);
+        assert_eq!(record.slashed, 500);
+        assert!(blockchain.get_pending_evidence().is_empty());
+        
+        // The same infraction cannot be punished again
+        let mut replay = blockchain.build_block(Vec::new()).unwrap();
+        replay.set_evidence(vec![evidence.clone()]);
+        assert!(blockchain.add_block(replay).is_err());
+        assert!(blockchain.add_evidence(evidence).is_err());
+    }
+    
+    #[test]
+    fn test_slashed_validator_stops_producing_within_the_epoch() {
+        let keypair = KeyPair::generate().unwrap();
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            genesis_accounts: vec![common::genesis_account("validator1", 10000)],
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain("evidence_active_set", spec);
+        let registration = common::registration_tx("validator1", keypair.public_key_hex(), 10000);
+        blockchain.add_block(blockchain.build_block(vec![registration]).unwrap()).unwrap();
+        
+        let dpos = DelegatedProofOfStake::new(21, 1000);
+        dpos.refresh_validators(&blockchain).unwrap();
+        assert_eq!(dpos.get_validators(), vec!["validator1".to_string()]);
+        
+        // Double-signing in the first block of epoch 1 removes validator1 before the epoch ends
+        let block_a = signed_block(&blockchain, &keypair, 0);
+        blockchain.observe_block(&block_a);
+        let evidence = blockchain.observe_block(&signed_block(&blockchain, &keypair, 1)).unwrap();
+        let mut block = block_a;
+        block.set_evidence(vec![evidence]);
+        block.sign_as("validator1", &keypair);
+        blockchain.add_block(block).unwrap();
+        
+        dpos.refresh_validators(&blockchain).unwrap();
+        assert!(dpos.get_validators().is_empty());
+    }
+    
+    #[test]
+    fn test_far_future_block_does_not_hide_double_signs() {
+        let keypair = KeyPair::generate().unwrap();
+        let blockchain = test_chain("far_future", &keypair);
+        
+        // A signed block claiming a height far beyond the tip is not remembered
+        let mut future = signed_block(&blockchain, &keypair, 0);
+        future.header.height = u64::MAX;
+        future.sign_as("validator1", &keypair);
+        assert!(blockchain.observe_block(&future).is_none());
+        
+        // So a double-sign at the real next height is still caught
+        assert!(blockchain.observe_block(&signed_block(&blockchain, &keypair, 0)).is_none());
+        let evidence = blockchain.observe_block(&signed_block(&blockchain, &keypair, 1)).unwrap();
+        assert_eq!(evidence.height(), 2);
+    }
+    
+    #[test]
+    fn test_forged_evidence_is_rejected() {
+        let keypair = KeyPair::generate().unwrap();
+        let other = KeyPair::generate().unwrap();
+        let blockchain = test_chain("forged", &keypair);
+        
+        // Blocks signed by someone else's key claiming validator1 produced them
+        let block_a = signed_block(&blockchain, &other, 0);
+        let block_b = signed_block(&blockchain, &other, 1);
+        
+        assert!(blockchain.observe_block(&block_a).is_none());
+        assert!(blockchain.observe_block(&block_b).is_none());
+        assert!(blockchain.get_pending_evidence().is_empty());
+    }
+}
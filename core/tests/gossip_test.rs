@@ -0,0 +1,267 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use hypernova_core::{
+        block::Block,
+        chain::{Blockchain, ChainEvent},
+        consensus::DevConsensus,
+        crypto::KeyPair,
+        gossip::{GossipMessage, GossipValidator, SeenCache, Topic, Verdict, Vote},
+        network::P2PNetwork,
+        reputation::PeerEvent,
+        spec::{ChainSpec, ConsensusComponents},
+    };
+    use crate::common::{self, transfer, wait_for};
+    use tokio::sync::mpsc::Receiver;
+    
+    struct RejectBlocks;
+    
+    impl GossipValidator for RejectBlocks {
+        fn validate(&self, message: &GossipMessage) -> Verdict {
+            match message.topic {
+                Topic::Blocks => Verdict::Reject,
+                _ => Verdict::Accept,
+            }
+        }
+    }
+    
+    fn dev_chain(name: &str) -> Blockchain {
+        common::test_chain(&format!("gossip_{}", name), ChainSpec::dev())
+    }
+    
+    /// Create a chain scheduling validator1 and validator2 from height 2
+    fn scheduled_chain(name: &str, keys: &[KeyPair; 2]) -> Blockchain {
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            consensus: ConsensusComponents {
+                scheduled_producer: true,
+                ai_proof: false,
+                quantum_signature: false,
+            },
+            genesis_accounts: vec![
+                common::genesis_account("validator1", 5000),
+                common::genesis_account("validator2", 5000),
+            ],
+            ..ChainSpec::default()
+        };
+        let chain = common::test_chain(&format!("gossip_{}", name), spec);
+        
+        // Blocks nobody produced are only accepted before any validator is elected
+        let registrations = vec![
+            common::registration_tx("validator1", keys[0].public_key_hex(), 3000),
+            common::registration_tx("validator2", keys[1].public_key_hex(), 3000),
+        ];
+        assert_eq!(chain.validate(&block_gossip(&chain.build_block(registrations).unwrap())), Verdict::Accept);
+        assert_eq!(chain.validate(&block_gossip(&chain.build_block(Vec::new()).unwrap())), Verdict::Reject);
+        
+        chain
+    }
+    
+    fn block_gossip(block: &Block) -> GossipMessage {
+        GossipMessage::new(Topic::Blocks, serde_json::to_vec(block).unwrap())
+    }
+    
+    /// Start a line of networks, each connected to the next
+    fn line(networks: Vec<P2PNetwork>) -> Vec<P2PNetwork> {
+        for network in &networks {
+            network.start().unwrap();
+        }
+        for pair in networks.windows(2) {
+            pair[0].connect(&format!("127.0.0.1:{}", pair[1].local_addr().unwrap().port())).unwrap();
+        }
+        networks
+    }
+    
+    async fn next_gossip(gossip: &mut Receiver<GossipMessage>) -> Option<GossipMessage> {
+        tokio::time::timeout(Duration::from_millis(500), gossip.recv()).await.ok().flatten()
+    }
+    
+    #[test]
+    fn test_seen_cache_forgets_oldest_ids() {
+        let mut seen = SeenCache::new(2);
+        assert!(seen.insert("a"));
+        assert!(!seen.insert("a"));
+        assert!(seen.insert("b"));
+        assert!(seen.insert("c"));
+        
+        assert_eq!(seen.len(), 2);
+        assert!(!seen.contains("a"));
+        assert!(seen.contains("b") && seen.contains("c"));
+        assert!(seen.insert("a"));
+        
+        let message = GossipMessage::new(Topic::Blocks, b"block".to_vec());
+        assert_eq!(GossipMessage::decode(&message.encode()).unwrap(), message);
+        assert_ne!(message.id(), GossipMessage::new(Topic::Votes, b"block".to_vec()).id());
+        assert!(GossipMessage::decode(&[9, 1, 2]).is_err());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gossip_reaches_the_network_once() {
+        let networks = line((0..3).map(|_| P2PNetwork::new(0).unwrap()).collect());
+        let mut receivers: Vec<_> = networks.iter().map(|network| network.take_gossip().unwrap()).collect();
+        
+        // A message published at one end is forwarded hop by hop to the other
+        let id = networks[0].publish(Topic::Transactions, b"tx").unwrap();
+        for receiver in &mut receivers[1..] {
+            let message = next_gossip(receiver).await.unwrap();
+            assert_eq!(message, GossipMessage::new(Topic::Transactions, b"tx".to_vec()));
+            assert_eq!(message.id(), id);
+        }
+        
+        // Publishing again, or hearing the message back, delivers nothing new
+        assert_eq!(networks[0].publish(Topic::Transactions, b"tx").unwrap(), id);
+        for receiver in &mut receivers {
+            assert!(next_gossip(receiver).await.is_none());
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_invalid_gossip_is_not_forwarded() {
+        let networks = line(vec![
+            P2PNetwork::new(0).unwrap(),
+            P2PNetwork::new(0).unwrap().with_gossip_validator(Arc::new(RejectBlocks)),
+            P2PNetwork::new(0).unwrap(),
+        ]);
+        let mut last = networks[2].take_gossip().unwrap();
+        
+        networks[0].publish(Topic::Transactions, b"tx").unwrap();
+        assert_eq!(next_gossip(&mut last).await.unwrap().topic, Topic::Transactions);
+        
+        // The validating node drops invalid blocks, banning the peer that keeps sending them
+        let sender = networks[0].public_key();
+        networks[0].publish(Topic::Blocks, b"block").unwrap();
+        assert!(next_gossip(&mut last).await.is_none());
+        assert!(networks[1].get_peers().contains(&sender));
+        assert_eq!(networks[1].peer_score(&sender), 1 + PeerEvent::InvalidGossip.score_change());
+        
+        for block in [b"block 2", b"block 3"] {
+            networks[0].publish(Topic::Blocks, block).unwrap();
+        }
+        assert!(next_gossip(&mut last).await.is_none());
+        assert!(!networks[1].get_peers().contains(&sender));
+        assert_eq!(networks[1].banned_peers()[0].public_key, sender);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gossiped_transactions_and_blocks_are_imported() {
+        let producer = Arc::new(dev_chain("producer"));
+        let follower = Arc::new(dev_chain("follower"));
+        let networks = line(vec![
+            P2PNetwork::new(0).unwrap().with_gossip_validator(producer.clone()),
+            P2PNetwork::new(0).unwrap().with_gossip_validator(follower.clone()),
+        ]);
+        let mut gossip = networks[1].take_gossip().unwrap();
+        let mut events = follower.subscribe();
+        
+        let tx = transfer("alice", "bob", 100, 0);
+        producer.add_transaction(tx.clone()).unwrap();
+        networks[0].publish(Topic::Transactions, &serde_json::to_vec(&tx).unwrap()).unwrap();
+        next_gossip(&mut gossip).await.unwrap();
+        assert_eq!(follower.get_pending_transactions().len(), 1);
+        
+        // The sealed block is imported and clears the transaction from the pool
+        let block = DevConsensus::instant().seal(&producer).unwrap().unwrap();
+        networks[0].publish(Topic::Blocks, &serde_json::to_vec(&block).unwrap()).unwrap();
+        next_gossip(&mut gossip).await.unwrap();
+        assert_eq!(follower.get_latest_block().unwrap().hash(), block.hash());
+        assert!(follower.get_pending_transactions().is_empty());
+        
+        // Both are announced to subscribers, so the node gossips them on
+        assert!(matches!(events.try_recv(), Ok(ChainEvent::Transaction(received)) if received.hash() == tx.hash()));
+        assert!(matches!(events.try_recv(), Ok(ChainEvent::Block(received)) if received.hash() == block.hash()));
+        
+        // Blocks that do not apply are dropped without blaming the peer
+        networks[0].publish(Topic::Blocks, &serde_json::to_vec(&block).unwrap()).unwrap();
+        let mut stale = block.clone();
+        stale.header.height = 9;
+        networks[0].publish(Topic::Blocks, &serde_json::to_vec(&stale).unwrap()).unwrap();
+        assert!(next_gossip(&mut gossip).await.is_none());
+        assert_eq!(networks[1].get_peers().len(), 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_forged_blocks_are_rejected_and_unplaceable_ones_ignored() {
+        let producer = dev_chain("forged_producer");
+        let follower = Arc::new(dev_chain("forged_follower"));
+        let networks = line(vec![
+            P2PNetwork::new(0).unwrap(),
+            P2PNetwork::new(0).unwrap().with_gossip_validator(follower.clone()),
+        ]);
+        let sender = networks[0].public_key();
+        let publish = |block: &Block| networks[0].publish(Topic::Blocks, &serde_json::to_vec(block).unwrap()).unwrap();
+        
+        producer.add_transaction(transfer("alice", "bob", 100, 0)).unwrap();
+        let block = DevConsensus::instant().seal(&producer).unwrap().unwrap();
+        
+        // A block whose parent the node does not have may be from a peer ahead of it
+        let mut orphan = block.clone();
+        orphan.header.prev_hash = "00".repeat(32);
+        publish(&orphan);
+        
+        // Blocks whose roots do not match their contents are forged, and only they cost the peer
+        let mut tampered = block.clone();
+        tampered.transactions.clear();
+        publish(&tampered);
+        assert!(wait_for(|| networks[1].peer_score(&sender) == PeerEvent::InvalidGossip.score_change()).await);
+        
+        // So are blocks not signed by a registered producer
+        let mut unsigned = block.clone();
+        unsigned.sign_as("validator1", &KeyPair::generate().unwrap());
+        publish(&unsigned);
+        assert!(wait_for(|| networks[1].banned_peers().iter().any(|ban| ban.public_key == sender)).await);
+        assert_eq!(follower.get_height(), 0);
+    }
+    
+    #[test]
+    fn test_blocks_from_validators_out_of_turn_are_rejected() {
+        let keys = [KeyPair::generate().unwrap(), KeyPair::generate().unwrap()];
+        let chain = scheduled_chain("out_of_turn", &keys);
+        
+        // Both validators are active, but only the scheduled one may produce the next block
+        let block = chain.build_block(Vec::new()).unwrap();
+        let scheduled = chain.get_scheduled_producer_of(&block.header).unwrap().address;
+        let (other, other_key, scheduled_key) = match scheduled.as_str() {
+            "validator1" => ("validator2", &keys[1], &keys[0]),
+            _ => ("validator1", &keys[0], &keys[1]),
+        };
+        
+        let mut out_of_turn = block.clone();
+        out_of_turn.sign_as(other, other_key);
+        assert_eq!(chain.validate(&block_gossip(&out_of_turn)), Verdict::Reject);
+        
+        let mut in_turn = block;
+        in_turn.sign_as(&scheduled, scheduled_key);
+        assert_eq!(chain.validate(&block_gossip(&in_turn)), Verdict::Accept);
+        assert_eq!(chain.get_height(), 2);
+    }
+    
+    #[test]
+    fn test_votes_must_come_from_the_verification_committee() {
+        let keys = [KeyPair::generate().unwrap(), KeyPair::generate().unwrap()];
+        let chain = scheduled_chain("votes", &keys);
+        let gossip = |vote: &Vote| GossipMessage::new(Topic::Votes, serde_json::to_vec(vote).unwrap());
+        
+        let mut block = chain.build_block(Vec::new()).unwrap();
+        let scheduled = chain.get_scheduled_producer_of(&block.header).unwrap().address;
+        let key = if scheduled == "validator1" { &keys[0] } else { &keys[1] };
+        block.sign_as(&scheduled, key);
+        
+        // Votes for a block this node does not have yet cannot be checked
+        let vote = Vote::new("validator1", &block, &keys[0]);
+        assert_eq!(chain.validate(&gossip(&vote)), Verdict::Ignore);
+        
+        chain.add_block(block.clone()).unwrap();
+        assert_eq!(chain.validate(&gossip(&vote)), Verdict::Accept);
+        
+        // Votes signed with another key or by a validator off the committee are forged
+        let forged = Vote::new("validator1", &block, &keys[1]);
+        assert_eq!(chain.validate(&gossip(&forged)), Verdict::Reject);
+        let outsider = Vote::new("validator3", &block, &KeyPair::generate().unwrap());
+        assert_eq!(chain.validate(&gossip(&outsider)), Verdict::Reject);
+        assert_eq!(chain.validate(&GossipMessage::new(Topic::Votes, b"vote".to_vec())), Verdict::Reject);
+    }
+}
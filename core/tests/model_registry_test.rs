@@ -0,0 +1,240 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use hypernova_core::{
+        ai_client::BlockData,
+        ai_model::{Activation, DenseLayer, QuantizedModel, FEATURE_COUNT, ONE},
+        ai_proof::AIProof,
+        block::Block,
+        chain::Blockchain,
+        consensus::{Consensus, ProofOfAI},
+        crypto::KeyPair,
+        governance::{ProposalAction, ProposalStatus, Vote},
+        model_registry::{ModelStatus, ModelSubmission},
+        spec::ChainSpec,
+        state::ChainState,
+        transaction::{Transaction, TransactionType},
+    };
+    use crate::common;
+    
+    fn test_chain(name: &str) -> Blockchain {
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            // Test blocks are unsigned, so liveness is not enforced
+            liveness_threshold_bps: 0,
+            genesis_accounts: ["alice", "validator1", "validator2"]
+                .iter()
+                .map(|address| common::genesis_account(address, 10000))
+                .collect(),
+            ..ChainSpec::default()
+        };
+        
+        common::test_chain(&format!("model_registry_{}", name), spec)
+    }
+    
+    fn signed_tx(tx_type: TransactionType, from: &str, amount: Option<u64>, data: Vec<u8>) -> Transaction {
+        common::signed(Transaction::new(tx_type, from.to_string(), None, amount, Some(data), 0, 0))
+    }
+    
+    fn proposal_tx(action: ProposalAction) -> Transaction {
+        signed_tx(TransactionType::GovernanceProposal, "alice", None, action.encode())
+    }
+    
+    fn vote_tx(voter: &str, proposal_id: u64) -> Transaction {
+        let vote = Vote { proposal_id, approve: true };
+        signed_tx(TransactionType::GovernanceVote, voter, None, vote.encode())
+    }
+    
+    fn add_block(blockchain: &Blockchain, transactions: Vec<Transaction>) {
+        let block = blockchain.build_block(common::with_nonces(blockchain, transactions)).unwrap();
+        blockchain.add_block(block).unwrap();
+    }
+    
+    /// Model that is always 90% confident
+    fn test_model() -> QuantizedModel {
+        QuantizedModel::new(vec![DenseLayer {
+            weights: vec![vec![0; FEATURE_COUNT]],
+            biases: vec![ONE / 10 * 9],
+            activation: Activation::Linear,
+        }]).unwrap()
+    }
+    
+    fn block_with_proof(blockchain: &Blockchain, keypair: &KeyPair, model: &QuantizedModel) -> Block {
+        let mut block = blockchain.build_block(Vec::new()).unwrap();
+        block.header.producer = Some("validator1".to_string());
+        
+        let confidence_bps = model.evaluate(&BlockData::from_block(&block), 0).unwrap();
+        let mut proof = AIProof::new(&block.header, "model1".to_string(), model.hash(), 0, confidence_bps);
+        proof.sign(keypair);
+        block.add_ai_proof(proof.to_bytes());
+        block
+    }
+    
+    #[test]
+    fn test_invalid_genesis_models_are_rejected() {
+        let model = |model_id: &str, model_hash: String| ModelSubmission {
+            model_id: model_id.to_string(),
+            model_hash,
+            architecture: "dense".to_string(),
+        };
+        let valid = ChainSpec {
+            genesis_models: vec![model("first", "ab".repeat(32)), model("second", "cd".repeat(32))],
+            ..ChainSpec::default()
+        };
+        assert!(valid.validate().is_ok());
+        assert_eq!(ChainState::genesis(&valid).unwrap().models().models().count(), 2);
+        
+        // The spec check and the genesis state agree on malformed and duplicate models
+        for genesis_models in [
+            vec![model("first", "ab".to_string())],
+            vec![model("first", "ab".repeat(32)), model("first", "cd".repeat(32))],
+            vec![model("first", "ab".repeat(32)), model("second", "ab".repeat(32))],
+        ] {
+            let spec = ChainSpec { genesis_models, ..ChainSpec::default() };
+            assert!(spec.validate().is_err());
+            assert!(ChainState::genesis(&spec).is_err());
+        }
+    }
+    
+    #[test]
+    fn test_submission_requires_valid_data_and_stake() {
+        let blockchain = test_chain("submission");
+        let model = test_model();
+        
+        let submission = ModelSubmission {
+            model_id: "model1".to_string(),
+            model_hash: hex::encode(model.hash()),
+            architecture: "dense(10x1,linear)".to_string(),
+        };
+        
+        let mut malformed = submission.clone();
+        malformed.model_hash = "abcd".to_string();
+        assert!(ModelSubmission::decode(&malformed.encode()).is_err());
+        
+        // The stake must cover the chain minimum
+        let understaked = blockchain.build_block(common::with_nonces(&blockchain, vec![
+            signed_tx(TransactionType::AIModelSubmission, "alice", Some(999), submission.encode()),
+        ])).unwrap();
+        assert!(blockchain.add_block(understaked).is_err());
+        
+        add_block(&blockchain, vec![
+            signed_tx(TransactionType::AIModelSubmission, "alice", Some(1000), submission.encode()),
+        ]);
+        
+        let record = blockchain.get_model("model1").unwrap();
+        assert_eq!(record.owner, "alice");
+        assert_eq!(record.stake, 1000);
+        assert_eq!(record.status, ModelStatus::Pending);
+        assert_eq!(blockchain.get_balance("alice"), 9000);
+        
+        // Ids and model files can only be registered once
+        let mut renamed = submission;
+        renamed.model_id = "model2".to_string();
+        let duplicate = blockchain.build_block(common::with_nonces(&blockchain, vec![
+            signed_tx(TransactionType::AIModelSubmission, "alice", Some(1000), renamed.encode()),
+        ])).unwrap();
+        assert!(blockchain.add_block(duplicate).is_err());
+    }
+    
+    #[test]
+    fn test_unapproved_models_release_their_stake_and_id() {
+        let spec = ChainSpec {
+            governance_voting_period: 2,
+            genesis_accounts: vec![common::genesis_account("alice", 10000)],
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain("model_registry_expiry", spec);
+        
+        let submission = ModelSubmission {
+            model_id: "model1".to_string(),
+            model_hash: hex::encode(test_model().hash()),
+            architecture: "dense(10x1,linear)".to_string(),
+        };
+        
+        // Submitted at height 1, the proposal at height 2 keeps it pending through height 4
+        add_block(&blockchain, vec![
+            signed_tx(TransactionType::AIModelSubmission, "alice", Some(1000), submission.encode()),
+        ]);
+        add_block(&blockchain, vec![proposal_tx(ProposalAction::ApproveModel {
+            model_id: "model1".to_string(),
+        })]);
+        assert_eq!(blockchain.get_model("model1").unwrap().pending_until, 4);
+        
+        add_block(&blockchain, Vec::new());
+        add_block(&blockchain, Vec::new());
+        assert_eq!(blockchain.get_proposal(0).unwrap().status, ProposalStatus::Voting);
+        assert_eq!(blockchain.get_model("model1").unwrap().status, ModelStatus::Pending);
+        
+        // The proposal is rejected and the model removed once voting ends
+        add_block(&blockchain, Vec::new());
+        assert_eq!(blockchain.get_proposal(0).unwrap().status, ProposalStatus::Rejected);
+        assert!(blockchain.get_model("model1").is_none());
+        assert_eq!(blockchain.get_balance("alice"), 10000);
+        
+        // The id and model file can be submitted again
+        add_block(&blockchain, vec![
+            signed_tx(TransactionType::AIModelSubmission, "alice", Some(1000), submission.encode()),
+        ]);
+        assert_eq!(blockchain.get_model("model1").unwrap().status, ModelStatus::Pending);
+        assert_eq!(blockchain.get_balance("alice"), 9000);
+    }
+    
+    #[test]
+    fn test_governance_approves_and_retires_models() {
+        let blockchain = test_chain("governance");
+        let keypair = KeyPair::generate().unwrap();
+        let model = test_model();
+        let proof_of_ai = ProofOfAI::new("http://127.0.0.1:9", 0.75).with_model(model.clone());
+        
+        let submission = ModelSubmission {
+            model_id: "model1".to_string(),
+            model_hash: hex::encode(model.hash()),
+            architecture: "dense(10x1,linear)".to_string(),
+        };
+        
+        // Validators elected at the end of epoch 0 vote in epoch 1
+        add_block(&blockchain, vec![
+            common::registration_tx("validator1", keypair.public_key_hex(), 5000),
+            common::registration_tx("validator2", KeyPair::generate().unwrap().public_key_hex(), 3000),
+            signed_tx(TransactionType::AIModelSubmission, "alice", Some(1000), submission.encode()),
+        ]);
+        add_block(&blockchain, vec![proposal_tx(ProposalAction::ApproveModel {
+            model_id: "model1".to_string(),
+        })]);
+        
+        // Pending models cannot prove blocks
+        let pending = block_with_proof(&blockchain, &keypair, &model);
+        assert!(proof_of_ai.validate_block(&pending, &blockchain).is_err());
+        
+        // Only members of the current validator set can vote
+        let outsider = blockchain.build_block(common::with_nonces(&blockchain, vec![vote_tx("alice", 0)])).unwrap();
+        assert!(blockchain.add_block(outsider).is_err());
+        
+        // 5000 of 8000 stake is below the two-thirds threshold
+        add_block(&blockchain, vec![vote_tx("validator1", 0)]);
+        assert_eq!(blockchain.get_proposal(0).unwrap().status, ProposalStatus::Voting);
+        
+        add_block(&blockchain, vec![vote_tx("validator2", 0)]);
+        assert_eq!(blockchain.get_proposal(0).unwrap().status, ProposalStatus::Passed);
+        assert_eq!(blockchain.get_model("model1").unwrap().status, ModelStatus::Approved);
+        
+        let approved = block_with_proof(&blockchain, &keypair, &model);
+        proof_of_ai.validate_block(&approved, &blockchain).unwrap();
+        
+        // Retiring the model returns its stake and stops it proving blocks
+        add_block(&blockchain, vec![proposal_tx(ProposalAction::RetireModel {
+            model_id: "model1".to_string(),
+        })]);
+        add_block(&blockchain, vec![vote_tx("validator1", 1), vote_tx("validator2", 1)]);
+        
+        let record = blockchain.get_model("model1").unwrap();
+        assert_eq!(record.status, ModelStatus::Retired);
+        assert_eq!(record.stake, 0);
+        assert_eq!(blockchain.get_balance("alice"), 10000);
+        
+        let retired = block_with_proof(&blockchain, &keypair, &model);
+        assert!(proof_of_ai.validate_block(&retired, &blockchain).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use hypernova_core::crypto::KeyPair;
+    use hypernova_core::handshake::{ChainStatus, SERVICE_FULL_NODE, SERVICE_VALIDATOR};
+    use hypernova_core::network::{InboundMessage, NetworkConfig, P2PNetwork, MAX_MESSAGE_SIZE};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc::Receiver;
+    
+    async fn next_message(inbound: &mut Receiver<InboundMessage>) -> InboundMessage {
+        tokio::time::timeout(Duration::from_secs(5), inbound.recv())
+            .await
+            .expect("no message received")
+            .expect("inbound channel closed")
+    }
+    
+    async fn wait_for_peers(network: &P2PNetwork, count: usize) {
+        for _ in 0..100 {
+            if network.get_peers().len() == count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("expected {} peers, got {:?}", count, network.get_peers());
+    }
+    
+    #[tokio::test]
+    async fn test_connect_on_current_thread_runtime() {
+        let listener = P2PNetwork::new(0).unwrap();
+        listener.start().unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let dialer = P2PNetwork::new(0).unwrap();
+        
+        // Blocking would stall the only worker thread, so only the async call connects
+        assert!(dialer.connect(&address).is_err());
+        assert_eq!(dialer.connect_async(&address).await.unwrap(), listener.public_key());
+        wait_for_peers(&listener, 1).await;
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_peers_exchange_framed_messages() {
+        let listener = P2PNetwork::new(0).unwrap();
+        listener.start().unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let mut listener_inbound = listener.take_inbound().unwrap();
+        assert!(listener.take_inbound().is_none());
+        
+        let dialer = P2PNetwork::new(0).unwrap();
+        let mut dialer_inbound = dialer.take_inbound().unwrap();
+        let peer = dialer.connect(&address).unwrap();
+        assert_eq!(peer, listener.public_key());
+        assert!(dialer.connect(&address).is_err());
+        wait_for_peers(&listener, 1).await;
+        
+        // Messages arrive whole and in order, including large ones
+        let large = vec![7u8; 1024 * 1024];
+        dialer.send(&peer, b"hello").unwrap();
+        dialer.send(&peer, &large).unwrap();
+        let message = next_message(&mut listener_inbound).await;
+        assert_eq!(message, InboundMessage { peer: dialer.public_key(), data: b"hello".to_vec() });
+        assert_eq!(next_message(&mut listener_inbound).await.data, large);
+        
+        // The listener replies to the peer it accepted
+        listener.broadcast(b"welcome").unwrap();
+        let reply = next_message(&mut dialer_inbound).await;
+        assert_eq!(reply, InboundMessage { peer: peer.clone(), data: b"welcome".to_vec() });
+        
+        assert!(dialer.send(&peer, &vec![0u8; MAX_MESSAGE_SIZE + 1]).is_err());
+        assert!(dialer.send(&address, b"unknown").is_err());
+        
+        // Disconnecting closes the connection on both sides
+        dialer.disconnect(&peer).unwrap();
+        assert!(dialer.send(&peer, b"gone").is_err());
+        wait_for_peers(&listener, 0).await;
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_concurrent_handshakes_respect_peer_limit() {
+        let listener = P2PNetwork::new(0)
+            .unwrap()
+            .with_config(NetworkConfig { max_peers: 1, ..NetworkConfig::default() });
+        listener.start().unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        
+        // Handshakes racing past the capacity check still leave a single peer
+        let dialers: Vec<P2PNetwork> = (0..4).map(|_| P2PNetwork::new(0).unwrap()).collect();
+        let results = tokio::join!(
+            dialers[0].connect_async(&address),
+            dialers[1].connect_async(&address),
+            dialers[2].connect_async(&address),
+            dialers[3].connect_async(&address),
+        );
+        assert!([results.0.is_ok(), results.1.is_ok(), results.2.is_ok(), results.3.is_ok()].contains(&true));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(listener.get_peers().len(), 1);
+    }
+    
+    #[test]
+    fn test_network_runs_outside_a_runtime() {
+        let listener = P2PNetwork::new(0).unwrap();
+        listener.start().unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let mut inbound = listener.take_inbound().unwrap();
+        
+        let dialer = P2PNetwork::new(0).unwrap();
+        let peer = dialer.connect(&address).unwrap();
+        dialer.send(&peer, b"ping").unwrap();
+        
+        let message = inbound.blocking_recv().unwrap();
+        assert_eq!(message.data, b"ping");
+        
+        assert!(dialer.connect("127.0.0.1:1").is_err());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_handshake_checks_chain_and_identity() {
+        let status = |chain_id: &str| ChainStatus {
+            chain_id: chain_id.to_string(),
+            genesis_hash: "genesis".to_string(),
+            best_height: 12,
+            best_hash: "best".to_string(),
+        };
+        
+        let listener = P2PNetwork::new(0).unwrap().with_node_key(KeyPair::from_seed(&[1; 32]).unwrap());
+        listener.set_chain_status(status("hypernova-test"));
+        listener.start().unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        
+        // Peers on another chain are turned away with a reason
+        let stranger = P2PNetwork::new(0).unwrap();
+        stranger.set_chain_status(status("other-chain"));
+        let error = stranger.connect(&address).unwrap_err();
+        assert!(error.to_string().contains("wrong chain (code 3)"), "{}", error);
+        assert!(stranger.get_peers().is_empty());
+        
+        // A node holding the same key is treated as this node
+        let twin = P2PNetwork::new(0).unwrap().with_node_key(KeyPair::from_seed(&[1; 32]).unwrap());
+        twin.set_chain_status(status("hypernova-test"));
+        let error = twin.connect(&address).unwrap_err();
+        assert!(error.to_string().contains("connected to self"), "{}", error);
+        
+        // Compatible peers learn each other's identity, chain and services
+        let validator = P2PNetwork::new(0)
+            .unwrap()
+            .with_node_key(KeyPair::from_seed(&[2; 32]).unwrap())
+            .with_services(SERVICE_FULL_NODE | SERVICE_VALIDATOR);
+        validator.set_chain_status(status("hypernova-test"));
+        let peer = validator.connect(&address).unwrap();
+        
+        let info = validator.get_peer_info(&peer).unwrap();
+        assert_eq!(info.address, address);
+        assert_eq!(info.status, status("hypernova-test"));
+        
+        wait_for_peers(&listener, 1).await;
+        let info = listener.get_peer_info(&validator.public_key()).unwrap();
+        assert_eq!(info.services, SERVICE_FULL_NODE | SERVICE_VALIDATOR);
+    }
+    
+    /// Forward connections to a target, recording every byte sent by the client
+    async fn recording_proxy(target: String) -> (String, Arc<Mutex<Vec<u8>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        
+        let log = recorded.clone();
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = listener.accept().await {
+                let mut server = TcpStream::connect(&target).await.unwrap();
+                let log = log.clone();
+                tokio::spawn(async move {
+                    let (mut client_read, mut client_write) = client.split();
+                    let (mut server_read, mut server_write) = server.split();
+                    let upstream = async {
+                        let mut buffer = [0u8; 4096];
+                        while let Ok(read @ 1..) = client_read.read(&mut buffer).await {
+                            log.lock().unwrap().extend_from_slice(&buffer[..read]);
+                            if server_write.write_all(&buffer[..read]).await.is_err() {
+                                break;
+                            }
+                        }
+                    };
+                    let downstream = tokio::io::copy(&mut server_read, &mut client_write);
+                    let _ = tokio::join!(upstream, downstream);
+                });
+            }
+        });
+        
+        (address, recorded)
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_connections_are_encrypted_and_pinned_to_node_keys() {
+        let listener = P2PNetwork::new(0).unwrap();
+        listener.start().unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let mut inbound = listener.take_inbound().unwrap();
+        let (proxy, recorded) = recording_proxy(address.clone()).await;
+        
+        // Nothing readable crosses the wire, not even the node key in the hello
+        let dialer = P2PNetwork::new(0).unwrap();
+        let peer = dialer.connect(&proxy).unwrap();
+        dialer.send(&peer, b"top secret payload").unwrap();
+        assert_eq!(next_message(&mut inbound).await.data, b"top secret payload");
+        
+        let wire = recorded.lock().unwrap().clone();
+        let contains = |needle: &[u8]| wire.windows(needle.len()).any(|window| window == needle);
+        assert!(!contains(b"top secret payload"));
+        assert!(!contains(dialer.public_key().as_bytes()));
+        
+        // The peer is the same node whichever address reaches it
+        let error = dialer.connect(&address).unwrap_err();
+        assert!(error.to_string().contains("already connected"), "{}", error);
+        assert_eq!(dialer.get_peers().len(), 1);
+        
+        // A client skipping the Noise handshake is dropped
+        let mut plaintext = TcpStream::connect(&address).await.unwrap();
+        plaintext.write_all(&[0, 0, 0, 5, b'h', b'e', b'l', b'l', b'o']).await.unwrap();
+        let mut buffer = Vec::new();
+        let closed = tokio::time::timeout(Duration::from_secs(5), plaintext.read_to_end(&mut buffer)).await;
+        assert!(closed.is_ok());
+        assert_eq!(listener.get_peers().len(), 1);
+    }
+}
@@ -0,0 +1,154 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use hypernova_core::{
+        network::{MessageKind, MessageRates, NetworkConfig, P2PNetwork},
+        ratelimit::{Rate, TokenBucket},
+        reputation::PeerEvent,
+    };
+    use crate::common::wait_for;
+    
+    async fn connect(listener: &P2PNetwork, dialer: &P2PNetwork) -> String {
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let peer = dialer.connect(&address).unwrap();
+        assert!(wait_for(|| !listener.get_peers().is_empty()).await);
+        peer
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(Rate::new(10, 3));
+        assert_eq!(bucket.refill_period(), Duration::from_millis(300));
+        
+        // A full bucket allows a burst, then refuses until tokens are earned back
+        assert!(bucket.try_take(2));
+        assert!(bucket.try_take(1));
+        assert!(!bucket.try_take(1));
+        tokio::time::advance(Duration::from_millis(150)).await;
+        assert!(bucket.try_take(1));
+        
+        // Reserving goes into debt and tells how long paying it back takes
+        let wait = bucket.reserve(10);
+        assert!(wait > Duration::from_millis(940) && wait <= Duration::from_millis(950), "{:?}", wait);
+    }
+    
+    fn rate_limited_listener(application: Rate) -> P2PNetwork {
+        let listener = P2PNetwork::new(0).unwrap().with_config(NetworkConfig {
+            message_rates: MessageRates {
+                application,
+                ..MessageRates::default()
+            },
+            ..NetworkConfig::default()
+        });
+        listener.start().unwrap();
+        listener
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bursts_over_the_rate_are_dropped_and_penalized_once() {
+        let listener = rate_limited_listener(Rate::new(1, 5));
+        let mut inbound = listener.take_inbound().unwrap();
+        let dialer = P2PNetwork::new(0).unwrap();
+        let peer = connect(&listener, &dialer).await;
+        
+        for i in 0..20u8 {
+            dialer.send(&peer, &[i]).unwrap();
+        }
+        assert!(wait_for(|| listener.metrics().rate_limited.get(&MessageKind::Application).is_some_and(|dropped| *dropped >= 14)).await);
+        
+        // Messages within the burst are delivered, and the rest only cost the peer one penalty
+        let mut delivered = 0;
+        while inbound.try_recv().is_ok() {
+            delivered += 1;
+        }
+        assert!((5..=6).contains(&delivered), "{} messages delivered", delivered);
+        assert_eq!(delivered + listener.metrics().rate_limited[&MessageKind::Application], 20);
+        assert_eq!(listener.peer_score(&dialer.public_key()), PeerEvent::Spam.score_change());
+        assert!(listener.get_peers().contains(&dialer.public_key()));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_flooding_peers_are_dropped_and_banned() {
+        let listener = rate_limited_listener(Rate::new(50, 5));
+        let dialer = P2PNetwork::new(0).unwrap();
+        let peer = connect(&listener, &dialer).await;
+        
+        // Sending twice as fast as allowed costs the peer a penalty every tenth of a second
+        let flood = async {
+            loop {
+                let _ = dialer.send(&peer, b"flood");
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        };
+        let banned = wait_for(|| !listener.banned_peers().is_empty());
+        tokio::select! {
+            banned = banned => assert!(banned),
+            _ = flood => unreachable!(),
+        }
+        
+        assert_eq!(listener.banned_peers()[0].public_key, dialer.public_key());
+        assert!(listener.get_peers().is_empty());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_outbound_bandwidth_and_send_queue_backpressure() {
+        let listener = P2PNetwork::new(0).unwrap();
+        listener.start().unwrap();
+        let mut inbound = listener.take_inbound().unwrap();
+        
+        let dialer = P2PNetwork::new(0).unwrap().with_config(NetworkConfig {
+            max_outbound_bandwidth: Some(Rate::new(10_000, 1)),
+            send_queue_bytes: 25_000,
+            ..NetworkConfig::default()
+        });
+        let peer = connect(&listener, &dialer).await;
+        let message = vec![7u8; 10_000];
+        let start = Instant::now();
+        
+        // The writer holds back each message for a second, so the queue fills after two
+        dialer.send(&peer, &message).unwrap();
+        dialer.send(&peer, &message).unwrap();
+        assert!(dialer.send(&peer, &message).is_err());
+        assert_eq!(dialer.metrics().queue_full[&MessageKind::Application], 1);
+        
+        // Waiting for room succeeds once the first message is written
+        dialer.send_async(&peer, &message).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(900));
+        
+        for _ in 0..3 {
+            let received = tokio::time::timeout(Duration::from_secs(5), inbound.recv()).await.unwrap().unwrap();
+            assert_eq!(received.data, message);
+        }
+        assert!(start.elapsed() >= Duration::from_millis(2900));
+        assert!(wait_for(|| dialer.metrics().bytes_sent == 3 * 10_001).await);
+        assert_eq!(listener.metrics().bytes_received, 3 * 10_001);
+        assert_eq!(dialer.metrics().dropped(), 1);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_messages_the_node_does_not_take_are_dropped() {
+        let listener = P2PNetwork::new(0).unwrap().with_config(NetworkConfig {
+            delivery_queue_size: 3,
+            ..NetworkConfig::default()
+        });
+        listener.start().unwrap();
+        let mut inbound = listener.take_inbound().unwrap();
+        
+        let dialer = P2PNetwork::new(0).unwrap();
+        let peer = connect(&listener, &dialer).await;
+        
+        // Only as many messages as the queue holds wait for a node that is not reading
+        for i in 0..10u8 {
+            dialer.send(&peer, &[i]).unwrap();
+        }
+        assert!(wait_for(|| listener.metrics().undelivered.get(&MessageKind::Application) == Some(&7)).await);
+        
+        for i in 0..3u8 {
+            assert_eq!(inbound.try_recv().unwrap().data, vec![i]);
+        }
+        assert!(inbound.try_recv().is_err());
+        assert!(listener.get_peers().contains(&dialer.public_key()));
+    }
+}
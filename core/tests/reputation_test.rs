@@ -0,0 +1,106 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use hypernova_core::{
+        network::P2PNetwork,
+        reputation::{PeerEvent, Reputation, BAN_THRESHOLD, MAX_SCORE},
+        rpc::{RpcRequest, RpcServer},
+        spec::ChainSpec,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use crate::common::{self, wait_for};
+    
+    fn call(rpc: &RpcServer, method: &str, params: Value) -> Value {
+        let response = rpc.handle(RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: json!(1),
+        });
+        assert!(response.error.is_none(), "{:?}", response.error);
+        response.result.unwrap()
+    }
+    
+    #[test]
+    fn test_scores_lead_to_persisted_bans() {
+        let path = std::env::temp_dir().join("hypernova_banned_peers.json");
+        let _ = std::fs::remove_file(&path);
+        let mut reputation = Reputation::open(&path, Duration::from_secs(60));
+        
+        // Good behaviour raises the score up to a cap
+        for _ in 0..100 {
+            reputation.record("good", PeerEvent::UsefulResponse);
+        }
+        assert_eq!(reputation.score("good"), MAX_SCORE);
+        
+        // Repeated misbehaviour gets a peer banned once the score reaches the threshold
+        assert!(reputation.record("bad", PeerEvent::InvalidGossip).is_none());
+        assert_eq!(reputation.score("bad"), -50);
+        let ban = reputation.record("bad", PeerEvent::InvalidGossip).unwrap();
+        assert_eq!(ban.public_key, "bad");
+        assert!(reputation.is_banned("bad"));
+        assert_eq!(reputation.score("bad"), 0);
+        
+        // Breaking the protocol costs even the best peers a ban
+        assert!(reputation.record("good", PeerEvent::ProtocolViolation).is_some());
+        assert!(MAX_SCORE + PeerEvent::ProtocolViolation.score_change() <= BAN_THRESHOLD);
+        
+        // Bans outlive restarts until lifted
+        reputation.save();
+        let mut reopened = Reputation::open(&path, Duration::from_secs(60));
+        assert_eq!(reopened.bans().len(), 2);
+        assert!(reopened.unban("bad"));
+        assert!(!reopened.unban("bad"));
+        reopened.save();
+        assert!(!Reputation::open(&path, Duration::from_secs(60)).is_banned("bad"));
+        
+        // Expired bans are dropped
+        reopened.ban("brief", Duration::from_secs(0), "testing");
+        assert!(!reopened.is_banned("brief"));
+        assert_eq!(reopened.bans().len(), 1);
+        
+        // A corrupt file is ignored rather than keeping the node from starting
+        std::fs::write(&path, b"not json").unwrap();
+        assert!(Reputation::open(&path, Duration::from_secs(60)).bans().is_empty());
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_banned_peers_are_refused_until_unbanned() {
+        let blockchain = Arc::new(common::test_chain("reputation_rpc", ChainSpec::dev()));
+        
+        let listener = Arc::new(P2PNetwork::new(0).unwrap());
+        listener.start().unwrap();
+        let address = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let rpc = RpcServer::new(blockchain, 0).with_network(listener.clone());
+        
+        let dialer = P2PNetwork::new(0).unwrap();
+        let peer = dialer.connect(&address).unwrap();
+        assert!(wait_for(|| !listener.get_peers().is_empty()).await);
+        
+        let peers = call(&rpc, "admin_peers", json!([]));
+        assert_eq!(peers[0]["info"]["public_key"], json!(dialer.public_key()));
+        assert_eq!(peers[0]["score"], json!(0));
+        
+        // A peer breaking the protocol is banned and disconnected at once
+        listener.report_peer(&dialer.public_key(), PeerEvent::ProtocolViolation);
+        assert!(listener.get_peers().is_empty());
+        let bans = call(&rpc, "admin_bannedPeers", json!([]));
+        assert_eq!(bans[0]["public_key"], json!(dialer.public_key()));
+        
+        // It is refused when it comes back
+        dialer.disconnect(&peer).unwrap();
+        let error = dialer.connect(&address).unwrap_err();
+        assert!(error.to_string().contains("banned (code 10)"), "{}", error);
+        
+        // Operators can lift and impose bans
+        assert_eq!(call(&rpc, "admin_unbanPeer", json!([dialer.public_key()])), json!(true));
+        dialer.connect(&address).unwrap();
+        
+        let ban = call(&rpc, "admin_banPeer", json!([dialer.public_key(), 30]));
+        assert_eq!(ban["reason"], json!("banned by operator"));
+        assert!(listener.get_peers().is_empty());
+    }
+}
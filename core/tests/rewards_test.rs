@@ -0,0 +1,182 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use hypernova_core::{
+        ai_proof::AIProof,
+        crypto::KeyPair,
+        governance::{ProposalAction, Vote},
+        model_registry::ModelSubmission,
+        rewards::{FeeDistribution, MonetaryPolicy, RewardTotals},
+        spec::ChainSpec,
+        staking::ValidatorRegistration,
+        transaction::{Transaction, TransactionType},
+    };
+    use crate::common;
+    
+    fn signed_tx(
+        tx_type: TransactionType,
+        from: &str,
+        to: Option<&str>,
+        amount: Option<u64>,
+        data: Option<Vec<u8>>,
+        fee: u64,
+    ) -> Transaction {
+        common::signed(Transaction::new(tx_type, from.to_string(), to.map(str::to_string), amount, data, fee, 0))
+    }
+    
+    #[test]
+    fn test_issuance_schedule_and_caps() {
+        let policy = MonetaryPolicy {
+            block_reward: 1000,
+            reward_decay_interval: 10,
+            reward_decay_bps: 5000,
+            ..MonetaryPolicy::default()
+        };
+        assert_eq!(policy.scheduled_reward(9), 1000);
+        assert_eq!(policy.scheduled_reward(10), 500);
+        assert_eq!(policy.scheduled_reward(35), 125);
+        assert_eq!(policy.scheduled_reward(1000), 0);
+        
+        // Issuance stops at the maximum supply and is limited by the inflation cap
+        let totals = RewardTotals {
+            genesis_supply: 100_000,
+            issued: 9_800,
+            ..RewardTotals::default()
+        };
+        let capped = MonetaryPolicy {
+            max_supply: Some(110_000),
+            ..policy.clone()
+        };
+        assert_eq!(capped.block_reward(0, &totals), 200);
+        
+        let inflation_capped = MonetaryPolicy {
+            max_annual_inflation_bps: Some(1000),
+            blocks_per_year: 100,
+            ..policy.clone()
+        };
+        assert_eq!(inflation_capped.block_reward(0, &totals), 109);
+        
+        // Fee shares always add up to the fees paid
+        let split = FeeDistribution::default().split(999);
+        assert_eq!(split.producer + split.model_provider + split.treasury + split.burned, 999);
+        
+        let invalid = MonetaryPolicy {
+            fee_distribution: FeeDistribution {
+                burn_bps: 2000,
+                ..FeeDistribution::default()
+            },
+            ..MonetaryPolicy::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+    
+    #[test]
+    fn test_rewards_and_fees_are_distributed() {
+        let account = |address: &str, balance: u64| common::genesis_account(address, balance);
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            genesis_accounts: vec![
+                account("validator1", 10000),
+                account("delegator", 10000),
+                account("provider", 10000),
+                account("user", 10000),
+            ],
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain("rewards_distribution", spec);
+        let keypair = KeyPair::generate().unwrap();
+        
+        // Block 1 has no producer, so its fees all go to the treasury or are burned
+        let registration = ValidatorRegistration {
+            public_key: keypair.public_key_hex(),
+            quantum_public_key: None,
+            commission_bps: 1000,
+        };
+        let submission = ModelSubmission {
+            model_id: "provided".to_string(),
+            model_hash: hex::encode([7u8; 32]),
+            architecture: "dense(10x1,linear)".to_string(),
+        };
+        let block = blockchain.build_block(common::with_nonces(&blockchain, vec![
+            signed_tx(TransactionType::ValidatorRegistration, "validator1", None, Some(4000), Some(registration.encode()), 0),
+            signed_tx(TransactionType::Delegation, "delegator", Some("validator1"), Some(4000), None, 0),
+            signed_tx(TransactionType::AIModelSubmission, "provider", None, Some(1000), Some(submission.encode()), 100),
+        ])).unwrap();
+        blockchain.add_block(block).unwrap();
+        assert_eq!(blockchain.get_balance("treasury"), 90);
+        assert_eq!(blockchain.get_reward_totals().issued, 0);
+        
+        // Validator1 approves the model through governance in its own block
+        let action = ProposalAction::ApproveModel { model_id: "provided".to_string() };
+        let vote = Vote { proposal_id: 0, approve: true };
+        let mut block = blockchain.build_block(common::with_nonces(&blockchain, vec![
+            signed_tx(TransactionType::GovernanceProposal, "provider", None, None, Some(action.encode()), 0),
+            signed_tx(TransactionType::GovernanceVote, "validator1", None, None, Some(vote.encode()), 0),
+        ])).unwrap();
+        block.sign_as("validator1", &keypair);
+        blockchain.add_block(block).unwrap();
+        
+        let before = |address: &str| blockchain.get_balance(address);
+        let (validator, delegator, provider, treasury) = (before("validator1"), before("delegator"), before("provider"), before("treasury"));
+        
+        // A block proved with the provider's model and carrying 100 in fees
+        let mut block = blockchain.build_block(common::with_nonces(&blockchain, vec![
+            signed_tx(TransactionType::Transfer, "user", Some("delegator"), Some(0), None, 100),
+        ])).unwrap();
+        block.add_ai_proof(AIProof::new(&block.header, "provided".to_string(), [7u8; 32], 0, 9000).to_bytes());
+        block.sign_as("validator1", &keypair);
+        blockchain.add_block(block).unwrap();
+        
+        // Reward 100 plus half the fees: 10% commission, the rest shared by stake
+        assert_eq!(blockchain.get_balance("validator1") - validator, 15 + 67 + 1);
+        assert_eq!(blockchain.get_balance("delegator") - delegator, 67);
+        assert_eq!(blockchain.get_balance("provider") - provider, 20);
+        assert_eq!(blockchain.get_balance("treasury") - treasury, 20);
+        
+        let totals = blockchain.get_reward_totals();
+        assert_eq!(totals.issued, 200);
+        assert_eq!(totals.fees, 200);
+        assert_eq!(totals.burned, 20);
+        assert_eq!(totals.supply(), 40000 + 200 - 20);
+    }
+    
+    #[test]
+    fn test_punished_producer_is_not_paid() {
+        let spec = ChainSpec {
+            min_self_stake: 1000,
+            genesis_accounts: vec![
+                common::genesis_account("validator1", 10000),
+                common::genesis_account("user", 10000),
+            ],
+            ..ChainSpec::default()
+        };
+        let blockchain = common::test_chain("rewards_punished_producer", spec);
+        let keypair = KeyPair::generate().unwrap();
+        let tx = common::registration_tx("validator1", keypair.public_key_hex(), 5000);
+        blockchain.add_block(blockchain.build_block(vec![tx]).unwrap()).unwrap();
+        
+        // The validator double-signs at height 2
+        for shard_id in 0..2 {
+            let mut block = blockchain.build_block(Vec::new()).unwrap();
+            block.header.shard_id = shard_id;
+            block.sign_as("validator1", &keypair);
+            blockchain.observe_block(&block);
+        }
+        
+        let (validator, treasury) = (blockchain.get_balance("validator1"), blockchain.get_balance("treasury"));
+        
+        // Its block carrying the evidence against it earns neither the reward nor its share of fees
+        let mut block = blockchain.build_block(vec![
+            signed_tx(TransactionType::Transfer, "user", Some("treasury"), Some(0), None, 100),
+        ]).unwrap();
+        block.set_evidence(blockchain.get_pending_evidence());
+        block.sign_as("validator1", &keypair);
+        blockchain.add_block(block).unwrap();
+        
+        assert_eq!(blockchain.get_balance("validator1"), validator);
+        assert_eq!(blockchain.get_balance("treasury") - treasury, 90);
+        assert_eq!(blockchain.get_reward_totals().issued, 0);
+    }
+}
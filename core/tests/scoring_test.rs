@@ -0,0 +1,112 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use hypernova_core::{
+        chain::Blockchain,
+        scoring::{HeuristicScorer, ScoringContext, ScreeningAction, ScreeningPolicy, TransactionScore, TransactionScorer, TransactionScreener},
+        spec::ChainSpec,
+        transaction::Transaction,
+    };
+    use crate::common::{self, transfer};
+    
+    /// Scorer counting the transactions it scores
+    struct CountingScorer(Arc<AtomicUsize>);
+    
+    impl TransactionScorer for CountingScorer {
+        fn score(&self, _tx: &Transaction, _context: &ScoringContext) -> Result<TransactionScore, Box<dyn std::error::Error>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(TransactionScore::default())
+        }
+        
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+    }
+    
+    fn test_chain(name: &str, policy: ScreeningPolicy) -> Blockchain {
+        let spec = ChainSpec {
+            genesis_accounts: ["alice", "bob"]
+                .iter()
+                .map(|address| common::genesis_account(address, 10000))
+                .collect(),
+            ..ChainSpec::default()
+        };
+        
+        common::test_chain(&format!("scoring_{}", name), spec)
+            .with_transaction_screener(TransactionScreener::new(Box::new(HeuristicScorer::default()), policy))
+    }
+    
+    #[test]
+    fn test_heuristic_patterns() {
+        let blockchain = test_chain("heuristic", ScreeningPolicy::default());
+        let state = blockchain.get_state();
+        let scorer = HeuristicScorer::default();
+        
+        // An ordinary transfer between known accounts is not anomalous
+        let ordinary = transfer("alice", "bob", 100, 0);
+        let score = scorer.score(&ordinary, &ScoringContext { state: &state, pending: &[] }).unwrap();
+        assert_eq!(score.score_bps, 0);
+        
+        // A first transaction draining the sender into an unseen account
+        let draining = transfer("alice", "mallory", 9500, 0);
+        let score = scorer.score(&draining, &ScoringContext { state: &state, pending: &[] }).unwrap();
+        assert_eq!(score.score_bps, 5000);
+        assert_eq!(score.reasons.len(), 2);
+        
+        // A sender with many transactions in flight, one dwarfing the rest
+        let pending: Vec<Transaction> = (0..8).map(|nonce| transfer("bob", "alice", 1, nonce)).collect();
+        let burst = transfer("bob", "alice", 200, 8);
+        let score = scorer.score(&burst, &ScoringContext { state: &state, pending: &pending }).unwrap();
+        assert_eq!(score.score_bps, 6000);
+    }
+    
+    #[test]
+    fn test_screening_actions() {
+        let blockchain = test_chain("actions", ScreeningPolicy {
+            log_bps: None,
+            deprioritize_bps: Some(2000),
+            reject_bps: Some(5000),
+        });
+        
+        let flagged = transfer("alice", "carol", 100, 0);
+        let ordinary = transfer("alice", "bob", 100, 1);
+        blockchain.add_transaction(flagged.clone()).unwrap();
+        blockchain.add_transaction(ordinary.clone()).unwrap();
+        
+        // Rejected transactions never enter the pool but their score is kept
+        let anomalous = transfer("bob", "dave", 9500, 0);
+        assert!(blockchain.add_transaction(anomalous.clone()).is_err());
+        let score = blockchain.get_transaction_score(&anomalous.hash()).unwrap();
+        assert_eq!(score.action, ScreeningAction::Reject);
+        assert_eq!(score.score_bps, 5000);
+        assert_eq!(score.scorer, "heuristic");
+        
+        // Deprioritized transactions are included after unflagged ones
+        let candidates: Vec<String> = blockchain.get_block_candidates().iter().map(Transaction::hash).collect();
+        assert_eq!(candidates, vec![ordinary.hash(), flagged.hash()]);
+        
+        let pending = blockchain.get_pending_scores();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].action, ScreeningAction::Deprioritize);
+        assert_eq!(pending[1].action, ScreeningAction::Admit);
+    }
+    
+    #[test]
+    fn test_pending_transactions_are_scored_once() {
+        let scored = Arc::new(AtomicUsize::new(0));
+        let blockchain = test_chain("cached", ScreeningPolicy::default())
+            .with_transaction_screener(TransactionScreener::new(Box::new(CountingScorer(scored.clone())), ScreeningPolicy::default()));
+        
+        blockchain.add_transaction(transfer("alice", "bob", 100, 0)).unwrap();
+        blockchain.add_transaction(transfer("bob", "alice", 100, 0)).unwrap();
+        assert_eq!(scored.load(Ordering::SeqCst), 2);
+        
+        // Building blocks reuses the scores given at admission
+        assert_eq!(blockchain.get_block_candidates().len(), 2);
+        assert_eq!(blockchain.get_block_candidates().len(), 2);
+        assert_eq!(scored.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,211 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use hypernova_core::{
+        consensus::{Consensus, DelegatedProofOfStake, DevConsensus, HybridConsensus, ProofOfAI},
+        crypto::KeyPair,
+        simulation::{NetworkConditions, NodeBehavior, Simulation, SimulationConfig},
+        spec::ConsensusComponents,
+        staking::ValidatorStatus,
+    };
+    use crate::common;
+    
+    const VALIDATORS: [&str; 4] = ["validator1", "validator2", "validator3", "validator4"];
+    
+    type Engine<'a> = Box<dyn Fn(&str, KeyPair) -> Box<dyn Consensus> + 'a>;
+    
+    fn scheduled_engine(address: &str, keypair: KeyPair) -> Box<dyn Consensus> {
+        let proof_of_ai = ProofOfAI::new("http://127.0.0.1:1", 0.75);
+        Box::new(HybridConsensus::new(proof_of_ai).with_validator(address, keypair))
+    }
+    
+    fn config(network: NetworkConditions) -> SimulationConfig {
+        let mut config = SimulationConfig {
+            seed: 7,
+            network,
+            ..SimulationConfig::default()
+        };
+        config.spec.consensus = ConsensusComponents {
+            scheduled_producer: true,
+            ai_proof: false,
+            quantum_signature: false,
+        };
+        config
+    }
+    
+    fn simulation(name: &str, network: NetworkConditions, behaviors: [NodeBehavior; 4]) -> Simulation {
+        simulation_with(name, config(network), behaviors)
+    }
+    
+    fn simulation_with(name: &str, config: SimulationConfig, behaviors: [NodeBehavior; 4]) -> Simulation {
+        let dir = std::env::temp_dir().join(format!("hypernova_sim_{}", name));
+        let validators: Vec<(&str, NodeBehavior)> = VALIDATORS.iter().copied().zip(behaviors).collect();
+        Simulation::new(dir.to_str().unwrap(), config, &validators, scheduled_engine).unwrap()
+    }
+    
+    #[test]
+    fn test_lossy_network_stays_safe_and_live() {
+        let network = NetworkConditions {
+            min_latency_ms: 20,
+            max_latency_ms: 400,
+            drop_bps: 1000,
+        };
+        let mut sim = simulation("lossy", network, [NodeBehavior::Honest; 4]);
+        
+        sim.run_for(60_000);
+        
+        sim.check_safety().unwrap();
+        sim.check_liveness(20).unwrap();
+        assert!(sim.stats().dropped > 0);
+    }
+    
+    #[test]
+    fn test_crashed_producer_is_passed_over() {
+        let behaviors = [
+            NodeBehavior::Crashed,
+            NodeBehavior::Honest,
+            NodeBehavior::Honest,
+            NodeBehavior::Honest,
+        ];
+        let mut sim = simulation("crashed", NetworkConditions::default(), behaviors);
+        
+        // The next validator takes each of the crashed node's slots once its time runs out
+        sim.run_for(60_000);
+        sim.check_safety().unwrap();
+        sim.check_liveness(20).unwrap();
+        
+        // Its missed slots are recorded, so it is jailed at the end of its epoch
+        let record = sim.blockchain("validator2").unwrap().get_validator("validator1").unwrap();
+        assert_eq!(record.status, ValidatorStatus::Jailed);
+    }
+    
+    #[test]
+    fn test_partition_heals() {
+        // Handing slots over would let both sides build conflicting chains past the finality depth
+        let mut config = config(NetworkConditions::default());
+        config.spec.slot_duration = 0;
+        let mut sim = simulation_with("partition", config, [NodeBehavior::Honest; 4]);
+        sim.partition(&[&VALIDATORS[..2], &VALIDATORS[2..]], 5_000, 20_000).unwrap();
+        
+        // Neither side holds every slot, so both stall while partitioned
+        sim.run_until(19_000);
+        let stalled = sim.heights();
+        sim.run_until(21_000);
+        assert!(sim.stats().partitioned > 0);
+        
+        // Once healed the nodes catch up and agree
+        sim.run_until(40_000);
+        sim.check_safety().unwrap();
+        sim.check_liveness(stalled.values().copied().max().unwrap() + 5).unwrap();
+    }
+    
+    #[test]
+    fn test_equivocation_is_detected() {
+        let behaviors = [
+            NodeBehavior::Equivocating,
+            NodeBehavior::Honest,
+            NodeBehavior::Honest,
+            NodeBehavior::Honest,
+        ];
+        let mut sim = simulation("equivocation", NetworkConditions::default(), behaviors);
+        
+        sim.run_for(20_000);
+        
+        // Honest nodes never finalize conflicting blocks and collect evidence of the double-sign
+        sim.check_safety().unwrap();
+        let incriminated = VALIDATORS[1..].iter().any(|address| {
+            let blockchain = sim.blockchain(address).unwrap();
+            !blockchain.get_pending_evidence().is_empty() || blockchain.get_validator("validator1").unwrap().slashed > 0
+        });
+        assert!(incriminated);
+    }
+    
+    #[test]
+    fn test_runs_are_reproducible() {
+        let network = NetworkConditions {
+            drop_bps: 2000,
+            ..NetworkConditions::default()
+        };
+        let mut first = simulation("replay_a", network.clone(), [NodeBehavior::Honest; 4]);
+        let mut second = simulation("replay_b", network, [NodeBehavior::Honest; 4]);
+        
+        first.run_for(30_000);
+        second.run_for(30_000);
+        
+        assert_eq!(first.heights(), second.heights());
+        assert_eq!(first.stats(), second.stats());
+        for address in VALIDATORS {
+            let tip = |sim: &Simulation| sim.blockchain(address).unwrap().get_latest_block().unwrap().hash();
+            assert_eq!(tip(&first), tip(&second));
+        }
+    }
+    
+    #[test]
+    fn test_hybrid_chain_with_more_validators_than_the_committee() {
+        let endpoint = common::mock_proof_service(&common::consensus_model());
+        let dir = std::env::temp_dir().join("hypernova_sim_large_set");
+        let mut config = SimulationConfig {
+            seed: 13,
+            ..SimulationConfig::default()
+        };
+        config.spec.consensus = ConsensusComponents {
+            scheduled_producer: true,
+            ai_proof: true,
+            quantum_signature: false,
+        };
+        config.spec.genesis_models = vec![common::consensus_model_submission()];
+        // Without slots handed over, every scheduled producer must be able to prove its own block
+        config.spec.slot_duration = 0;
+        
+        let addresses: Vec<String> = (1..=8).map(|index| format!("validator{}", index)).collect();
+        assert!(addresses.len() > config.spec.poai_committee_size);
+        let validators: Vec<(&str, NodeBehavior)> = addresses.iter().map(|address| (address.as_str(), NodeBehavior::Honest)).collect();
+        let mut sim = Simulation::new(dir.to_str().unwrap(), config, &validators, |address, keypair| {
+            let proof_of_ai = ProofOfAI::new(&endpoint, 0.75).with_model(common::consensus_model());
+            Box::new(HybridConsensus::new(proof_of_ai).with_validator(address, keypair))
+        }).unwrap();
+        
+        // Scheduled producers outside the committee still prove their blocks, so no height stalls
+        sim.run_for(60_000);
+        sim.check_safety().unwrap();
+        sim.check_liveness(40).unwrap();
+    }
+    
+    #[test]
+    fn test_every_engine_converges() {
+        let endpoint = common::mock_proof_service(&common::consensus_model());
+        let proof_of_ai = || ProofOfAI::new(&endpoint, 0.75).with_model(common::consensus_model());
+        
+        let engines: Vec<(&str, bool, Engine)> = vec![
+            ("dev", false, Box::new(|_, _| Box::new(DevConsensus::instant()))),
+            ("dpos", false, Box::new(|_, _| Box::new(DelegatedProofOfStake::new(21, 1000)))),
+            ("poai", false, Box::new(|address, keypair| Box::new(proof_of_ai().with_validator(address, keypair)))),
+            ("hybrid", true, Box::new(|address, keypair| Box::new(HybridConsensus::new(proof_of_ai()).with_validator(address, keypair)))),
+        ];
+        
+        for (name, scheduled, engine) in engines {
+            let dir = std::env::temp_dir().join(format!("hypernova_sim_engine_{}", name));
+            let mut config = SimulationConfig {
+                seed: 11,
+                ..SimulationConfig::default()
+            };
+            config.spec.consensus = ConsensusComponents {
+                scheduled_producer: scheduled,
+                ai_proof: name == "poai" || name == "hybrid",
+                quantum_signature: false,
+            };
+            config.spec.genesis_models = vec![common::consensus_model_submission()];
+            // Only one of the validators racing for each height is credited with it
+            config.spec.liveness_threshold_bps = 0;
+            
+            let validators: Vec<(&str, NodeBehavior)> = VALIDATORS.iter().map(|address| (*address, NodeBehavior::Honest)).collect();
+            let mut sim = Simulation::new(dir.to_str().unwrap(), config, &validators, engine).unwrap();
+            
+            // Engines letting every validator produce fork at each height, and fork choice settles it
+            sim.run_for(20_000);
+            sim.check_safety().unwrap_or_else(|e| panic!("{}: {}", name, e));
+            sim.check_liveness(10).unwrap_or_else(|e| panic!("{}: {}", name, e));
+        }
+    }
+}
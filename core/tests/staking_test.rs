@@ -0,0 +1,238 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use hypernova_core::{
+        chain::Blockchain,
+        consensus::DelegatedProofOfStake,
+        crypto::{KeyPair, Signature, SignatureType},
+        spec::{self, ChainSpec},
+        state::ChainState,
+        staking::{SlashReason, ValidatorRegistration, ValidatorStatus},
+        transaction::{Transaction, TransactionType},
+    };
+    use crate::common::{self, signed};
+    
+    fn test_chain(name: &str) -> Blockchain {
+        let spec = ChainSpec {
+            epoch_length: 2,
+            unbonding_period: 3,
+            min_self_stake: 1000,
+            // Test blocks are unsigned, so liveness is not enforced
+            liveness_threshold_bps: 0,
+            genesis_accounts: vec![
+                common::genesis_account("alice", 10000),
+                common::genesis_account("validator1", 5000),
+            ],
+            ..ChainSpec::default()
+        };
+        
+        common::test_chain(&format!("staking_{}", name), spec)
+    }
+    
+    fn signed_tx(tx_type: TransactionType, candidate: &str, amount: u64, nonce: u64) -> Transaction {
+        signed(Transaction::new(
+            tx_type,
+            "alice".to_string(),
+            Some(candidate.to_string()),
+            Some(amount),
+            None,
+            0,
+            nonce,
+        ))
+    }
+    
+    fn registration_tx(address: &str, self_stake: u64) -> Transaction {
+        let registration = ValidatorRegistration {
+            public_key: "11".repeat(32),
+            quantum_public_key: None,
+            commission_bps: 500,
+        };
+        
+        signed(Transaction::new(
+            TransactionType::ValidatorRegistration,
+            address.to_string(),
+            None,
+            Some(self_stake),
+            Some(registration.encode()),
+            0,
+            0,
+        ))
+    }
+    
+    fn add_block(blockchain: &Blockchain, transactions: Vec<Transaction>) {
+        let block = blockchain.build_block(transactions).unwrap();
+        blockchain.add_block(block).unwrap();
+    }
+    
+    #[test]
+    fn test_delegation_elects_validator_at_epoch_boundary() {
+        let blockchain = test_chain("election");
+        let dpos = DelegatedProofOfStake::new(21, 1000);
+        
+        dpos.refresh_validators(&blockchain).unwrap();
+        assert!(dpos.get_validators().is_empty());
+        
+        // Register and delegate in the last block of epoch 0
+        add_block(&blockchain, vec![
+            registration_tx("validator1", 1000),
+            signed_tx(TransactionType::Delegation, "validator1", 4000, 0),
+        ]);
+        assert_eq!(blockchain.get_balance("alice"), 6000);
+        
+        // The delegation counts for the blocks of epoch 1
+        dpos.refresh_validators(&blockchain).unwrap();
+        assert_eq!(dpos.get_validators(), vec!["validator1".to_string()]);
+        
+        let positions = blockchain.get_delegations("alice");
+        assert_eq!(positions.delegations.len(), 1);
+        assert_eq!(positions.delegations[0].amount, 4000);
+    }
+    
+    #[test]
+    fn test_undelegation_waits_for_unbonding_period() {
+        let blockchain = test_chain("unbonding");
+        
+        add_block(&blockchain, vec![
+            registration_tx("validator1", 1000),
+            signed_tx(TransactionType::Delegation, "validator1", 4000, 0),
+        ]);
+        add_block(&blockchain, vec![signed_tx(TransactionType::Undelegation, "validator1", 1500, 1)]);
+        
+        let positions = blockchain.get_delegations("alice");
+        assert_eq!(positions.delegations[0].amount, 2500);
+        assert_eq!(positions.unbonding.len(), 1);
+        assert_eq!(positions.unbonding[0].release_height, 5);
+        
+        add_block(&blockchain, Vec::new());
+        add_block(&blockchain, Vec::new());
+        assert_eq!(blockchain.get_balance("alice"), 6000);
+        
+        add_block(&blockchain, Vec::new());
+        assert_eq!(blockchain.get_balance("alice"), 7500);
+        assert!(blockchain.get_delegations("alice").unbonding.is_empty());
+    }
+    
+    #[test]
+    fn test_delegation_without_funds_is_rejected() {
+        let blockchain = test_chain("insufficient");
+        
+        let block = blockchain
+            .build_block(vec![signed_tx(TransactionType::Delegation, "validator1", 20000, 0)])
+            .unwrap();
+        
+        assert!(blockchain.add_block(block).is_err());
+        assert_eq!(blockchain.get_height(), 0);
+        assert_eq!(blockchain.get_balance("alice"), 10000);
+    }
+    
+    #[test]
+    fn test_validator_registration_requires_min_self_stake() {
+        let blockchain = test_chain("registration");
+        
+        let block = blockchain.build_block(vec![registration_tx("validator1", 500)]).unwrap();
+        assert!(blockchain.add_block(block).is_err());
+        
+        // Delegating to an unregistered candidate is rejected too
+        let block = blockchain
+            .build_block(vec![signed_tx(TransactionType::Delegation, "validator1", 4000, 0)])
+            .unwrap();
+        assert!(blockchain.add_block(block).is_err());
+        
+        add_block(&blockchain, vec![registration_tx("validator1", 2000)]);
+        
+        let record = blockchain.get_validator("validator1").unwrap();
+        assert_eq!(record.status, ValidatorStatus::Active);
+        assert_eq!(record.commission_bps, 500);
+        assert_eq!(blockchain.get_balance("validator1"), 3000);
+        
+        // Self-stake cannot drop below the minimum without leaving entirely
+        let unbond = signed(Transaction::new(
+            TransactionType::Undelegation,
+            "validator1".to_string(),
+            Some("validator1".to_string()),
+            Some(1500),
+            None,
+            0,
+            1,
+        ));
+        let block = blockchain.build_block(vec![unbond.clone()]).unwrap();
+        assert!(blockchain.add_block(block).is_err());
+        
+        let mut unbond_all = unbond;
+        unbond_all.amount = Some(2000);
+        add_block(&blockchain, vec![signed(unbond_all)]);
+        assert_eq!(blockchain.get_validator("validator1").unwrap().status, ValidatorStatus::Inactive);
+    }
+    
+    #[test]
+    fn test_double_sign_slash_tombstones_validator() {
+        let blockchain = test_chain("slashing");
+        
+        add_block(&blockchain, vec![
+            registration_tx("validator1", 2000),
+            signed_tx(TransactionType::Delegation, "validator1", 4000, 0),
+        ]);
+        
+        let spec = blockchain.get_spec().clone();
+        let mut state = blockchain.get_state();
+        let slashed = state.slash_validator("validator1", SlashReason::DoubleSign, 1, &spec).unwrap();
+        
+        // 5% of 6000 bonded
+        assert_eq!(slashed, 300);
+        assert_eq!(state.staking().self_stake("validator1"), 1900);
+        assert_eq!(state.staking().positions("alice").delegations[0].amount, 3800);
+        assert_eq!(state.staking().validator("validator1").unwrap().status, ValidatorStatus::Tombstoned);
+    }
+    
+    #[test]
+    fn test_genesis_balances_cannot_overflow() {
+        let spec = |accounts: [&str; 2]| ChainSpec {
+            genesis_accounts: accounts.iter().map(|address| common::genesis_account(address, u64::MAX)).collect(),
+            ..ChainSpec::default()
+        };
+        
+        // Neither one account funded twice nor the total supply may wrap around
+        assert!(ChainState::genesis(&spec(["alice", "alice"])).is_err());
+        assert!(ChainState::genesis(&spec(["alice", "bob"])).is_err());
+    }
+    
+    #[test]
+    fn test_transactions_need_the_senders_signature() {
+        let blockchain = test_chain("signatures");
+        
+        // Placeholder signatures and other accounts' keys are refused in the pool and in blocks
+        let mut forged = signed_tx(TransactionType::Delegation, "validator1", 4000, 0);
+        forged.sign(Signature {
+            bytes: vec![0; 64],
+            sig_type: SignatureType::Ed25519,
+        });
+        let mut stolen = common::transfer("alice", "mallory", 5000, 0);
+        stolen.sign_with(&spec::dev_keypair("validator1"));
+        
+        for tx in [forged, stolen] {
+            assert!(blockchain.add_transaction(tx.clone()).is_err());
+            assert!(blockchain.add_block(blockchain.build_block(vec![tx]).unwrap()).is_err());
+        }
+        assert_eq!(blockchain.get_balance("alice"), 10000);
+        
+        // An address that is a public key signs with that key without registering one
+        let keypair = KeyPair::from_seed(&[7; 32]).unwrap();
+        let address = keypair.public_key_hex();
+        add_block(&blockchain, vec![common::transfer("alice", &address, 1000, 0)]);
+        
+        let mut spend = Transaction::new(
+            TransactionType::Transfer,
+            address.clone(),
+            Some("bob".to_string()),
+            Some(400),
+            None,
+            0,
+            0,
+        );
+        spend.sign_with(&keypair);
+        add_block(&blockchain, vec![spend]);
+        assert_eq!(blockchain.get_balance(&address), 600);
+        assert_eq!(blockchain.get_balance("bob"), 400);
+    }
+}
\ No newline at end of file
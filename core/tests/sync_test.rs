@@ -0,0 +1,276 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+    use hypernova_core::{
+        block::Block,
+        chain::Blockchain,
+        consensus::DevConsensus,
+        crypto::KeyPair,
+        handshake::ChainStatus,
+        network::P2PNetwork,
+        spec::ChainSpec,
+        sync::{BlockSource, SignedHeader, SyncConfig, SyncManager, SyncProgress},
+    };
+    use crate::common::{self, registration_tx, transfer};
+    
+    /// Peer serving blocks with tampered transactions
+    struct Tampering(Arc<Blockchain>);
+    
+    impl BlockSource for Tampering {
+        fn status(&self) -> ChainStatus {
+            self.0.status()
+        }
+        
+        fn headers(&self, start: u64, count: u64) -> Vec<SignedHeader> {
+            self.0.headers(start, count)
+        }
+        
+        fn bodies(&self, hashes: &[String]) -> Vec<Block> {
+            let mut blocks = self.0.bodies(hashes);
+            for block in &mut blocks {
+                for tx in &mut block.transactions {
+                    tx.amount = Some(1_000_000);
+                }
+            }
+            blocks
+        }
+    }
+    
+    /// Peer announcing more blocks than it has
+    struct Boasting(Arc<Blockchain>);
+    
+    impl BlockSource for Boasting {
+        fn status(&self) -> ChainStatus {
+            ChainStatus { best_height: 1000, ..self.0.status() }
+        }
+        
+        fn headers(&self, start: u64, count: u64) -> Vec<SignedHeader> {
+            self.0.headers(start, count)
+        }
+        
+        fn bodies(&self, hashes: &[String]) -> Vec<Block> {
+            self.0.bodies(hashes)
+        }
+    }
+    
+    /// Peer serving headers without ever returning their blocks
+    struct Withholding(Arc<Blockchain>);
+    
+    impl BlockSource for Withholding {
+        fn status(&self) -> ChainStatus {
+            self.0.status()
+        }
+        
+        fn headers(&self, start: u64, count: u64) -> Vec<SignedHeader> {
+            self.0.headers(start, count)
+        }
+        
+        fn bodies(&self, _hashes: &[String]) -> Vec<Block> {
+            Vec::new()
+        }
+    }
+    
+    /// Peer serving headers signed with a key that is not the producer's
+    struct Forging(Arc<Blockchain>);
+    
+    impl BlockSource for Forging {
+        fn status(&self) -> ChainStatus {
+            self.0.status()
+        }
+        
+        fn headers(&self, start: u64, count: u64) -> Vec<SignedHeader> {
+            let keypair = KeyPair::generate().unwrap();
+            let mut headers = self.0.headers(start, count);
+            for signed in &mut headers {
+                signed.signature = Some(keypair.sign(signed.header.hash().as_bytes()));
+            }
+            headers
+        }
+        
+        fn bodies(&self, hashes: &[String]) -> Vec<Block> {
+            self.0.bodies(hashes)
+        }
+    }
+    
+    fn dev_chain(name: &str) -> Arc<Blockchain> {
+        Arc::new(common::test_chain(&format!("sync_{}", name), ChainSpec::dev()))
+    }
+    
+    /// Build a chain registering a validator, followed by blocks it signed each holding a transfer, and copies of it
+    fn chains(name: &str, length: u64, copies: usize) -> Vec<Arc<Blockchain>> {
+        let producer = dev_chain(&format!("{}_0", name));
+        let keypair = KeyPair::generate().unwrap();
+        producer.add_transaction(registration_tx("alice", keypair.public_key_hex(), 10000)).unwrap();
+        DevConsensus::instant().seal(&producer).unwrap().unwrap();
+        for nonce in 1..length {
+            producer.add_transaction(transfer("alice", "bob", 10, nonce)).unwrap();
+            let mut block = producer.build_block(producer.get_block_candidates()).unwrap();
+            block.sign_as("alice", &keypair);
+            producer.add_block(block).unwrap();
+        }
+        
+        let mut chains = vec![producer.clone()];
+        for copy in 1..copies {
+            let chain = dev_chain(&format!("{}_{}", name, copy));
+            for height in 1..=length {
+                chain.add_block(producer.get_block_by_height(height).unwrap()).unwrap();
+            }
+            chains.push(chain);
+        }
+        chains
+    }
+    
+    /// Start networks serving blocks and connect a syncing node to each of them
+    fn serve(sources: Vec<Arc<dyn BlockSource>>, follower: Arc<Blockchain>) -> (Arc<P2PNetwork>, Vec<P2PNetwork>) {
+        let network = Arc::new(P2PNetwork::new(0).unwrap().with_block_source(follower));
+        let servers: Vec<P2PNetwork> = sources
+            .into_iter()
+            .map(|source| P2PNetwork::new(0).unwrap().with_block_source(source))
+            .collect();
+        
+        for server in &servers {
+            server.start().unwrap();
+            network.connect(&format!("127.0.0.1:{}", server.local_addr().unwrap().port())).unwrap();
+        }
+        (network, servers)
+    }
+    
+    fn small_batches() -> SyncConfig {
+        SyncConfig {
+            max_headers_per_round: 25,
+            bodies_per_request: 4,
+            ..SyncConfig::default()
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_node_catches_up_from_several_peers() {
+        let chains = chains("catch_up", 60, 3);
+        let follower = dev_chain("catch_up_follower");
+        let sources = chains.iter().map(|chain| chain.clone() as Arc<dyn BlockSource>).collect();
+        let (network, _servers) = serve(sources, follower.clone());
+        
+        let manager = SyncManager::new(follower.clone(), network).with_config(small_batches());
+        assert_eq!(manager.sync().await, 60);
+        
+        assert_eq!(follower.get_latest_block().unwrap().hash(), chains[0].get_latest_block().unwrap().hash());
+        assert_eq!(follower.get_balance("bob"), chains[0].get_balance("bob"));
+        assert_eq!(manager.progress(), SyncProgress {
+            syncing: false,
+            starting_height: 0,
+            current_height: 60,
+            highest_height: 60,
+        });
+        
+        // Nothing is downloaded once caught up
+        assert_eq!(manager.sync().await, 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_lying_peers_are_dropped() {
+        let chains = chains("lying", 30, 3);
+        let follower = dev_chain("lying_follower");
+        let sources: Vec<Arc<dyn BlockSource>> = vec![
+            Arc::new(Tampering(chains[0].clone())),
+            Arc::new(Boasting(chains[1].clone())),
+            chains[2].clone(),
+        ];
+        let (network, servers) = serve(sources, follower.clone());
+        
+        let manager = SyncManager::new(follower.clone(), network.clone()).with_config(small_batches());
+        assert_eq!(manager.sync().await, 30);
+        assert_eq!(follower.get_latest_block().unwrap().hash(), chains[2].get_latest_block().unwrap().hash());
+        
+        // Peers that lied about their height or tampered with blocks were disconnected
+        let peers = network.get_peers();
+        assert!(!peers.contains(&servers[0].public_key()));
+        assert!(!peers.contains(&servers[1].public_key()));
+        assert!(peers.contains(&servers[2].public_key()));
+        
+        // The height they announced is forgotten with them
+        assert_eq!(manager.progress().highest_height, 30);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_headers_not_signed_by_their_producer_are_rejected() {
+        let chains = chains("forged", 30, 1);
+        let honest = dev_chain("forged_honest");
+        for height in 1..=20 {
+            honest.add_block(chains[0].get_block_by_height(height).unwrap()).unwrap();
+        }
+        let follower = dev_chain("forged_follower");
+        let sources: Vec<Arc<dyn BlockSource>> = vec![Arc::new(Forging(chains[0].clone())), honest.clone()];
+        let (network, servers) = serve(sources, follower.clone());
+        
+        // Headers are asked from the peer furthest ahead, whose forged signatures get it dropped
+        let manager = SyncManager::new(follower.clone(), network.clone()).with_config(small_batches());
+        assert_eq!(manager.sync().await, 20);
+        assert_eq!(follower.get_latest_block().unwrap().hash(), honest.get_latest_block().unwrap().hash());
+        assert!(!network.get_peers().contains(&servers[0].public_key()));
+        assert_eq!(manager.progress().highest_height, 20);
+    }    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_peers_on_another_branch_are_not_blamed_for_missing_blocks() {
+        let (longer, shorter) = (chains("branch", 30, 1).remove(0), chains("branch_other", 25, 1).remove(0));
+        let follower = dev_chain("branch_follower");
+        let sources: Vec<Arc<dyn BlockSource>> = vec![longer.clone(), shorter];
+        let (network, servers) = serve(sources, follower.clone());
+        
+        // The peer on the shorter branch is asked for blocks it does not have, and keeps its standing
+        let manager = SyncManager::new(follower.clone(), network.clone()).with_config(small_batches());
+        assert_eq!(manager.sync().await, 30);
+        assert_eq!(follower.get_latest_block().unwrap().hash(), longer.get_latest_block().unwrap().hash());
+        assert!(network.get_peers().contains(&servers[1].public_key()));
+        assert!(network.peer_score(&servers[1].public_key()) >= 0);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_supplier_of_headers_nobody_has_blocks_for_is_dropped() {
+        let chains = chains("withheld", 30, 1);
+        let honest = dev_chain("withheld_honest");
+        for height in 1..=20 {
+            honest.add_block(chains[0].get_block_by_height(height).unwrap()).unwrap();
+        }
+        let follower = dev_chain("withheld_follower");
+        let sources: Vec<Arc<dyn BlockSource>> = vec![Arc::new(Withholding(chains[0].clone())), honest.clone()];
+        let (network, servers) = serve(sources, follower.clone());
+        
+        // Blocks the other peer has are imported, and the supplier is blamed for the rest
+        let manager = SyncManager::new(follower.clone(), network.clone()).with_config(small_batches());
+        assert_eq!(manager.sync().await, 20);
+        assert_eq!(follower.get_latest_block().unwrap().hash(), honest.get_latest_block().unwrap().hash());
+        assert!(!network.get_peers().contains(&servers[0].public_key()));
+        assert!(network.get_peers().contains(&servers[1].public_key()));
+    }
+    
+    #[test]
+    fn test_competing_blocks_are_added_one_at_a_time() {
+        let chain = dev_chain("competing");
+        let balance = chain.get_balance("bob") + chain.get_balance("charlie");
+        
+        // Gossip, sync and sealing may all add a block on the same tip at once
+        for nonce in 0..20 {
+            let blocks = ["bob", "charlie"].map(|to| chain.build_block(vec![transfer("alice", to, 10, nonce)]).unwrap());
+            let barrier = Arc::new(Barrier::new(2));
+            let added: Vec<bool> = blocks
+                .map(|block| {
+                    let (chain, barrier) = (chain.clone(), barrier.clone());
+                    thread::spawn(move || {
+                        barrier.wait();
+                        chain.add_block(block).is_ok()
+                    })
+                })
+                .map(|handle| handle.join().unwrap())
+                .to_vec();
+            assert_eq!(added.iter().filter(|&&added| added).count(), 1);
+        }
+        
+        // The state reflects exactly the blocks on the chain
+        assert_eq!(chain.get_height(), 20);
+        assert_eq!(chain.get_state().height, 20);
+        assert_eq!(chain.get_balance("bob") + chain.get_balance("charlie"), balance + 200);
+    }
+}
@@ -0,0 +1,140 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use rand::{rngs::StdRng, SeedableRng};
+    use tokio::time::Instant;
+    use hypernova_core::{
+        crypto::KeyPair,
+        gossip::Topic,
+        network::{NetworkConfig, P2PNetwork},
+        transport::MemoryNetwork,
+    };
+    use crate::common::wait_for;
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_nodes_connect_and_discover_each_other_in_memory() {
+        let network = MemoryNetwork::new();
+        let config = NetworkConfig {
+            target_outbound: 2,
+            discovery_interval: Duration::from_millis(100),
+            ..NetworkConfig::default()
+        };
+        let nodes: Vec<P2PNetwork> = (0..3)
+            .map(|_| {
+                P2PNetwork::new(0)
+                    .unwrap()
+                    .with_transport(network.transport())
+                    .with_config(config.clone())
+            })
+            .collect();
+        for node in &nodes {
+            node.start().unwrap();
+        }
+        let mut inbound = nodes[1].take_inbound().unwrap();
+        
+        // Nodes joining through the first one find each other through discovery
+        let address = nodes[0].local_addr().unwrap().to_string();
+        assert!(address.starts_with("10.0.0."));
+        nodes[1].connect_async(&address).await.unwrap();
+        nodes[2].connect_async(&address).await.unwrap();
+        assert!(wait_for(|| nodes.iter().all(|node| node.get_peers().len() == 2)).await);
+        
+        nodes[0].broadcast(b"hello").unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(5), inbound.recv()).await.unwrap().unwrap();
+        assert_eq!(message.data, b"hello");
+        assert_eq!(message.peer, nodes[0].public_key());
+        
+        // Nothing listens at an unknown address
+        assert!(nodes[0].connect_async("10.0.0.99:1").await.is_err());
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_latency_and_disconnects_are_injected() {
+        let network = MemoryNetwork::new();
+        let (first, second, third) = (network.transport(), network.transport(), network.transport());
+        let (first_host, second_host, third_host) = (first.host(), second.host(), third.host());
+        let listener = P2PNetwork::new(0).unwrap().with_transport(first);
+        listener.start().unwrap();
+        let dialer = P2PNetwork::new(0).unwrap().with_transport(second);
+        let other = P2PNetwork::new(0).unwrap().with_transport(third);
+        let mut inbound = listener.take_inbound().unwrap();
+        
+        let address = listener.local_addr().unwrap().to_string();
+        let peer = dialer.connect_async(&address).await.unwrap();
+        let other_peer = other.connect_async(&address).await.unwrap();
+        assert!(wait_for(|| listener.get_peers().len() == 2).await);
+        
+        // Messages take the injected latency to arrive
+        network.set_latency(Duration::from_millis(200));
+        let start = Instant::now();
+        dialer.send(&peer, b"slow").unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(5), inbound.recv()).await.unwrap().unwrap();
+        assert_eq!(message.data, b"slow");
+        assert!(start.elapsed() >= Duration::from_millis(200) && start.elapsed() < Duration::from_millis(250));
+        
+        // A link's own latency takes the place of the network's
+        network.set_link_latency(third_host, first_host, Duration::from_millis(500));
+        let start = Instant::now();
+        other.send(&other_peer, b"slower").unwrap();
+        dialer.send(&peer, b"slow").unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(5), inbound.recv()).await.unwrap().unwrap();
+        assert_eq!((message.data.as_slice(), message.peer), (b"slow".as_slice(), dialer.public_key()));
+        let message = tokio::time::timeout(Duration::from_secs(5), inbound.recv()).await.unwrap().unwrap();
+        assert_eq!(message.data, b"slower");
+        assert!(start.elapsed() >= Duration::from_millis(500) && start.elapsed() < Duration::from_millis(550));
+        
+        // Cutting the connection drops the peer at both ends
+        network.set_latency(Duration::ZERO);
+        assert_eq!(network.disconnect(second_host, first_host), 1);
+        assert!(wait_for(|| !listener.get_peers().contains(&dialer.public_key()) && dialer.get_peers().is_empty()).await);
+        assert_eq!(network.isolate(second_host), 0);
+        assert_eq!(network.isolate(third_host), 1);
+    }
+    
+    /// Publish gossip to one of three peers and return which one got it
+    async fn gossip_target(seed: u64) -> usize {
+        let network = MemoryNetwork::new();
+        let config = NetworkConfig { target_outbound: 0, ..NetworkConfig::default() };
+        let peers: Vec<P2PNetwork> = (1..=3u8)
+            .map(|key| {
+                P2PNetwork::new(0)
+                    .unwrap()
+                    .with_node_key(KeyPair::from_seed(&[key; 32]).unwrap())
+                    .with_transport(network.transport())
+                    .with_config(config.clone())
+            })
+            .collect();
+        let mut received: Vec<_> = peers.iter().map(|peer| peer.take_gossip().unwrap()).collect();
+        
+        let publisher = P2PNetwork::new(0)
+            .unwrap()
+            .with_transport(network.transport())
+            .with_config(NetworkConfig { gossip_fanout: 1, ..NetworkConfig::default() })
+            .with_rng(StdRng::seed_from_u64(seed));
+        for peer in &peers {
+            peer.start().unwrap();
+            publisher.connect_async(&peer.local_addr().unwrap().to_string()).await.unwrap();
+        }
+        
+        publisher.publish(Topic::Transactions, b"transaction").unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let targets: Vec<usize> = (0..peers.len()).filter(|index| received[*index].try_recv().is_ok()).collect();
+        assert_eq!(targets.len(), 1);
+        targets[0]
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_gossip_targets_follow_the_injected_rng() {
+        let mut targets = Vec::new();
+        for seed in 0..8 {
+            let target = gossip_target(seed).await;
+            assert_eq!(gossip_target(seed).await, target);
+            targets.push(target);
+        }
+        
+        // The seed, not the order peers connected in, decides where gossip goes
+        assert!(targets.iter().any(|target| *target != targets[0]), "{:?}", targets);
+    }
+}
@@ -0,0 +1,99 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use serde_json::json;
+    use hypernova_core::{
+        chain::Blockchain,
+        consensus::{Consensus, DelegatedProofOfStake},
+        crypto::KeyPair,
+        rpc::{RpcRequest, RpcServer},
+        spec::ChainSpec,
+        staking::ValidatorStatus,
+        transaction::{Transaction, TransactionType},
+    };
+    use crate::common;
+    
+    fn registration_tx(address: &str, keypair: &KeyPair, self_stake: u64) -> Transaction {
+        common::registration_tx(address, keypair.public_key_hex(), self_stake)
+    }
+    
+    fn produce(blockchain: &Blockchain, producer: &str, keypair: &KeyPair, transactions: Vec<Transaction>) -> Result<(), Box<dyn std::error::Error>> {
+        let mut block = blockchain.build_block(common::with_nonces(blockchain, transactions))?;
+        block.sign_as(producer, keypair);
+        blockchain.add_block(block)
+    }
+    
+    #[test]
+    fn test_validator_missing_slots_is_jailed_until_unjail() {
+        let spec = ChainSpec {
+            epoch_length: 2,
+            min_self_stake: 1000,
+            jail_period: 2,
+            genesis_accounts: vec![
+                common::genesis_account("validator1", 5000),
+                common::genesis_account("validator2", 5000),
+            ],
+            ..ChainSpec::default()
+        };
+        let blockchain = Arc::new(common::test_chain("uptime_jail", spec));
+        
+        let key1 = KeyPair::generate().unwrap();
+        let key2 = KeyPair::generate().unwrap();
+        
+        // Both validators are elected for epoch 1
+        produce(&blockchain, "validator1", &key1, vec![
+            registration_tx("validator1", &key1, 3000),
+            registration_tx("validator2", &key2, 2000),
+        ]).unwrap();
+        
+        let dpos = DelegatedProofOfStake::new(21, 1000);
+        dpos.refresh_validators(&blockchain).unwrap();
+        let mut validators = dpos.get_validators();
+        validators.sort();
+        assert_eq!(validators, vec!["validator1".to_string(), "validator2".to_string()]);
+        
+        // One of the epoch's two slots is validator2's, but validator1 signs both blocks
+        let mut claimed = false;
+        for _ in 0..2 {
+            let mut forged = blockchain.build_block(Vec::new()).unwrap();
+            let scheduled = blockchain.get_scheduled_producer_of(&forged.header).unwrap().address;
+            
+            // A block naming validator2 as producer counts for it only with its signature
+            forged.sign_as(&scheduled, &key1);
+            assert_eq!(dpos.validate_block(&forged, &blockchain).is_ok(), scheduled == "validator1");
+            claimed |= scheduled == "validator2";
+            blockchain.add_block(forged).unwrap();
+        }
+        assert!(claimed);
+        
+        let record = blockchain.get_validator("validator2").unwrap();
+        assert_eq!(record.status, ValidatorStatus::Jailed);
+        assert_eq!(record.jailed_until, 5);
+        
+        dpos.refresh_validators(&blockchain).unwrap();
+        assert_eq!(dpos.get_validators(), vec!["validator1".to_string()]);
+        
+        // Uptime is reported over RPC
+        let rpc = RpcServer::new(blockchain.clone(), 0);
+        let response = rpc.handle(RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "hnc_getValidatorUptime".to_string(),
+            params: json!(["validator2"]),
+            id: json!(1),
+        });
+        let result = response.result.unwrap();
+        assert_eq!(result["history"][0]["epoch"], 1);
+        assert_eq!(result["history"][0]["stats"]["missed"], 1);
+        assert_eq!(result["history"][0]["uptime_bps"], 0);
+        
+        // Unjailing is only possible once the jail period is over
+        let unjail = common::signed(Transaction::new(TransactionType::Unjail, "validator2".to_string(), None, None, None, 0, 0));
+        assert!(produce(&blockchain, "validator1", &key1, vec![unjail.clone()]).is_err());
+        produce(&blockchain, "validator1", &key1, Vec::new()).unwrap();
+        produce(&blockchain, "validator1", &key1, vec![unjail]).unwrap();
+        
+        assert_eq!(blockchain.get_validator("validator2").unwrap().status, ValidatorStatus::Active);
+    }
+}